@@ -1,14 +1,53 @@
 use std::fmt;
 
+use serde::Serialize;
+use utoipa::ToSchema;
 use warp::{Rejection, Reply};
 use warp::body::BodyDeserializeError;
 use warp::cors::CorsForbidden;
 use warp::http::StatusCode;
 use warp::reject::Reject;
 
+pub mod responses;
+
 #[derive(Debug)]
 pub struct InvalidId;
 
+/// Error response returned by an APILayer service
+#[derive(Debug, Clone)]
+pub struct APILayerError {
+    pub status: u16,
+    pub message: String,
+}
+
+/// Request refused because the client used up its quota on the route
+#[derive(Debug, Clone)]
+pub struct RateLimited {
+    /// Requests allowed per period on the route
+    pub limit: u32,
+    /// Seconds until the client can send another request
+    pub retry_after: u64,
+    /// Seconds until the quota is full again
+    pub reset: u64,
+}
+
+/// Field of a request body refused by the domain rules
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    #[schema(example = "title")]
+    pub field: &'static str,
+    #[schema(example = "Must be between 10 and 255 characters, got 3")]
+    pub message: String,
+}
+
+/// Body of the 422 response listing every invalid field
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ValidationFailure {
+    #[schema(example = "Validation failed")]
+    pub message: &'static str,
+    pub errors: Vec<FieldError>,
+}
+
 #[derive(Debug)]
 pub enum Error {
     CORSForbidden(CorsForbidden),
@@ -16,9 +55,43 @@ pub enum Error {
     ParseError(std::num::ParseIntError),
     InvalidId(InvalidId),
     MissingParameters,
+    InvalidParameter(String),
     StartGreaterThanEnd,
     QuestionNotFound,
     QuestionAlreadyExists,
+    QuestionClosed,
+    QuestionLocked,
+    AnswerNotFound,
+    CommentNotFound,
+    AttachmentNotFound,
+    BookmarkNotFound,
+    SubscriptionNotFound,
+    NotificationNotFound,
+    WebhookNotFound,
+    FlagNotFound,
+    AlreadyFlagged,
+    RevisionNotFound,
+    AccountAlreadyExists,
+    AccountNotFound,
+    WrongPassword,
+    InvalidResetToken,
+    Unauthorized,
+    Forbidden,
+    SelfVote,
+    TooManyRequests(RateLimited),
+    /// Body longer than the route's limit, in bytes
+    PayloadTooLarge(u64),
+    LengthRequired,
+    /// Requested range outside of the file, whose size in bytes is given
+    RangeNotSatisfiable(u64),
+    ValidationError(String),
+    InvalidFields(Vec<FieldError>),
+    ArgonLibraryError(argon2::Error),
+    ExternalAPIError(reqwest::Error),
+    ClientError(APILayerError),
+    ServerError(APILayerError),
+    MailError(String),
+    StorageError(String),
     DatabaseQueryError,
 }
 
@@ -30,6 +103,24 @@ impl fmt::Display for InvalidId {
 
 impl Reject for InvalidId {}
 
+impl fmt::Display for APILayerError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Status: {}, Message: {}", self.status, self.message)
+    }
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Limit: {}, Retry after: {}s", self.limit, self.retry_after)
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}: {}", self.field, self.message)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -40,9 +131,50 @@ impl fmt::Display for Error {
             Error::ParseError(error) => write!(formatter, "Parse error: {}", error),
             Error::InvalidId(error) => write!(formatter, "Invalid id: {}", error),
             Error::MissingParameters => write!(formatter, "Missing parameters"),
+            Error::InvalidParameter(error) => write!(formatter, "Invalid parameter: {}", error),
             Error::StartGreaterThanEnd => write!(formatter, "Start cannot be greater than end"),
             Error::QuestionNotFound => write!(formatter, "Question not found"),
             Error::QuestionAlreadyExists => write!(formatter, "Question already exists"),
+            Error::QuestionClosed => write!(formatter, "Question is closed to new answers"),
+            Error::QuestionLocked => write!(formatter, "Question is locked by moderators"),
+            Error::AnswerNotFound => write!(formatter, "Answer not found"),
+            Error::CommentNotFound => write!(formatter, "Comment not found"),
+            Error::AttachmentNotFound => write!(formatter, "Attachment not found"),
+            Error::BookmarkNotFound => write!(formatter, "Bookmark not found"),
+            Error::SubscriptionNotFound => write!(formatter, "Subscription not found"),
+            Error::NotificationNotFound => write!(formatter, "Notification not found"),
+            Error::WebhookNotFound => write!(formatter, "Webhook not found"),
+            Error::FlagNotFound => write!(formatter, "No open flags on this content"),
+            Error::AlreadyFlagged => write!(formatter, "Content already flagged"),
+            Error::RevisionNotFound => write!(formatter, "Revision not found"),
+            Error::AccountAlreadyExists => write!(formatter, "Account already exists"),
+            Error::AccountNotFound => write!(formatter, "Account not found"),
+            Error::WrongPassword => write!(formatter, "Wrong password"),
+            Error::InvalidResetToken => write!(formatter, "Invalid or expired reset token"),
+            Error::Unauthorized => write!(formatter, "No valid session provided"),
+            Error::Forbidden => write!(formatter, "Not allowed to perform this action"),
+            Error::SelfVote => write!(formatter, "Cannot vote on own content"),
+            Error::TooManyRequests(error) => write!(formatter, "Too many requests: {}", error),
+            Error::PayloadTooLarge(limit) => {
+                write!(formatter, "Body larger than {} bytes", limit)
+            }
+            Error::LengthRequired => write!(formatter, "Missing Content-Length"),
+            Error::RangeNotSatisfiable(size) => {
+                write!(formatter, "Range not satisfiable for {} bytes", size)
+            }
+            Error::ValidationError(error) => write!(formatter, "Validation error: {}", error),
+            Error::InvalidFields(errors) => {
+                let errors: Vec<String> = errors.iter().map(FieldError::to_string).collect();
+                write!(formatter, "Invalid fields: {}", errors.join(", "))
+            }
+            Error::ArgonLibraryError(error) => {
+                write!(formatter, "Cannot verify password: {}", error)
+            }
+            Error::ExternalAPIError(error) => write!(formatter, "External API error: {}", error),
+            Error::ClientError(error) => write!(formatter, "External client error: {}", error),
+            Error::ServerError(error) => write!(formatter, "External server error: {}", error),
+            Error::MailError(error) => write!(formatter, "Mail error: {}", error),
+            Error::StorageError(error) => write!(formatter, "Storage error: {}", error),
             Error::DatabaseQueryError => write!(formatter, "Query could not be executed"),
         }
     }
@@ -51,7 +183,18 @@ impl fmt::Display for Error {
 impl Reject for Error {}
 
 pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
-    match r.find::<Error>() {
+    if let Some(Error::InvalidFields(errors)) = r.find::<Error>() {
+        let body = ValidationFailure {
+            message: "Validation failed",
+            errors: errors.clone(),
+        };
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&body),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        )
+        .into_response());
+    }
+    let reply: Result<_, Rejection> = match r.find::<Error>() {
         Some(Error::CORSForbidden(error)) => Ok(warp::reply::with_status(
             error.to_string(),
             StatusCode::FORBIDDEN,
@@ -68,6 +211,10 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "Missing parameters".to_string(),
             StatusCode::BAD_REQUEST,
         )),
+        Some(Error::InvalidParameter(error)) => Ok(warp::reply::with_status(
+            format!("Invalid parameter: {}", error),
+            StatusCode::BAD_REQUEST,
+        )),
         Some(Error::StartGreaterThanEnd) => Ok(warp::reply::with_status(
             "Start cannot be greater than end".to_string(),
             StatusCode::BAD_REQUEST,
@@ -84,6 +231,114 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "Question already exists".to_string(),
             StatusCode::CONFLICT,
         )),
+        Some(Error::QuestionClosed) => Ok(warp::reply::with_status(
+            "Question is closed to new answers".to_string(),
+            StatusCode::CONFLICT,
+        )),
+        Some(Error::QuestionLocked) => Ok(warp::reply::with_status(
+            "Question is locked by moderators".to_string(),
+            StatusCode::CONFLICT,
+        )),
+        Some(Error::AnswerNotFound) => Ok(warp::reply::with_status(
+            "Answer not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::CommentNotFound) => Ok(warp::reply::with_status(
+            "Comment not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::AttachmentNotFound) => Ok(warp::reply::with_status(
+            "Attachment not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::BookmarkNotFound) => Ok(warp::reply::with_status(
+            "Bookmark not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::SubscriptionNotFound) => Ok(warp::reply::with_status(
+            "Subscription not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::NotificationNotFound) => Ok(warp::reply::with_status(
+            "Notification not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::WebhookNotFound) => Ok(warp::reply::with_status(
+            "Webhook not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::FlagNotFound) => Ok(warp::reply::with_status(
+            "No open flags on this content".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::AlreadyFlagged) => Ok(warp::reply::with_status(
+            "Content already flagged".to_string(),
+            StatusCode::CONFLICT,
+        )),
+        Some(Error::RevisionNotFound) => Ok(warp::reply::with_status(
+            "Revision not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::AccountAlreadyExists) => Ok(warp::reply::with_status(
+            "Account already exists".to_string(),
+            StatusCode::CONFLICT,
+        )),
+        Some(Error::AccountNotFound) => Ok(warp::reply::with_status(
+            "Account not found".to_string(),
+            StatusCode::NOT_FOUND,
+        )),
+        Some(Error::WrongPassword) => Ok(warp::reply::with_status(
+            "Wrong e-mail/password combination".to_string(),
+            StatusCode::UNAUTHORIZED,
+        )),
+        Some(Error::InvalidResetToken) => Ok(warp::reply::with_status(
+            "Invalid or expired reset token".to_string(),
+            StatusCode::BAD_REQUEST,
+        )),
+        Some(Error::Unauthorized) => Ok(warp::reply::with_status(
+            "No valid session provided".to_string(),
+            StatusCode::UNAUTHORIZED,
+        )),
+        Some(Error::Forbidden) => Ok(warp::reply::with_status(
+            "Not allowed to perform this action".to_string(),
+            StatusCode::FORBIDDEN,
+        )),
+        Some(Error::SelfVote) => Ok(warp::reply::with_status(
+            "Cannot vote on own content".to_string(),
+            StatusCode::FORBIDDEN,
+        )),
+        Some(Error::TooManyRequests(_)) => Ok(warp::reply::with_status(
+            "Too many requests".to_string(),
+            StatusCode::TOO_MANY_REQUESTS,
+        )),
+        Some(Error::PayloadTooLarge(limit)) => Ok(warp::reply::with_status(
+            format!("Body cannot be larger than {} bytes", limit),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        )),
+        Some(Error::LengthRequired) => Ok(warp::reply::with_status(
+            "Content-Length required".to_string(),
+            StatusCode::LENGTH_REQUIRED,
+        )),
+        Some(Error::RangeNotSatisfiable(_)) => Ok(warp::reply::with_status(
+            "Range not satisfiable".to_string(),
+            StatusCode::RANGE_NOT_SATISFIABLE,
+        )),
+        Some(Error::ValidationError(error)) => Ok(warp::reply::with_status(
+            error.to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        )),
+        Some(Error::ArgonLibraryError(_error)) => Ok(warp::reply::with_status(
+            "Cannot verify password".to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )),
+        Some(Error::ExternalAPIError(_))
+        | Some(Error::ClientError(_))
+        | Some(Error::ServerError(_))
+        | Some(Error::MailError(_))
+        | Some(Error::StorageError(_)) => Ok(warp::reply::with_status(
+            "Internal server error".to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )),
         Some(Error::DatabaseQueryError) => Ok(warp::reply::with_status(
             "Query could not be executed".to_string(),
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -96,5 +351,22 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
                 StatusCode::NOT_FOUND,
             ))
         }
+    };
+    let mut response = reply?.into_response();
+    match r.find::<Error>() {
+        Some(Error::TooManyRequests(limited)) => {
+            let headers = response.headers_mut();
+            headers.insert("RateLimit-Limit", limited.limit.into());
+            headers.insert("RateLimit-Remaining", 0.into());
+            headers.insert("RateLimit-Reset", limited.reset.into());
+            headers.insert("Retry-After", limited.retry_after.into());
+        }
+        Some(Error::RangeNotSatisfiable(size)) => {
+            if let Ok(value) = format!("bytes */{}", size).parse() {
+                response.headers_mut().insert("Content-Range", value);
+            }
+        }
+        _ => {}
     }
+    Ok(response)
 }
@@ -0,0 +1,53 @@
+//! Error responses shared by many routes, as described in the OpenAPI document. Bodies
+//! are plain text, apart from the list of invalid fields.
+
+use utoipa::ToResponse;
+
+use crate::ValidationFailure;
+
+/// Missing or malformed query parameter
+#[derive(ToResponse)]
+#[response(example = json!("Missing parameters"))]
+pub struct BadRequest(pub String);
+
+/// No valid session token in the `Authorization` header
+#[derive(ToResponse)]
+#[response(example = json!("No valid session provided"))]
+pub struct Unauthorized(pub String);
+
+/// Session not allowed to act on this content
+#[derive(ToResponse)]
+#[response(example = json!("Not allowed to perform this action"))]
+pub struct Forbidden(pub String);
+
+/// Body sent without a `Content-Length` header
+#[derive(ToResponse)]
+#[response(example = json!("Content-Length required"))]
+pub struct LengthRequired(pub String);
+
+/// Body longer than the route's limit
+#[derive(ToResponse)]
+#[response(example = json!("Body cannot be larger than 16384 bytes"))]
+pub struct PayloadTooLarge(pub String);
+
+/// Some fields of the body were refused, all of them being listed
+#[derive(ToResponse)]
+pub struct InvalidFields(pub ValidationFailure);
+
+/// Body refused by the domain rules, with the reason
+#[derive(ToResponse)]
+#[response(example = json!("Validation error: Comment too short"))]
+pub struct ValidationError(pub String);
+
+/// Quota of the route used up
+#[derive(ToResponse)]
+#[response(
+    example = json!("Too many requests"),
+    headers(
+        ("RateLimit-Limit" = u32, description = "Requests allowed per period"),
+        ("RateLimit-Remaining" = u32, description = "Requests left in the period"),
+        ("RateLimit-Reset" = u64, description = "Seconds until the quota is full again"),
+        ("Retry-After" = u64, description = "Seconds until another request is accepted")
+    )
+)]
+pub struct TooManyRequests(pub String);
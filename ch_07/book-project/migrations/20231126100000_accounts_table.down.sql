@@ -0,0 +1,9 @@
+-- Add down migration script here
+ALTER TABLE answers
+    DROP COLUMN IF EXISTS account_id;
+
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS account_id;
+
+DROP TABLE IF EXISTS sessions;
+DROP TABLE IF EXISTS accounts;
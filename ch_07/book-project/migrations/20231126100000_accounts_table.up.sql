@@ -0,0 +1,22 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS accounts
+(
+    id         SERIAL PRIMARY KEY,
+    email      VARCHAR(255) NOT NULL UNIQUE,
+    password   VARCHAR(255) NOT NULL,
+    role       VARCHAR(16)  NOT NULL DEFAULT 'user',
+    created_on TIMESTAMP    NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS sessions
+(
+    token      VARCHAR(64) PRIMARY KEY,
+    account_id INTEGER   NOT NULL REFERENCES accounts (id),
+    expires_on TIMESTAMP NOT NULL
+);
+
+ALTER TABLE questions
+    ADD COLUMN account_id INTEGER REFERENCES accounts (id);
+
+ALTER TABLE answers
+    ADD COLUMN account_id INTEGER REFERENCES accounts (id);
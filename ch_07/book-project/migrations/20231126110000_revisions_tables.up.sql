@@ -0,0 +1,33 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS question_revisions
+(
+    id          SERIAL PRIMARY KEY,
+    question_id INTEGER      NOT NULL REFERENCES questions (id),
+    revision    INTEGER      NOT NULL,
+    title       VARCHAR(255) NOT NULL,
+    content     TEXT         NOT NULL,
+    tags        TEXT[],
+    account_id  INTEGER REFERENCES accounts (id),
+    created_on  TIMESTAMP    NOT NULL DEFAULT NOW(),
+    UNIQUE (question_id, revision)
+);
+
+CREATE TABLE IF NOT EXISTS answer_revisions
+(
+    id         SERIAL PRIMARY KEY,
+    answer_id  INTEGER   NOT NULL REFERENCES answers (id),
+    revision   INTEGER   NOT NULL,
+    content    TEXT      NOT NULL,
+    account_id INTEGER REFERENCES accounts (id),
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (answer_id, revision)
+);
+
+-- Existing content becomes the first revision of its history
+INSERT INTO question_revisions (question_id, revision, title, content, tags, account_id, created_on)
+SELECT id, 1, title, content, tags, account_id, created_on
+FROM questions;
+
+INSERT INTO answer_revisions (answer_id, revision, content, account_id)
+SELECT id, 1, content, account_id
+FROM answers;
@@ -0,0 +1,23 @@
+-- Add down migration script here
+ALTER TABLE answer_revisions
+    DROP CONSTRAINT answer_revisions_answer_id_fkey,
+    ADD CONSTRAINT answer_revisions_answer_id_fkey
+        FOREIGN KEY (answer_id) REFERENCES answers (id);
+
+ALTER TABLE question_revisions
+    DROP CONSTRAINT question_revisions_question_id_fkey,
+    ADD CONSTRAINT question_revisions_question_id_fkey
+        FOREIGN KEY (question_id) REFERENCES questions (id);
+
+ALTER TABLE answers
+    DROP CONSTRAINT answers_question_id_fkey,
+    ADD CONSTRAINT answers_question_id_fkey
+        FOREIGN KEY (question_id) REFERENCES questions (id);
+
+ALTER TABLE answers
+    DROP COLUMN IF EXISTS deleted_by,
+    DROP COLUMN IF EXISTS deleted_at;
+
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS deleted_by,
+    DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+ALTER TABLE questions
+    ADD COLUMN deleted_at TIMESTAMP,
+    ADD COLUMN deleted_by INTEGER REFERENCES accounts (id);
+
+ALTER TABLE answers
+    ADD COLUMN deleted_at TIMESTAMP,
+    ADD COLUMN deleted_by INTEGER REFERENCES accounts (id);
+
+-- Purging a question permanently removes everything attached to it
+ALTER TABLE answers
+    DROP CONSTRAINT answers_question_id_fkey,
+    ADD CONSTRAINT answers_question_id_fkey
+        FOREIGN KEY (question_id) REFERENCES questions (id) ON DELETE CASCADE;
+
+ALTER TABLE question_revisions
+    DROP CONSTRAINT question_revisions_question_id_fkey,
+    ADD CONSTRAINT question_revisions_question_id_fkey
+        FOREIGN KEY (question_id) REFERENCES questions (id) ON DELETE CASCADE;
+
+ALTER TABLE answer_revisions
+    DROP CONSTRAINT answer_revisions_answer_id_fkey,
+    ADD CONSTRAINT answer_revisions_answer_id_fkey
+        FOREIGN KEY (answer_id) REFERENCES answers (id) ON DELETE CASCADE;
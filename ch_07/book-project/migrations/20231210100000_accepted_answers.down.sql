@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS accepted_answer_id;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS votes;
+
+ALTER TABLE answers
+    DROP COLUMN IF EXISTS score;
+
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS score;
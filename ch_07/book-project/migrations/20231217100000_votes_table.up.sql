@@ -0,0 +1,20 @@
+-- Add up migration script here
+ALTER TABLE questions
+    ADD COLUMN score INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE answers
+    ADD COLUMN score INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS votes
+(
+    id          SERIAL PRIMARY KEY,
+    account_id  INTEGER   NOT NULL REFERENCES accounts (id),
+    question_id INTEGER REFERENCES questions (id) ON DELETE CASCADE,
+    answer_id   INTEGER REFERENCES answers (id) ON DELETE CASCADE,
+    value       SMALLINT  NOT NULL CHECK (value IN (-1, 1)),
+    created_on  TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+
+CREATE UNIQUE INDEX votes_account_question_idx ON votes (account_id, question_id) WHERE question_id IS NOT NULL;
+CREATE UNIQUE INDEX votes_account_answer_idx ON votes (account_id, answer_id) WHERE answer_id IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS reputation_events;
+
+ALTER TABLE accounts
+    DROP COLUMN IF EXISTS reputation;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+ALTER TABLE accounts
+    ADD COLUMN reputation INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS reputation_events
+(
+    id          SERIAL PRIMARY KEY,
+    account_id  INTEGER     NOT NULL REFERENCES accounts (id),
+    reason      VARCHAR(32) NOT NULL,
+    points      INTEGER     NOT NULL,
+    question_id INTEGER REFERENCES questions (id) ON DELETE SET NULL,
+    answer_id   INTEGER REFERENCES answers (id) ON DELETE SET NULL,
+    created_on  TIMESTAMP   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX reputation_events_account_idx ON reputation_events (account_id);
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS comments
+(
+    id          SERIAL PRIMARY KEY,
+    question_id INTEGER REFERENCES questions (id) ON DELETE CASCADE,
+    answer_id   INTEGER REFERENCES answers (id) ON DELETE CASCADE,
+    content     VARCHAR(600) NOT NULL,
+    account_id  INTEGER REFERENCES accounts (id),
+    created_on  TIMESTAMP    NOT NULL DEFAULT NOW(),
+    updated_on  TIMESTAMP,
+    deleted_at  TIMESTAMP,
+    deleted_by  INTEGER REFERENCES accounts (id),
+    CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+
+CREATE INDEX comments_question_idx ON comments (question_id);
+CREATE INDEX comments_answer_idx ON comments (answer_id);
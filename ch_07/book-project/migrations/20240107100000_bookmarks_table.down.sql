@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS bookmarks;
+
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS bookmark_count;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+ALTER TABLE questions
+    ADD COLUMN bookmark_count INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS bookmarks
+(
+    id          SERIAL PRIMARY KEY,
+    account_id  INTEGER   NOT NULL REFERENCES accounts (id),
+    question_id INTEGER   NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    collection  VARCHAR(64),
+    created_on  TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (account_id, question_id)
+);
+
+CREATE INDEX bookmarks_account_collection_idx ON bookmarks (account_id, collection);
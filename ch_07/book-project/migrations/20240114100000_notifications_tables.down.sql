@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS notifications;
+
+DROP TABLE IF EXISTS subscriptions;
@@ -0,0 +1,30 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS subscriptions
+(
+    id          SERIAL PRIMARY KEY,
+    account_id  INTEGER   NOT NULL REFERENCES accounts (id),
+    question_id INTEGER REFERENCES questions (id) ON DELETE CASCADE,
+    tag         VARCHAR(64),
+    created_on  TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK ((question_id IS NULL) <> (tag IS NULL))
+);
+
+CREATE UNIQUE INDEX subscriptions_account_question_idx ON subscriptions (account_id, question_id) WHERE question_id IS NOT NULL;
+CREATE UNIQUE INDEX subscriptions_account_tag_idx ON subscriptions (account_id, tag) WHERE tag IS NOT NULL;
+CREATE INDEX subscriptions_question_idx ON subscriptions (question_id);
+CREATE INDEX subscriptions_tag_idx ON subscriptions (tag);
+
+CREATE TABLE IF NOT EXISTS notifications
+(
+    id          SERIAL PRIMARY KEY,
+    account_id  INTEGER     NOT NULL REFERENCES accounts (id),
+    kind        VARCHAR(32) NOT NULL,
+    actor_id    INTEGER REFERENCES accounts (id),
+    question_id INTEGER     NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    answer_id   INTEGER REFERENCES answers (id) ON DELETE CASCADE,
+    comment_id  INTEGER REFERENCES comments (id) ON DELETE CASCADE,
+    read_on     TIMESTAMP,
+    created_on  TIMESTAMP   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX notifications_account_idx ON notifications (account_id, id);
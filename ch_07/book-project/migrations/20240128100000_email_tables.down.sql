@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS password_resets;
+
+DROP TABLE IF EXISTS email_preferences;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS email_preferences
+(
+    account_id     INTEGER PRIMARY KEY REFERENCES accounts (id),
+    new_answers    BOOLEAN     NOT NULL DEFAULT TRUE,
+    digest         VARCHAR(16) NOT NULL DEFAULT 'off',
+    last_digest_on TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS password_resets
+(
+    token      VARCHAR(64) PRIMARY KEY,
+    account_id INTEGER   NOT NULL REFERENCES accounts (id),
+    expires_on TIMESTAMP NOT NULL,
+    used_on    TIMESTAMP,
+    created_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX questions_title_trgm_idx ON questions USING GIN (title gin_trgm_ops);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS duplicate_of,
+    DROP COLUMN IF EXISTS close_reason,
+    DROP COLUMN IF EXISTS status;
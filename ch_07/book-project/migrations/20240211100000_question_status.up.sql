@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE questions
+    ADD COLUMN status       VARCHAR(16) NOT NULL DEFAULT 'open',
+    ADD COLUMN close_reason VARCHAR(16),
+    ADD COLUMN duplicate_of INTEGER REFERENCES questions (id) ON DELETE SET NULL;
@@ -0,0 +1,13 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS moderation_log;
+
+DROP TABLE IF EXISTS flags;
+
+ALTER TABLE comments
+    DROP COLUMN IF EXISTS hidden;
+
+ALTER TABLE answers
+    DROP COLUMN IF EXISTS hidden;
+
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS hidden;
@@ -0,0 +1,44 @@
+-- Add up migration script here
+ALTER TABLE questions
+    ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE answers
+    ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE comments
+    ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS flags
+(
+    id          SERIAL PRIMARY KEY,
+    account_id  INTEGER     NOT NULL REFERENCES accounts (id),
+    question_id INTEGER REFERENCES questions (id) ON DELETE CASCADE,
+    answer_id   INTEGER REFERENCES answers (id) ON DELETE CASCADE,
+    comment_id  INTEGER REFERENCES comments (id) ON DELETE CASCADE,
+    reason      VARCHAR(16) NOT NULL,
+    severity    INTEGER     NOT NULL,
+    note        TEXT,
+    status      VARCHAR(16) NOT NULL DEFAULT 'open',
+    created_on  TIMESTAMP   NOT NULL DEFAULT NOW(),
+    resolved_on TIMESTAMP,
+    CHECK (num_nonnulls(question_id, answer_id, comment_id) = 1)
+);
+
+CREATE UNIQUE INDEX flags_open_question_idx ON flags (account_id, question_id) WHERE status = 'open' AND question_id IS NOT NULL;
+CREATE UNIQUE INDEX flags_open_answer_idx ON flags (account_id, answer_id) WHERE status = 'open' AND answer_id IS NOT NULL;
+CREATE UNIQUE INDEX flags_open_comment_idx ON flags (account_id, comment_id) WHERE status = 'open' AND comment_id IS NOT NULL;
+CREATE INDEX flags_status_idx ON flags (status);
+
+CREATE TABLE IF NOT EXISTS moderation_log
+(
+    id             SERIAL PRIMARY KEY,
+    moderator_id   INTEGER     NOT NULL REFERENCES accounts (id),
+    author_id      INTEGER REFERENCES accounts (id),
+    question_id    INTEGER REFERENCES questions (id) ON DELETE SET NULL,
+    answer_id      INTEGER REFERENCES answers (id) ON DELETE SET NULL,
+    comment_id     INTEGER REFERENCES comments (id) ON DELETE SET NULL,
+    action         VARCHAR(16) NOT NULL,
+    note           TEXT,
+    flags_resolved INTEGER     NOT NULL,
+    created_on     TIMESTAMP   NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS answers_updated_on ON answers;
+
+DROP TRIGGER IF EXISTS questions_updated_on ON questions;
+
+DROP FUNCTION IF EXISTS set_updated_on();
+
+ALTER TABLE answers
+    DROP COLUMN IF EXISTS updated_on;
+
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS updated_on;
@@ -0,0 +1,23 @@
+-- Add up migration script here
+ALTER TABLE questions
+    ADD COLUMN IF NOT EXISTS updated_on TIMESTAMP NOT NULL DEFAULT NOW();
+
+UPDATE questions SET updated_on = created_on;
+
+ALTER TABLE answers
+    ADD COLUMN IF NOT EXISTS updated_on TIMESTAMP NOT NULL DEFAULT NOW();
+
+CREATE OR REPLACE FUNCTION set_updated_on() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_on = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER questions_updated_on
+    BEFORE UPDATE ON questions
+    FOR EACH ROW EXECUTE FUNCTION set_updated_on();
+
+CREATE TRIGGER answers_updated_on
+    BEFORE UPDATE ON answers
+    FOR EACH ROW EXECUTE FUNCTION set_updated_on();
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS attachments
+(
+    id           SERIAL PRIMARY KEY,
+    question_id  INTEGER REFERENCES questions (id) ON DELETE CASCADE,
+    answer_id    INTEGER REFERENCES answers (id) ON DELETE CASCADE,
+    account_id   INTEGER REFERENCES accounts (id),
+    file_name    VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size         BIGINT       NOT NULL,
+    blob_key     VARCHAR(64)  NOT NULL UNIQUE,
+    created_on   TIMESTAMP    NOT NULL DEFAULT NOW(),
+    CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+
+CREATE INDEX attachments_question_idx ON attachments (question_id);
+CREATE INDEX attachments_answer_idx ON attachments (answer_id);
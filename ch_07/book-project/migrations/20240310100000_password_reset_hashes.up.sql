@@ -0,0 +1,2 @@
+-- Add up migration script here
+UPDATE password_resets SET token = encode(sha256(convert_to(token, 'UTF8')), 'hex');
@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TABLE questions
+    ADD COLUMN IF NOT EXISTS content_html TEXT;
+
+ALTER TABLE answers
+    ADD COLUMN IF NOT EXISTS content_html TEXT;
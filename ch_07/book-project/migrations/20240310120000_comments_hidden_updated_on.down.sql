@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS comments_hidden_updated_on ON comments;
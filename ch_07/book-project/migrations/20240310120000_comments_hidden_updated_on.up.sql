@@ -0,0 +1,6 @@
+-- Add up migration script here
+CREATE TRIGGER comments_hidden_updated_on
+    BEFORE UPDATE OF hidden ON comments
+    FOR EACH ROW
+    WHEN (OLD.hidden IS DISTINCT FROM NEW.hidden)
+    EXECUTE FUNCTION set_updated_on();
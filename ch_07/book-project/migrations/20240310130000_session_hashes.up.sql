@@ -0,0 +1,2 @@
+-- Add up migration script here
+UPDATE sessions SET token = encode(sha256(convert_to(token, 'UTF8')), 'hex');
@@ -0,0 +1,156 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use errors::FieldError;
+
+use crate::domain::validation::Validator;
+
+/// Longest e-mail accepted, matching the `accounts.email` column
+pub const EMAIL_MAX_LENGTH: usize = 255;
+/// Shortest password accepted, in characters
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+/// Longest password accepted, in characters
+pub const PASSWORD_MAX_LENGTH: usize = 128;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct Account {
+    pub id: AccountId,
+    pub email: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
+pub struct AccountId(pub i32);
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct AccountDraft {
+    pub email: String,
+    pub password: String,
+}
+
+/// Body of a password reset request, answered the same whether the account exists or not
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+/// Token received by e-mail together with the new password
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PasswordReset {
+    pub token: String,
+    pub password: String,
+}
+
+/// Role of an account, deciding which privileged actions it can perform
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+/// Session resolved from the token sent in the `Authorization` header
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Session {
+    pub token: String,
+    pub account_id: AccountId,
+    pub role: Role,
+    pub expires_on: NaiveDateTime,
+}
+
+impl AccountDraft {
+    /// Trims the e-mail and checks its shape, and the password length, before registering
+    pub fn validated(self) -> Result<Self, Vec<FieldError>> {
+        let mut validator = Validator::new();
+        let email = validator.text("email", &self.email, 3..=EMAIL_MAX_LENGTH, false);
+        let well_formed = match email.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty()
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+                    && !domain.contains('@')
+                    && !email.contains(char::is_whitespace)
+            }
+            None => false,
+        };
+        if !well_formed {
+            validator.error("email", "Must be an e-mail address".to_string());
+        }
+        let length = self.password.chars().count();
+        if !(PASSWORD_MIN_LENGTH..=PASSWORD_MAX_LENGTH).contains(&length) {
+            validator.error(
+                "password",
+                format!(
+                    "Must be between {} and {} characters, got {}",
+                    PASSWORD_MIN_LENGTH, PASSWORD_MAX_LENGTH, length
+                ),
+            );
+        }
+        validator.finish(AccountDraft {
+            email,
+            password: self.password,
+        })
+    }
+}
+
+impl Session {
+    /// Moderators and admins can act on content owned by other accounts
+    pub fn is_moderator(&self) -> bool {
+        matches!(self.role, Role::Moderator | Role::Admin)
+    }
+
+    /// Whether the session belongs to the owner of a piece of content or to a moderator
+    pub fn can_modify(&self, owner: Option<AccountId>) -> bool {
+        owner == Some(self.account_id) || self.is_moderator()
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        match role {
+            "user" => Ok(Role::User),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("Unknown role: {}", role)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(email: &str, password: &str) -> AccountDraft {
+        AccountDraft {
+            email: email.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_account_draft_validated() {
+        let account = draft(" user@example.com ", "correct horse").validated().unwrap();
+        assert_eq!(account.email, "user@example.com");
+        assert!(draft("", "").validated().unwrap_err().len() >= 2);
+        for email in ["user", "@example.com", "user@example", "user@.com", "us er@example.com"] {
+            let errors = draft(email, "correct horse").validated().unwrap_err();
+            assert!(errors.iter().all(|error| error.field == "email"), "{}", email);
+        }
+        let errors = draft("user@example.com", "short").validated().unwrap_err();
+        assert_eq!(errors[0].field, "password");
+    }
+}
@@ -1,15 +1,32 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use errors::FieldError;
+
+use crate::domain::account::AccountId;
 use crate::domain::question::QuestionId;
+use crate::domain::validation::Validator;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Shortest answer accepted, in characters
+pub const ANSWER_MIN_LENGTH: usize = 20;
+/// Longest answer accepted, in characters
+pub const ANSWER_MAX_LENGTH: usize = 30_000;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct Answer {
     pub id: AnswerId,
     pub content: String,
+    /// `content` rendered from CommonMark to sanitized HTML
+    #[serde(default, skip_deserializing)]
+    pub content_html: String,
     pub question_id: QuestionId,
+    #[serde(default)]
+    pub account_id: Option<AccountId>,
+    #[serde(default)]
+    pub score: i32,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
 pub struct AnswerId(pub i32);
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -17,3 +34,34 @@ pub struct AnswerDraft {
     pub content: String,
     pub question_id: QuestionId,
 }
+
+impl AnswerDraft {
+    /// Trims the content, refusing it unless it passes `validate_answer_content`
+    pub fn new(content: &str, question_id: QuestionId) -> Result<Self, Vec<FieldError>> {
+        Ok(AnswerDraft {
+            content: validate_answer_content(content)?,
+            question_id,
+        })
+    }
+}
+
+/// Trims the content of a new or edited answer and checks its length and characters
+pub fn validate_answer_content(content: &str) -> Result<String, Vec<FieldError>> {
+    let mut validator = Validator::new();
+    let content = validator.text("content", content, ANSWER_MIN_LENGTH..=ANSWER_MAX_LENGTH, true);
+    validator.finish(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_draft_validation() {
+        let draft = AnswerDraft::new("  Clone the store into each filter.\n", QuestionId(1)).unwrap();
+        assert_eq!(draft.content, "Clone the store into each filter.");
+        assert!(AnswerDraft::new("Too short", QuestionId(1)).is_err());
+        assert!(validate_answer_content(&"a".repeat(ANSWER_MAX_LENGTH + 1)).is_err());
+        assert_eq!(validate_answer_content("Use Arc\u{0}").unwrap_err().len(), 2);
+    }
+}
@@ -0,0 +1,269 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use errors::FieldError;
+
+use crate::domain::account::AccountId;
+use crate::domain::answer::AnswerId;
+use crate::domain::question::QuestionId;
+
+/// Longest file name kept, in characters, matching the `attachments.file_name` column
+pub const FILE_NAME_MAX_LENGTH: usize = 255;
+/// Types accepted, as sniffed from the content rather than declared by the client
+pub const ALLOWED_CONTENT_TYPES: [&str; 8] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+    "application/zip",
+    "application/gzip",
+    "text/plain",
+];
+
+/// File uploaded to a question or answer. The content is kept in the blob store under
+/// `blob_key`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Attachment {
+    pub id: AttachmentId,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub account_id: Option<AccountId>,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    #[serde(skip)]
+    pub blob_key: String,
+    pub created_on: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
+pub struct AttachmentId(pub i32);
+
+/// Question or answer a file is attached to
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AttachmentTarget {
+    Question(QuestionId),
+    Answer(AnswerId),
+}
+
+#[derive(Debug, Clone)]
+pub struct AttachmentDraft {
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub size: i64,
+}
+
+/// Inclusive bounds of the part of a file asked for with a `Range` header
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Attachment {
+    /// Images are shown by the browser, anything else is downloaded
+    pub fn content_disposition(&self) -> String {
+        let disposition = if self.content_type.starts_with("image/") {
+            "inline"
+        } else {
+            "attachment"
+        };
+        // Plain ASCII name for old clients, the exact one percent-encoded for the others
+        let ascii_name: String = self
+            .file_name
+            .chars()
+            .map(|c| if c.is_ascii() && c != '\\' { c } else { '_' })
+            .collect();
+        let encoded_name: String = self
+            .file_name
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' => {
+                    (byte as char).to_string()
+                }
+                _ => format!("%{:02X}", byte),
+            })
+            .collect();
+        format!(
+            "{}; filename=\"{}\"; filename*=UTF-8''{}",
+            disposition, ascii_name, encoded_name
+        )
+    }
+}
+
+impl AttachmentTarget {
+    /// Column of the `attachments` table referencing the content
+    pub fn column(&self) -> &'static str {
+        match self {
+            AttachmentTarget::Question(_) => "question_id",
+            AttachmentTarget::Answer(_) => "answer_id",
+        }
+    }
+
+    /// Table holding the content
+    pub fn table(&self) -> &'static str {
+        match self {
+            AttachmentTarget::Question(_) => "questions",
+            AttachmentTarget::Answer(_) => "answers",
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            AttachmentTarget::Question(id) => id.0,
+            AttachmentTarget::Answer(id) => id.0,
+        }
+    }
+}
+
+impl AttachmentDraft {
+    /// Cleans up the file name and sniffs the type of the content, refusing empty files
+    /// and types outside of `ALLOWED_CONTENT_TYPES`
+    pub fn new(file_name: Option<&str>, content: &[u8]) -> Result<Self, Vec<FieldError>> {
+        let error = |message: String| vec![FieldError { field: "file", message }];
+        if content.is_empty() {
+            return Err(error("Must not be empty".to_string()));
+        }
+        let content_type = sniff_content_type(content).ok_or_else(|| {
+            error(format!("Type must be one of {}", ALLOWED_CONTENT_TYPES.join(", ")))
+        })?;
+        Ok(AttachmentDraft {
+            file_name: clean_file_name(file_name.unwrap_or_default()),
+            content_type,
+            size: content.len() as i64,
+        })
+    }
+}
+
+impl ByteRange {
+    /// Reads a single `bytes` range of a `size` bytes file. Missing, malformed or multiple
+    /// ranges give `None`, the whole file being sent as servers may ignore them, and ranges
+    /// starting past the end are refused.
+    pub fn parse(range: Option<&str>, size: u64) -> Result<Option<ByteRange>, String> {
+        let spec = match range.and_then(|range| range.trim().strip_prefix("bytes=")) {
+            Some(spec) if !spec.contains(',') => spec,
+            _ => return Ok(None),
+        };
+        let (start, end) = match spec.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => return Ok(None),
+        };
+        let range = match (start.parse::<u64>(), end.parse::<u64>()) {
+            // Suffix range, the last `end` bytes
+            (Err(_), Ok(length)) if start.is_empty() => {
+                if length == 0 || size == 0 {
+                    return Err(format!("Empty suffix range of {} bytes", size));
+                }
+                ByteRange {
+                    start: size.saturating_sub(length),
+                    end: size - 1,
+                }
+            }
+            (Ok(start), Err(_)) if end.is_empty() => ByteRange {
+                start,
+                end: size.saturating_sub(1),
+            },
+            (Ok(start), Ok(end)) if start <= end => ByteRange {
+                start,
+                end: end.min(size.saturating_sub(1)),
+            },
+            _ => return Ok(None),
+        };
+        if range.start >= size {
+            return Err(format!("Range starts at {} of {} bytes", range.start, size));
+        }
+        Ok(Some(range))
+    }
+
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Detects the type from the first bytes of the content, text being anything that is
+/// valid UTF-8 without a known signature
+fn sniff_content_type(content: &[u8]) -> Option<&'static str> {
+    match infer::get(content) {
+        Some(kind) => ALLOWED_CONTENT_TYPES
+            .into_iter()
+            .find(|allowed| *allowed == kind.mime_type()),
+        None if std::str::from_utf8(content).is_ok() => Some("text/plain"),
+        None => None,
+    }
+}
+
+/// Keeps the last component of the path some browsers send, without quotes or control
+/// characters
+fn clean_file_name(file_name: &str) -> String {
+    let file_name: String = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(FILE_NAME_MAX_LENGTH)
+        .collect();
+    match file_name.trim() {
+        "" | "." | ".." => "attachment".to_string(),
+        file_name => file_name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_draft_sniffs_content() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let draft = AttachmentDraft::new(Some("C:\\Users\\ada\\screen\"shot\".png"), png).unwrap();
+        assert_eq!(draft.content_type, "image/png");
+        assert_eq!(draft.file_name, "screenshot.png");
+        let draft = AttachmentDraft::new(None, b"thread 'main' panicked").unwrap();
+        assert_eq!((draft.content_type, draft.file_name.as_str()), ("text/plain", "attachment"));
+
+        assert!(AttachmentDraft::new(Some("empty.log"), b"").is_err());
+        // Executable whatever its name
+        assert!(AttachmentDraft::new(Some("logo.png"), b"MZ\x90\0\x03\0\0\0\x04\0").is_err());
+    }
+
+    #[test]
+    fn test_byte_range_parse() {
+        let parse = |range: &str| ByteRange::parse(Some(range), 100);
+        assert_eq!(parse("bytes=0-9"), Ok(Some(ByteRange { start: 0, end: 9 })));
+        assert_eq!(parse("bytes=90-"), Ok(Some(ByteRange { start: 90, end: 99 })));
+        assert_eq!(parse("bytes=-10"), Ok(Some(ByteRange { start: 90, end: 99 })));
+        assert_eq!(parse("bytes=50-500"), Ok(Some(ByteRange { start: 50, end: 99 })));
+        assert_eq!(parse("bytes=0-1,5-6"), Ok(None));
+        assert_eq!(parse("bytes=9-1"), Ok(None));
+        assert_eq!(parse("items=0-1"), Ok(None));
+        assert!(parse("bytes=100-").is_err());
+        assert!(parse("bytes=-0").is_err());
+        assert_eq!(ByteRange::parse(None, 100), Ok(None));
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        let attachment = |file_name: &str, content_type: &str| Attachment {
+            id: AttachmentId(1),
+            question_id: Some(QuestionId(1)),
+            answer_id: None,
+            account_id: None,
+            file_name: file_name.to_string(),
+            content_type: content_type.to_string(),
+            size: 1,
+            blob_key: "key".to_string(),
+            created_on: Default::default(),
+        };
+        assert_eq!(
+            attachment("crash log.txt", "text/plain").content_disposition(),
+            "attachment; filename=\"crash log.txt\"; filename*=UTF-8''crash%20log.txt"
+        );
+        assert_eq!(
+            attachment("écran.png", "image/png").content_disposition(),
+            "inline; filename=\"_cran.png\"; filename*=UTF-8''%C3%A9cran.png"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::question::Question;
+
+/// Longest collection name accepted, in characters, matching the `bookmarks.collection` column
+pub const COLLECTION_MAX_LENGTH: usize = 64;
+
+/// Question saved by an account, optionally filed under a named collection
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Bookmark {
+    pub question: Question,
+    pub collection: Option<String>,
+    pub bookmarked_on: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BookmarkDraft {
+    pub collection: Option<String>,
+}
+
+impl BookmarkDraft {
+    /// Trims the collection name, treating a blank one as no collection
+    pub fn new(collection: Option<&String>) -> Result<Self, String> {
+        let collection = collection
+            .map(|collection| collection.trim())
+            .filter(|collection| !collection.is_empty());
+        if let Some(collection) = collection {
+            let length = collection.chars().count();
+            if length > COLLECTION_MAX_LENGTH {
+                return Err(format!(
+                    "Collection name must be at most {} characters, got {}",
+                    COLLECTION_MAX_LENGTH, length
+                ));
+            }
+        }
+        Ok(BookmarkDraft {
+            collection: collection.map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmark_collection_name() {
+        let draft = |collection: &str| BookmarkDraft::new(Some(&collection.to_string()));
+        assert_eq!(BookmarkDraft::new(None).unwrap().collection, None);
+        assert_eq!(draft("   ").unwrap().collection, None);
+        assert_eq!(draft(" Read later ").unwrap().collection, Some("Read later".to_string()));
+        assert!(draft(&"a".repeat(COLLECTION_MAX_LENGTH)).is_ok());
+        assert!(draft(&"a".repeat(COLLECTION_MAX_LENGTH + 1)).is_err());
+    }
+}
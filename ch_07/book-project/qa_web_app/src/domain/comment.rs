@@ -0,0 +1,101 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use errors::FieldError;
+
+use crate::domain::account::AccountId;
+use crate::domain::answer::AnswerId;
+use crate::domain::question::QuestionId;
+use crate::domain::validation::Validator;
+
+/// Shortest comment accepted, in characters
+pub const COMMENT_MIN_LENGTH: usize = 15;
+/// Longest comment accepted, in characters, matching the `comments.content` column
+pub const COMMENT_MAX_LENGTH: usize = 600;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct Comment {
+    pub id: CommentId,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub content: String,
+    pub account_id: Option<AccountId>,
+    pub created_on: NaiveDateTime,
+    pub updated_on: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
+pub struct CommentId(pub i32);
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct CommentDraft {
+    pub content: String,
+}
+
+/// Question or answer a comment is attached to
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CommentTarget {
+    Question(QuestionId),
+    Answer(AnswerId),
+}
+
+impl CommentDraft {
+    /// Trims the comment and checks its length
+    pub fn validated(self) -> Result<Self, Vec<FieldError>> {
+        let mut validator = Validator::new();
+        let content = validator.text(
+            "content",
+            &self.content,
+            COMMENT_MIN_LENGTH..=COMMENT_MAX_LENGTH,
+            true,
+        );
+        validator.finish(CommentDraft { content })
+    }
+}
+
+impl CommentTarget {
+    /// Column of the `comments` table referencing the commented content
+    pub fn column(&self) -> &'static str {
+        match self {
+            CommentTarget::Question(_) => "question_id",
+            CommentTarget::Answer(_) => "answer_id",
+        }
+    }
+
+    /// Table holding the commented content
+    pub fn table(&self) -> &'static str {
+        match self {
+            CommentTarget::Question(_) => "questions",
+            CommentTarget::Answer(_) => "answers",
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            CommentTarget::Question(id) => id.0,
+            CommentTarget::Answer(id) => id.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_length_limits() {
+        let draft = |content: &str| CommentDraft {
+            content: content.to_string(),
+        };
+        assert!(draft("Too short").validated().is_err());
+        assert!(draft("   Too short     ").validated().is_err());
+        assert_eq!(
+            draft("  Which version of warp?\n").validated().unwrap().content,
+            "Which version of warp?"
+        );
+        assert!(draft(&"a".repeat(COMMENT_MAX_LENGTH)).validated().is_ok());
+        let errors = draft(&"a".repeat(COMMENT_MAX_LENGTH + 1)).validated().unwrap_err();
+        assert_eq!(errors[0].field, "content");
+    }
+}
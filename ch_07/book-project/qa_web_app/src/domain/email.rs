@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::answer::Answer;
+use crate::domain::notification::NotificationKind;
+use crate::domain::question::{Question, QuestionId};
+
+const NEW_ANSWER_TEXT: &str = include_str!("../../templates/new_answer.txt");
+const NEW_ANSWER_HTML: &str = include_str!("../../templates/new_answer.html");
+const PASSWORD_RESET_TEXT: &str = include_str!("../../templates/password_reset.txt");
+const PASSWORD_RESET_HTML: &str = include_str!("../../templates/password_reset.html");
+const DIGEST_TEXT: &str = include_str!("../../templates/digest.txt");
+const DIGEST_HTML: &str = include_str!("../../templates/digest.html");
+
+/// Email rendered from one of the templates, with a plain-text and an HTML body
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Email {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// Which emails an account wants to receive. Password resets are always sent.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+pub struct EmailPreferences {
+    /// Email the author of a question when it gets an answer
+    pub new_answers: bool,
+    pub digest: DigestFrequency,
+}
+
+/// How often unread notifications are summed up in a digest email
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    Off,
+    Daily,
+    Weekly,
+}
+
+/// Unread notification listed in a digest
+#[derive(Debug, Clone)]
+pub struct DigestItem {
+    pub kind: NotificationKind,
+    pub question_id: QuestionId,
+    pub title: String,
+}
+
+impl Default for EmailPreferences {
+    fn default() -> Self {
+        EmailPreferences {
+            new_answers: true,
+            digest: DigestFrequency::Off,
+        }
+    }
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestFrequency::Off => "off",
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+}
+
+impl FromStr for DigestFrequency {
+    type Err = String;
+
+    fn from_str(frequency: &str) -> Result<Self, Self::Err> {
+        match frequency {
+            "off" => Ok(DigestFrequency::Off),
+            "daily" => Ok(DigestFrequency::Daily),
+            "weekly" => Ok(DigestFrequency::Weekly),
+            _ => Err(format!("Unknown digest frequency: {}", frequency)),
+        }
+    }
+}
+
+impl Email {
+    pub fn new_answer(question: &Question, answer: &Answer, app_url: &str) -> Self {
+        let url = format!("{}/questions/{}", app_url, question.id.0);
+        Email {
+            subject: format!("New answer to \"{}\"", question.title),
+            text: render(NEW_ANSWER_TEXT, &[
+                ("title", &question.title),
+                ("answer", &answer.content),
+                ("url", &url),
+            ]),
+            html: render(NEW_ANSWER_HTML, &[
+                ("title", &escape_html(&question.title)),
+                ("answer", &escape_html(&answer.content)),
+                ("url", &escape_html(&url)),
+            ]),
+        }
+    }
+
+    pub fn password_reset(token: &str, expires_on: NaiveDateTime, app_url: &str) -> Self {
+        let url = format!("{}/password-reset/confirm", app_url);
+        let expires_on = expires_on.format("%Y-%m-%d %H:%M").to_string();
+        Email {
+            subject: "Reset your password".to_string(),
+            text: render(PASSWORD_RESET_TEXT, &[
+                ("token", token),
+                ("expires_on", &expires_on),
+                ("url", &url),
+            ]),
+            html: render(PASSWORD_RESET_HTML, &[
+                ("token", &escape_html(token)),
+                ("expires_on", &expires_on),
+                ("url", &escape_html(&url)),
+            ]),
+        }
+    }
+
+    pub fn digest(items: &[DigestItem], app_url: &str) -> Self {
+        let url = format!("{}/me/notifications", app_url);
+        let text_items: Vec<String> = items
+            .iter()
+            .map(|item| format!("- {}: {}", item.describe(), item.url(app_url)))
+            .collect();
+        let html_items: Vec<String> = items
+            .iter()
+            .map(|item| {
+                format!(
+                    "<li><a href=\"{}\">{}</a></li>",
+                    escape_html(&item.url(app_url)),
+                    escape_html(&item.describe())
+                )
+            })
+            .collect();
+        Email {
+            subject: format!("{} new notifications", items.len()),
+            text: render(DIGEST_TEXT, &[("items", &text_items.join("\n")), ("url", &url)]),
+            html: render(DIGEST_HTML, &[
+                ("items", &html_items.join("\n")),
+                ("url", &escape_html(&url)),
+            ]),
+        }
+    }
+}
+
+impl DigestItem {
+    fn describe(&self) -> String {
+        let activity = match self.kind {
+            NotificationKind::Answer => "New answer on",
+            NotificationKind::Comment => "New comment on",
+            NotificationKind::AcceptedAnswer => "Answer accepted on",
+            NotificationKind::Warning => "Moderator warning on",
+        };
+        format!("{} \"{}\"", activity, self.title)
+    }
+
+    fn url(&self, app_url: &str) -> String {
+        format!("{}/questions/{}", app_url, self.question_id.0)
+    }
+}
+
+/// Replaces every `{{name}}` placeholder of the template with its value
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |rendered, (name, value)| {
+            rendered.replace(&format!("{{{{{}}}}}", name), value)
+        })
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::answer::AnswerId;
+    use crate::domain::question::QuestionStatus;
+
+    use super::*;
+
+    #[test]
+    fn test_render_replaces_placeholders() {
+        assert_eq!(
+            render("{{greeting}}, {{name}}! {{greeting}}", &[("greeting", "Hi"), ("name", "Ada")]),
+            "Hi, Ada! Hi"
+        );
+    }
+
+    #[test]
+    fn test_new_answer_escapes_html() {
+        let question = Question {
+            id: QuestionId(7),
+            title: "Why <script>?".to_string(),
+            content: "content".to_string(),
+            content_html: String::new(),
+            tags: None,
+            account_id: None,
+            accepted_answer_id: None,
+            score: 0,
+            bookmark_count: 0,
+            status: QuestionStatus::Open,
+            close_reason: None,
+            duplicate_of: None,
+        };
+        let answer = Answer {
+            id: AnswerId(1),
+            content: "Use & escape".to_string(),
+            content_html: String::new(),
+            question_id: QuestionId(7),
+            account_id: None,
+            score: 0,
+        };
+        let email = Email::new_answer(&question, &answer, "http://localhost:3030");
+        assert!(email.text.contains("Why <script>?"));
+        assert!(email.html.contains("Why &lt;script&gt;?"));
+        assert!(email.html.contains("Use &amp; escape"));
+        assert!(email.html.contains("http://localhost:3030/questions/7"));
+        assert!(!email.html.contains("{{"));
+    }
+}
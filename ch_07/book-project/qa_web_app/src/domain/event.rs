@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::answer::{Answer, AnswerId};
+use crate::domain::question::{Question, QuestionId};
+use crate::domain::vote::VoteTarget;
+
+/// Change to a question or answer streamed to `/events` listeners
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    QuestionCreated,
+    QuestionUpdated,
+    QuestionDeleted,
+    QuestionRestored,
+    AnswerCreated,
+    AnswerUpdated,
+    AnswerDeleted,
+    AnswerRestored,
+    QuestionVoted,
+    AnswerVoted,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Event {
+    pub id: EventId,
+    pub kind: EventKind,
+    pub question_id: QuestionId,
+    pub answer_id: Option<AnswerId>,
+    /// Tags of the question the event is about, also for answer events
+    pub tags: Vec<String>,
+    /// Content after the change, absent for deletions and votes
+    pub question: Option<Question>,
+    pub answer: Option<Answer>,
+    /// Score after a vote
+    pub score: Option<i32>,
+    pub created_on: NaiveDateTime,
+}
+
+/// Sequence number of an event, sent as the SSE `id` and used to resume with `Last-Event-ID`
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd, Hash, ToSchema)]
+pub struct EventId(pub u64);
+
+/// Event before being numbered by the event bus
+#[derive(Debug, Clone)]
+pub struct EventDraft {
+    pub kind: EventKind,
+    pub question_id: QuestionId,
+    pub answer_id: Option<AnswerId>,
+    pub tags: Vec<String>,
+    pub question: Option<Question>,
+    pub answer: Option<Answer>,
+    pub score: Option<i32>,
+}
+
+/// Restriction on the events a listener receives
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct EventFilter {
+    pub tag: Option<String>,
+    pub question_id: Option<QuestionId>,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::QuestionCreated => "question_created",
+            EventKind::QuestionUpdated => "question_updated",
+            EventKind::QuestionDeleted => "question_deleted",
+            EventKind::QuestionRestored => "question_restored",
+            EventKind::AnswerCreated => "answer_created",
+            EventKind::AnswerUpdated => "answer_updated",
+            EventKind::AnswerDeleted => "answer_deleted",
+            EventKind::AnswerRestored => "answer_restored",
+            EventKind::QuestionVoted => "question_voted",
+            EventKind::AnswerVoted => "answer_voted",
+        }
+    }
+}
+
+impl Event {
+    pub fn new(id: EventId, draft: EventDraft) -> Self {
+        Event {
+            id,
+            kind: draft.kind,
+            question_id: draft.question_id,
+            answer_id: draft.answer_id,
+            tags: draft.tags,
+            question: draft.question,
+            answer: draft.answer,
+            score: draft.score,
+            created_on: Utc::now().naive_utc(),
+        }
+    }
+}
+
+impl EventDraft {
+    pub fn question(kind: EventKind, question: &Question) -> Self {
+        EventDraft {
+            kind,
+            question_id: question.id.clone(),
+            answer_id: None,
+            tags: question.tags.clone().unwrap_or_default(),
+            question: Some(question.clone()),
+            answer: None,
+            score: None,
+        }
+    }
+
+    pub fn question_deleted(question_id: QuestionId, tags: Option<Vec<String>>) -> Self {
+        EventDraft {
+            kind: EventKind::QuestionDeleted,
+            question_id,
+            answer_id: None,
+            tags: tags.unwrap_or_default(),
+            question: None,
+            answer: None,
+            score: None,
+        }
+    }
+
+    pub fn answer(kind: EventKind, answer: &Answer, tags: Option<Vec<String>>) -> Self {
+        EventDraft {
+            kind,
+            question_id: answer.question_id.clone(),
+            answer_id: Some(answer.id.clone()),
+            tags: tags.unwrap_or_default(),
+            question: None,
+            answer: Some(answer.clone()),
+            score: None,
+        }
+    }
+
+    pub fn answer_deleted(
+        question_id: QuestionId,
+        answer_id: AnswerId,
+        tags: Option<Vec<String>>,
+    ) -> Self {
+        EventDraft {
+            kind: EventKind::AnswerDeleted,
+            question_id,
+            answer_id: Some(answer_id),
+            tags: tags.unwrap_or_default(),
+            question: None,
+            answer: None,
+            score: None,
+        }
+    }
+
+    pub fn voted(
+        target: &VoteTarget,
+        question_id: QuestionId,
+        score: i32,
+        tags: Option<Vec<String>>,
+    ) -> Self {
+        let (kind, answer_id) = match target {
+            VoteTarget::Question(_) => (EventKind::QuestionVoted, None),
+            VoteTarget::Answer(id) => (EventKind::AnswerVoted, Some(id.clone())),
+        };
+        EventDraft {
+            kind,
+            question_id,
+            answer_id,
+            tags: tags.unwrap_or_default(),
+            question: None,
+            answer: None,
+            score: Some(score),
+        }
+    }
+}
+
+impl EventFilter {
+    /// Reads the `tag` and `question_id` query parameters
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self, String> {
+        let question_id = match params.get("question_id") {
+            Some(question_id) => Some(QuestionId(
+                question_id
+                    .parse()
+                    .map_err(|_| format!("Invalid question id: {}", question_id))?,
+            )),
+            None => None,
+        };
+        Ok(EventFilter {
+            tag: params.get("tag").cloned(),
+            question_id,
+        })
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        let tag_matches = self.tag.as_ref().is_none_or(|tag| event.tags.contains(tag));
+        let question_matches = self
+            .question_id
+            .as_ref()
+            .is_none_or(|question_id| &event.question_id == question_id);
+        tag_matches && question_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(question_id: i32, tags: &[&str]) -> Event {
+        Event::new(
+            EventId(1),
+            EventDraft::question_deleted(
+                QuestionId(question_id),
+                Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_event_filter_matches() {
+        let params = HashMap::from([
+            ("tag".to_string(), "rust".to_string()),
+            ("question_id".to_string(), "1".to_string()),
+        ]);
+        let filter = EventFilter::from_params(&params).unwrap();
+        assert!(filter.matches(&event(1, &["rust", "warp"])));
+        assert!(!filter.matches(&event(1, &["go"])));
+        assert!(!filter.matches(&event(2, &["rust"])));
+        assert!(EventFilter::default().matches(&event(2, &[])));
+    }
+
+    #[test]
+    fn test_event_filter_rejects_invalid_question_id() {
+        let params = HashMap::from([("question_id".to_string(), "one".to_string())]);
+        assert!(EventFilter::from_params(&params).is_err());
+    }
+}
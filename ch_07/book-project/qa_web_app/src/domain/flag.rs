@@ -0,0 +1,320 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::account::AccountId;
+use crate::domain::answer::AnswerId;
+use crate::domain::comment::CommentId;
+use crate::domain::question::QuestionId;
+
+/// Longest note accepted with a flag or a moderation decision, in characters
+pub const NOTE_MAX_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagReason {
+    Spam,
+    Abuse,
+    OffTopic,
+    Other,
+}
+
+/// Question, answer or comment being flagged
+#[derive(Debug, Clone, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(tag = "type", content = "id", rename_all = "lowercase")]
+pub enum FlagTarget {
+    Question(QuestionId),
+    Answer(AnswerId),
+    Comment(CommentId),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagStatus {
+    Open,
+    /// Closed by a moderator without acting on the content
+    Dismissed,
+    /// Closed by a moderator deleting, editing the content or warning its author
+    Actioned,
+}
+
+/// Body of `POST /flags`, naming exactly one of the question, answer or comment
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct FlagDraft {
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub comment_id: Option<CommentId>,
+    pub reason: FlagReason,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Flag {
+    pub id: FlagId,
+    pub target: FlagTarget,
+    pub reason: FlagReason,
+    pub note: Option<String>,
+    pub account_id: AccountId,
+    pub status: FlagStatus,
+    pub created_on: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
+pub struct FlagId(pub i32);
+
+/// Flagged content waiting for a moderator, with its open flags summed up
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QueueItem {
+    pub target: FlagTarget,
+    pub content: String,
+    pub author_id: Option<AccountId>,
+    /// Whether it reached enough flags to be hidden from listings
+    pub hidden: bool,
+    pub flags: i64,
+    /// Highest severity among the reasons given
+    pub severity: i32,
+    pub reasons: Vec<FlagReason>,
+    pub first_flagged_on: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    /// Leaves the content as it is and shows it again
+    Dismiss,
+    Delete,
+    /// Replaces the content and shows it again
+    Edit,
+    /// Notifies the author and shows the content again
+    Warn,
+}
+
+/// Moderator's decision on flagged content
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ModerationDecision {
+    pub action: ModerationAction,
+    /// New content, required when editing
+    pub content: Option<String>,
+    /// Explanation kept in the moderation log
+    pub note: Option<String>,
+}
+
+/// Audit entry of a moderator's decision
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ModerationLogEntry {
+    pub id: i32,
+    pub target: Option<FlagTarget>,
+    pub action: ModerationAction,
+    pub moderator_id: AccountId,
+    pub author_id: Option<AccountId>,
+    pub note: Option<String>,
+    pub flags_resolved: i32,
+    pub created_on: NaiveDateTime,
+}
+
+impl FlagReason {
+    /// Weight of the reason when ordering the moderation queue, abuse first
+    pub fn severity(&self) -> i32 {
+        match self {
+            FlagReason::Abuse => 3,
+            FlagReason::Spam => 2,
+            FlagReason::OffTopic | FlagReason::Other => 1,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagReason::Spam => "spam",
+            FlagReason::Abuse => "abuse",
+            FlagReason::OffTopic => "off_topic",
+            FlagReason::Other => "other",
+        }
+    }
+}
+
+impl FromStr for FlagReason {
+    type Err = String;
+
+    fn from_str(reason: &str) -> Result<Self, Self::Err> {
+        match reason {
+            "spam" => Ok(FlagReason::Spam),
+            "abuse" => Ok(FlagReason::Abuse),
+            "off_topic" => Ok(FlagReason::OffTopic),
+            "other" => Ok(FlagReason::Other),
+            _ => Err(format!("Unknown flag reason: {}", reason)),
+        }
+    }
+}
+
+impl FlagStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagStatus::Open => "open",
+            FlagStatus::Dismissed => "dismissed",
+            FlagStatus::Actioned => "actioned",
+        }
+    }
+}
+
+impl FromStr for FlagStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "open" => Ok(FlagStatus::Open),
+            "dismissed" => Ok(FlagStatus::Dismissed),
+            "actioned" => Ok(FlagStatus::Actioned),
+            _ => Err(format!("Unknown flag status: {}", status)),
+        }
+    }
+}
+
+impl FlagTarget {
+    /// Parses the plural name used in moderation paths, e.g. `/moderation/answers/3`
+    pub fn from_path(kind: &str, id: i32) -> Option<Self> {
+        match kind {
+            "questions" => Some(FlagTarget::Question(QuestionId(id))),
+            "answers" => Some(FlagTarget::Answer(AnswerId(id))),
+            "comments" => Some(FlagTarget::Comment(CommentId(id))),
+            _ => None,
+        }
+    }
+
+    /// Builds the target from the `question_id`, `answer_id` and `comment_id` columns of a
+    /// row, exactly one of which is set
+    pub fn from_columns(
+        question_id: Option<i32>,
+        answer_id: Option<i32>,
+        comment_id: Option<i32>,
+    ) -> Option<Self> {
+        match (question_id, answer_id, comment_id) {
+            (Some(id), None, None) => Some(FlagTarget::Question(QuestionId(id))),
+            (None, Some(id), None) => Some(FlagTarget::Answer(AnswerId(id))),
+            (None, None, Some(id)) => Some(FlagTarget::Comment(CommentId(id))),
+            _ => None,
+        }
+    }
+
+    /// Table holding the flagged content
+    pub fn table(&self) -> &'static str {
+        match self {
+            FlagTarget::Question(_) => "questions",
+            FlagTarget::Answer(_) => "answers",
+            FlagTarget::Comment(_) => "comments",
+        }
+    }
+
+    /// Column of the `flags` and `moderation_log` tables referencing the flagged content
+    pub fn column(&self) -> &'static str {
+        match self {
+            FlagTarget::Question(_) => "question_id",
+            FlagTarget::Answer(_) => "answer_id",
+            FlagTarget::Comment(_) => "comment_id",
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            FlagTarget::Question(id) => id.0,
+            FlagTarget::Answer(id) => id.0,
+            FlagTarget::Comment(id) => id.0,
+        }
+    }
+}
+
+impl ModerationAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationAction::Dismiss => "dismiss",
+            ModerationAction::Delete => "delete",
+            ModerationAction::Edit => "edit",
+            ModerationAction::Warn => "warn",
+        }
+    }
+
+    /// Status given to the open flags once the action is taken
+    pub fn flag_status(&self) -> FlagStatus {
+        match self {
+            ModerationAction::Dismiss => FlagStatus::Dismissed,
+            _ => FlagStatus::Actioned,
+        }
+    }
+}
+
+impl FromStr for ModerationAction {
+    type Err = String;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        match action {
+            "dismiss" => Ok(ModerationAction::Dismiss),
+            "delete" => Ok(ModerationAction::Delete),
+            "edit" => Ok(ModerationAction::Edit),
+            "warn" => Ok(ModerationAction::Warn),
+            _ => Err(format!("Unknown moderation action: {}", action)),
+        }
+    }
+}
+
+impl FlagDraft {
+    pub fn target(&self) -> Result<FlagTarget, String> {
+        if self.note.as_ref().is_some_and(|note| note.chars().count() > NOTE_MAX_LENGTH) {
+            return Err(format!("Note must be at most {} characters", NOTE_MAX_LENGTH));
+        }
+        FlagTarget::from_columns(
+            self.question_id.as_ref().map(|id| id.0),
+            self.answer_id.as_ref().map(|id| id.0),
+            self.comment_id.as_ref().map(|id| id.0),
+        )
+        .ok_or_else(|| "Flag exactly one of question_id, answer_id or comment_id".to_string())
+    }
+}
+
+impl ModerationDecision {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.note.as_ref().is_some_and(|note| note.chars().count() > NOTE_MAX_LENGTH) {
+            return Err(format!("Note must be at most {} characters", NOTE_MAX_LENGTH));
+        }
+        match (self.action, &self.content) {
+            (ModerationAction::Edit, None) => Err("Editing requires the new content".to_string()),
+            (ModerationAction::Edit, Some(content)) if content.trim().is_empty() => {
+                Err("Content cannot be empty".to_string())
+            }
+            (ModerationAction::Edit, Some(_)) | (_, None) => Ok(()),
+            (_, Some(_)) => Err("Content is only allowed when editing".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_draft_requires_exactly_one_target() {
+        let draft = |question_id: Option<i32>, answer_id: Option<i32>| FlagDraft {
+            question_id: question_id.map(QuestionId),
+            answer_id: answer_id.map(AnswerId),
+            comment_id: None,
+            reason: FlagReason::Spam,
+            note: None,
+        };
+        assert_eq!(draft(None, Some(4)).target(), Ok(FlagTarget::Answer(AnswerId(4))));
+        assert!(draft(Some(1), Some(4)).target().is_err());
+        assert!(draft(None, None).target().is_err());
+    }
+
+    #[test]
+    fn test_moderation_decision_validation() {
+        let decision = |action: ModerationAction, content: Option<&str>| ModerationDecision {
+            action,
+            content: content.map(str::to_string),
+            note: None,
+        };
+        assert!(decision(ModerationAction::Edit, Some("Cleaned up")).validate().is_ok());
+        assert!(decision(ModerationAction::Edit, None).validate().is_err());
+        assert!(decision(ModerationAction::Dismiss, Some("text")).validate().is_err());
+        assert!(decision(ModerationAction::Warn, None).validate().is_ok());
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::account::AccountId;
+use crate::domain::event::Event;
+
+/// Message pushed to the clients watching a question page
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveMessage {
+    /// Change to the question or one of its answers
+    Event(Box<Event>),
+    /// Another visitor started or stopped typing an answer
+    Typing { account_id: AccountId, typing: bool },
+}
+
+/// Message sent by a client watching a question page
+#[derive(Debug, Clone, Deserialize, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Typing { typing: bool },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_message_parsing() {
+        let message: ClientMessage = serde_json::from_str(r#"{"type":"typing","typing":true}"#).unwrap();
+        assert_eq!(message, ClientMessage::Typing { typing: true });
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type":"vote"}"#).is_err());
+    }
+
+    #[test]
+    fn test_typing_message_format() {
+        let message = LiveMessage::Typing {
+            account_id: AccountId(3),
+            typing: false,
+        };
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"type":"typing","account_id":3,"typing":false}"#
+        );
+    }
+}
@@ -1,2 +1,19 @@
+pub mod account;
 pub mod answer;
+pub mod attachment;
+pub mod bookmark;
+pub mod comment;
+pub mod email;
+pub mod event;
+pub mod flag;
+pub mod live;
+pub mod notification;
+pub mod preview;
 pub mod question;
+pub mod reputation;
+pub mod revision;
+pub mod similarity;
+pub mod subscription;
+pub mod validation;
+pub mod vote;
+pub mod webhook;
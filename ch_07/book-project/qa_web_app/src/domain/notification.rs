@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::account::AccountId;
+use crate::domain::answer::AnswerId;
+use crate::domain::comment::CommentId;
+use crate::domain::question::QuestionId;
+
+/// Activity on a followed question that is reported to its followers, or a moderator's
+/// warning about flagged content sent to its author
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Answer,
+    Comment,
+    AcceptedAnswer,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Notification {
+    pub id: NotificationId,
+    pub kind: NotificationKind,
+    /// Account whose action caused the notification
+    pub actor_id: Option<AccountId>,
+    pub question_id: QuestionId,
+    pub answer_id: Option<AnswerId>,
+    pub comment_id: Option<CommentId>,
+    pub read_on: Option<NaiveDateTime>,
+    pub created_on: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
+pub struct NotificationId(pub i32);
+
+/// Page of an account's notifications together with how many are still unread overall
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Inbox {
+    pub unread: i64,
+    pub notifications: Vec<Notification>,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Answer => "answer",
+            NotificationKind::Comment => "comment",
+            NotificationKind::AcceptedAnswer => "accepted_answer",
+            NotificationKind::Warning => "warning",
+        }
+    }
+}
+
+impl FromStr for NotificationKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "answer" => Ok(NotificationKind::Answer),
+            "comment" => Ok(NotificationKind::Comment),
+            "accepted_answer" => Ok(NotificationKind::AcceptedAnswer),
+            "warning" => Ok(NotificationKind::Warning),
+            _ => Err(format!("Unknown notification kind: {}", kind)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_kind_round_trip() {
+        for kind in [
+            NotificationKind::Answer,
+            NotificationKind::Comment,
+            NotificationKind::AcceptedAnswer,
+            NotificationKind::Warning,
+        ] {
+            assert_eq!(NotificationKind::from_str(kind.as_str()), Ok(kind));
+        }
+        assert!(NotificationKind::from_str("vote").is_err());
+    }
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Body of `POST /preview`, with the question or answer content being edited
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PreviewDraft {
+    pub content: String,
+}
+
+/// Content rendered the way it will be shown once saved
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Preview {
+    pub content_html: String,
+}
@@ -1,32 +1,290 @@
 use std::fmt;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use errors::FieldError;
+
+use crate::domain::account::AccountId;
+use crate::domain::answer::{Answer, AnswerId};
+use crate::domain::comment::Comment;
+use crate::domain::similarity::SimilarQuestion;
+use crate::domain::validation::Validator;
+
+/// Shortest title accepted, in characters
+pub const TITLE_MIN_LENGTH: usize = 10;
+/// Longest title accepted, in characters, matching the `questions.title` column
+pub const TITLE_MAX_LENGTH: usize = 255;
+/// Shortest question content accepted, in characters
+pub const QUESTION_MIN_LENGTH: usize = 20;
+/// Longest question content accepted, in characters
+pub const QUESTION_MAX_LENGTH: usize = 30_000;
+/// Most tags a question can have
+pub const MAX_TAGS: usize = 5;
+/// Longest tag accepted, in characters
+pub const TAG_MAX_LENGTH: usize = 35;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct Question {
     pub id: QuestionId,
     pub title: String,
     pub content: String,
+    /// `content` rendered from CommonMark to sanitized HTML
+    #[serde(default, skip_deserializing)]
+    pub content_html: String,
     pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub account_id: Option<AccountId>,
+    #[serde(default)]
+    pub accepted_answer_id: Option<AnswerId>,
+    #[serde(default)]
+    pub score: i32,
+    #[serde(default)]
+    pub bookmark_count: i32,
+    #[serde(default)]
+    pub status: QuestionStatus,
+    #[serde(default)]
+    pub close_reason: Option<CloseReason>,
+    /// Canonical question when closed as a duplicate
+    #[serde(default)]
+    pub duplicate_of: Option<QuestionId>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
 pub struct QuestionId(pub i32);
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Whether a question still accepts answers. Closed questions can be reopened, locked
+/// ones are frozen by moderators.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QuestionStatus {
+    #[default]
+    Open,
+    Closed,
+    Locked,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    Duplicate,
+    OffTopic,
+    Unclear,
+}
+
+/// Body of a request to close a question
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CloseDraft {
+    pub reason: CloseReason,
+    /// Required when closing as a duplicate, refused otherwise
+    pub duplicate_of: Option<QuestionId>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct QuestionDraft {
     pub title: String,
     pub content: String,
     pub tags: Option<Vec<String>>,
 }
 
+/// Response to asking a question, listing existing questions that may already answer it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NewQuestion {
+    #[serde(flatten)]
+    pub question: Question,
+    pub similar: Vec<SimilarQuestion>,
+}
+
+/// Question as shown on its own page, with its answers and the comments on both
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuestionThread {
+    #[serde(flatten)]
+    pub question: Question,
+    pub comments: Vec<Comment>,
+    pub answers: Vec<AnswerThread>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnswerThread {
+    #[serde(flatten)]
+    pub answer: Answer,
+    pub comments: Vec<Comment>,
+}
+
+/// Restriction applied when listing questions
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum QuestionFilter {
+    #[default]
+    All,
+    /// Questions without any answer
+    Unanswered,
+    /// Questions without an accepted answer
+    Unsolved,
+}
+
+/// Order in which questions are listed
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum QuestionSort {
+    #[default]
+    Oldest,
+    Newest,
+    /// Highest score first
+    Score,
+}
+
 impl Question {
+    #[cfg(test)]
     fn new(id: QuestionId, title: String, content: String, tags: Option<Vec<String>>) -> Self {
         Question {
             id,
+            title,
+            content,
+            content_html: String::new(),
+            tags,
+            account_id: None,
+            accepted_answer_id: None,
+            score: 0,
+            bookmark_count: 0,
+            status: QuestionStatus::Open,
+            close_reason: None,
+            duplicate_of: None,
+        }
+    }
+}
+
+impl Question {
+    /// Checks the editable fields with the same rules as `QuestionDraft::new`
+    pub fn validated(self) -> Result<Self, Vec<FieldError>> {
+        let mut validator = Validator::new();
+        let (title, content, tags) =
+            validate_fields(&mut validator, &self.title, &self.content, self.tags);
+        validator.finish(Question {
             title,
             content,
             tags,
+            ..self
+        })
+    }
+}
+
+impl QuestionDraft {
+    /// Trims the title and content and normalizes the tags, reporting every invalid field
+    pub fn new(
+        title: &str,
+        content: &str,
+        tags: Option<Vec<String>>,
+    ) -> Result<Self, Vec<FieldError>> {
+        let mut validator = Validator::new();
+        let (title, content, tags) = validate_fields(&mut validator, title, content, tags);
+        validator.finish(QuestionDraft {
+            title,
+            content,
+            tags,
+        })
+    }
+}
+
+/// Trims the content of a question and checks its length, as when moderators edit it alone
+pub fn validate_question_content(content: &str) -> Result<String, Vec<FieldError>> {
+    let mut validator = Validator::new();
+    let content = validator.text(
+        "content",
+        content,
+        QUESTION_MIN_LENGTH..=QUESTION_MAX_LENGTH,
+        true,
+    );
+    validator.finish(content)
+}
+
+fn validate_fields(
+    validator: &mut Validator,
+    title: &str,
+    content: &str,
+    tags: Option<Vec<String>>,
+) -> (String, String, Option<Vec<String>>) {
+    let title = validator.text("title", title, TITLE_MIN_LENGTH..=TITLE_MAX_LENGTH, false);
+    let content = validator.text(
+        "content",
+        content,
+        QUESTION_MIN_LENGTH..=QUESTION_MAX_LENGTH,
+        true,
+    );
+    let tags = tags.map(|tags| validate_tags(validator, tags));
+    (title, content, tags)
+}
+
+/// Lowercases the tags and drops the repeated ones. Tags are made of letters, digits and
+/// `+`, `#`, `.` or `-`, like `c++`, `c#` or `asp.net`.
+fn validate_tags(validator: &mut Validator, tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        let length = tag.chars().count();
+        if !(1..=TAG_MAX_LENGTH).contains(&length) {
+            validator.error(
+                "tags",
+                format!("Tags must be between 1 and {} characters, got {}", TAG_MAX_LENGTH, length),
+            );
+        } else if !tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '+' | '#' | '.' | '-'))
+        {
+            validator.error(
+                "tags",
+                format!("Tag {} may only contain letters, digits, +, #, . and -", tag),
+            );
+        } else if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        validator.error(
+            "tags",
+            format!("At most {} tags are allowed, got {}", MAX_TAGS, normalized.len()),
+        );
+    }
+    normalized
+}
+
+impl CloseDraft {
+    pub fn validate(&self, question_id: &QuestionId) -> Result<(), String> {
+        match (self.reason, &self.duplicate_of) {
+            (CloseReason::Duplicate, None) => {
+                Err("Closing as duplicate requires duplicate_of".to_string())
+            }
+            (CloseReason::Duplicate, Some(duplicate_of)) if duplicate_of == question_id => {
+                Err("A question cannot duplicate itself".to_string())
+            }
+            (CloseReason::Duplicate, Some(_)) => Ok(()),
+            (_, Some(_)) => Err("duplicate_of is only allowed when closing as duplicate".to_string()),
+            (_, None) => Ok(()),
+        }
+    }
+}
+
+impl QuestionThread {
+    /// Attaches each comment to the question or answer it was written on, keeping the
+    /// order of `answers` and `comments`
+    pub fn new(question: Question, answers: Vec<Answer>, comments: Vec<Comment>) -> Self {
+        let (question_comments, answer_comments): (Vec<Comment>, Vec<Comment>) = comments
+            .into_iter()
+            .partition(|comment| comment.question_id.as_ref() == Some(&question.id));
+        let answers = answers
+            .into_iter()
+            .map(|answer| AnswerThread {
+                comments: answer_comments
+                    .iter()
+                    .filter(|comment| comment.answer_id.as_ref() == Some(&answer.id))
+                    .cloned()
+                    .collect(),
+                answer,
+            })
+            .collect();
+        QuestionThread {
+            question,
+            comments: question_comments,
+            answers,
         }
     }
 }
@@ -47,8 +305,104 @@ impl fmt::Display for QuestionId {
     }
 }
 
+impl QuestionId {
+    /// Id of a question, refusing the non-positive values the database never assigns
+    #[cfg(test)]
+    pub fn from_str(id: i32) -> Result<Self, String> {
+        if id > 0 {
+            Ok(QuestionId(id))
+        } else {
+            Err(format!("Invalid question id: {}", id))
+        }
+    }
+}
+
+impl QuestionStatus {
+    /// Closed and locked questions refuse new answers
+    pub fn accepts_answers(&self) -> bool {
+        *self == QuestionStatus::Open
+    }
+
+    /// Locked questions, their answers and comments are frozen for all but moderators
+    pub fn accepts_changes(&self, by_moderator: bool) -> bool {
+        *self != QuestionStatus::Locked || by_moderator
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuestionStatus::Open => "open",
+            QuestionStatus::Closed => "closed",
+            QuestionStatus::Locked => "locked",
+        }
+    }
+}
+
+impl FromStr for QuestionStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "open" => Ok(QuestionStatus::Open),
+            "closed" => Ok(QuestionStatus::Closed),
+            "locked" => Ok(QuestionStatus::Locked),
+            _ => Err(format!("Unknown question status: {}", status)),
+        }
+    }
+}
+
+impl CloseReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseReason::Duplicate => "duplicate",
+            CloseReason::OffTopic => "off_topic",
+            CloseReason::Unclear => "unclear",
+        }
+    }
+}
+
+impl FromStr for CloseReason {
+    type Err = String;
+
+    fn from_str(reason: &str) -> Result<Self, Self::Err> {
+        match reason {
+            "duplicate" => Ok(CloseReason::Duplicate),
+            "off_topic" => Ok(CloseReason::OffTopic),
+            "unclear" => Ok(CloseReason::Unclear),
+            _ => Err(format!("Unknown close reason: {}", reason)),
+        }
+    }
+}
+
+impl FromStr for QuestionFilter {
+    type Err = String;
+
+    fn from_str(filter: &str) -> Result<Self, Self::Err> {
+        match filter {
+            "all" => Ok(QuestionFilter::All),
+            "unanswered" => Ok(QuestionFilter::Unanswered),
+            "unsolved" => Ok(QuestionFilter::Unsolved),
+            _ => Err(format!("Unknown question filter: {}", filter)),
+        }
+    }
+}
+
+impl FromStr for QuestionSort {
+    type Err = String;
+
+    fn from_str(sort: &str) -> Result<Self, Self::Err> {
+        match sort {
+            "oldest" => Ok(QuestionSort::Oldest),
+            "newest" => Ok(QuestionSort::Newest),
+            "score" => Ok(QuestionSort::Score),
+            _ => Err(format!("Unknown question sort: {}", sort)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::domain::comment::CommentId;
+
     use super::*;
 
     #[test]
@@ -58,6 +412,11 @@ mod tests {
         assert_eq!(question_id, QuestionId(id));
     }
 
+    #[test]
+    fn test_question_id_from_str_invalid() {
+        assert!(QuestionId::from_str(0).is_err());
+    }
+
     #[test]
     fn test_question_display() {
         let id = 1234;
@@ -73,4 +432,82 @@ mod tests {
         let question_str = format!("Question: {}, {}, {}, {:?}", id, title, content, tags);
         assert_eq!(question.to_string(), question_str);
     }
+
+    #[test]
+    fn test_question_filter_from_str() {
+        assert_eq!(QuestionFilter::from_str("unanswered"), Ok(QuestionFilter::Unanswered));
+        assert_eq!(QuestionFilter::from_str("unsolved"), Ok(QuestionFilter::Unsolved));
+        assert!(QuestionFilter::from_str("solved").is_err());
+    }
+
+    #[test]
+    fn test_question_draft_validation() {
+        let content = "How do I share state between warp filters?";
+        let tags = |tags: &[&str]| Some(tags.iter().map(|tag| tag.to_string()).collect());
+        let draft = QuestionDraft::new("  Sharing state  ", content, tags(&["Rust", "warp", "rust"]))
+            .unwrap();
+        assert_eq!(draft.title, "Sharing state");
+        assert_eq!(draft.tags, tags(&["rust", "warp"]));
+        assert!(QuestionDraft::new("Sharing state", content, tags(&["c++", "c#", "asp.net"])).is_ok());
+
+        let errors = QuestionDraft::new("", "", tags(&["a", "b", "c", "d", "e", "f", "no spaces"]))
+            .unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|error| error.field).collect();
+        assert_eq!(fields, vec!["title", "content", "tags", "tags"]);
+        assert!(QuestionDraft::new(&"a".repeat(TITLE_MAX_LENGTH + 1), content, None).is_err());
+    }
+
+    #[test]
+    fn test_locked_question_frozen_for_non_moderators() {
+        assert!(QuestionStatus::Open.accepts_changes(false));
+        assert!(QuestionStatus::Closed.accepts_changes(false));
+        assert!(!QuestionStatus::Locked.accepts_changes(false));
+        assert!(QuestionStatus::Locked.accepts_changes(true));
+        assert!(!QuestionStatus::Locked.accepts_answers());
+    }
+
+    #[test]
+    fn test_close_draft_validation() {
+        let draft = |reason: CloseReason, duplicate_of: Option<i32>| CloseDraft {
+            reason,
+            duplicate_of: duplicate_of.map(QuestionId),
+        };
+        let id = QuestionId(1);
+        assert!(draft(CloseReason::Duplicate, Some(2)).validate(&id).is_ok());
+        assert!(draft(CloseReason::Duplicate, None).validate(&id).is_err());
+        assert!(draft(CloseReason::Duplicate, Some(1)).validate(&id).is_err());
+        assert!(draft(CloseReason::OffTopic, None).validate(&id).is_ok());
+        assert!(draft(CloseReason::Unclear, Some(2)).validate(&id).is_err());
+    }
+
+    #[test]
+    fn test_question_thread_attaches_comments() {
+        let question = Question::new(QuestionId(1), "title".to_string(), "content".to_string(), None);
+        let answer = |id: i32| Answer {
+            id: AnswerId(id),
+            content: "answer".to_string(),
+            content_html: String::new(),
+            question_id: QuestionId(1),
+            account_id: None,
+            score: 0,
+        };
+        let comment = |id: i32, question_id: Option<i32>, answer_id: Option<i32>| Comment {
+            id: CommentId(id),
+            question_id: question_id.map(QuestionId),
+            answer_id: answer_id.map(AnswerId),
+            content: "comment".to_string(),
+            account_id: None,
+            created_on: Default::default(),
+            updated_on: None,
+        };
+        let thread = QuestionThread::new(
+            question,
+            vec![answer(2), answer(1)],
+            vec![comment(1, Some(1), None), comment(2, None, Some(1)), comment(3, None, Some(1))],
+        );
+        assert_eq!(thread.comments.len(), 1);
+        assert_eq!(thread.answers[0].answer.id, AnswerId(2));
+        assert!(thread.answers[0].comments.is_empty());
+        assert_eq!(thread.answers[1].comments.len(), 2);
+    }
 }
@@ -0,0 +1,187 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::account::AccountId;
+use crate::domain::answer::AnswerId;
+use crate::domain::question::QuestionId;
+use crate::domain::vote::{VoteDirection, VoteTarget};
+
+/// Community signal that earns or costs reputation to the author of the content
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReputationReason {
+    QuestionUpvoted,
+    QuestionDownvoted,
+    AnswerUpvoted,
+    AnswerDownvoted,
+    AnswerAccepted,
+}
+
+/// Entry of the reputation ledger. Undoing a signal (withdrawing a vote, accepting a
+/// different answer) adds an entry with the same reason and negated points.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReputationEvent {
+    pub account_id: AccountId,
+    pub reason: ReputationReason,
+    pub points: i32,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub created_on: NaiveDateTime,
+}
+
+/// Net points earned for one reason
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReputationBreakdown {
+    pub reason: ReputationReason,
+    pub points: i32,
+    pub events: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Reputation {
+    pub account_id: AccountId,
+    pub reputation: i32,
+    pub breakdown: Vec<ReputationBreakdown>,
+    pub recent_events: Vec<ReputationEvent>,
+}
+
+/// Action on content owned by another account that is unlocked by reputation
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Privilege {
+    EditOthers,
+    DeleteOthers,
+}
+
+/// Reputation required for each privilege
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PrivilegeThresholds {
+    pub edit_others: i32,
+    pub delete_others: i32,
+}
+
+impl ReputationReason {
+    pub fn points(&self) -> i32 {
+        match self {
+            ReputationReason::QuestionUpvoted => 5,
+            ReputationReason::QuestionDownvoted => -2,
+            ReputationReason::AnswerUpvoted => 10,
+            ReputationReason::AnswerDownvoted => -2,
+            ReputationReason::AnswerAccepted => 15,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReputationReason::QuestionUpvoted => "question_upvoted",
+            ReputationReason::QuestionDownvoted => "question_downvoted",
+            ReputationReason::AnswerUpvoted => "answer_upvoted",
+            ReputationReason::AnswerDownvoted => "answer_downvoted",
+            ReputationReason::AnswerAccepted => "answer_accepted",
+        }
+    }
+
+    fn for_vote(target: &VoteTarget, direction: VoteDirection) -> Self {
+        match (target, direction) {
+            (VoteTarget::Question(_), VoteDirection::Up) => ReputationReason::QuestionUpvoted,
+            (VoteTarget::Question(_), VoteDirection::Down) => ReputationReason::QuestionDownvoted,
+            (VoteTarget::Answer(_), VoteDirection::Up) => ReputationReason::AnswerUpvoted,
+            (VoteTarget::Answer(_), VoteDirection::Down) => ReputationReason::AnswerDownvoted,
+        }
+    }
+}
+
+impl FromStr for ReputationReason {
+    type Err = String;
+
+    fn from_str(reason: &str) -> Result<Self, Self::Err> {
+        match reason {
+            "question_upvoted" => Ok(ReputationReason::QuestionUpvoted),
+            "question_downvoted" => Ok(ReputationReason::QuestionDownvoted),
+            "answer_upvoted" => Ok(ReputationReason::AnswerUpvoted),
+            "answer_downvoted" => Ok(ReputationReason::AnswerDownvoted),
+            "answer_accepted" => Ok(ReputationReason::AnswerAccepted),
+            _ => Err(format!("Unknown reputation reason: {}", reason)),
+        }
+    }
+}
+
+impl PrivilegeThresholds {
+    pub fn required_reputation(&self, privilege: Privilege) -> i32 {
+        match privilege {
+            Privilege::EditOthers => self.edit_others,
+            Privilege::DeleteOthers => self.delete_others,
+        }
+    }
+
+    pub fn allows(&self, privilege: Privilege, reputation: i32) -> bool {
+        reputation >= self.required_reputation(privilege)
+    }
+}
+
+/// Ledger entries for the content author when a voter goes from `previous` to `current`
+pub fn vote_reputation_changes(
+    target: &VoteTarget,
+    previous: Option<VoteDirection>,
+    current: Option<VoteDirection>,
+) -> Vec<(ReputationReason, i32)> {
+    if previous == current {
+        return vec![];
+    }
+    let mut changes = vec![];
+    if let Some(previous) = previous {
+        let reason = ReputationReason::for_vote(target, previous);
+        changes.push((reason, -reason.points()));
+    }
+    if let Some(current) = current {
+        let reason = ReputationReason::for_vote(target, current);
+        changes.push((reason, reason.points()));
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vote_reputation_changes_reverse_previous_vote() {
+        let target = VoteTarget::Answer(AnswerId(1));
+        let changes = vote_reputation_changes(
+            &target,
+            Some(VoteDirection::Down),
+            Some(VoteDirection::Up),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                (ReputationReason::AnswerDownvoted, 2),
+                (ReputationReason::AnswerUpvoted, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vote_reputation_changes_repeated_vote() {
+        let target = VoteTarget::Question(QuestionId(1));
+        let up = Some(VoteDirection::Up);
+        assert!(vote_reputation_changes(&target, up, up).is_empty());
+        assert_eq!(
+            vote_reputation_changes(&target, up, None),
+            vec![(ReputationReason::QuestionUpvoted, -5)]
+        );
+    }
+
+    #[test]
+    fn test_privilege_thresholds() {
+        let thresholds = PrivilegeThresholds {
+            edit_others: 1000,
+            delete_others: 5000,
+        };
+        assert!(!thresholds.allows(Privilege::EditOthers, 999));
+        assert!(thresholds.allows(Privilege::EditOthers, 1000));
+        assert!(!thresholds.allows(Privilege::DeleteOthers, 1000));
+    }
+}
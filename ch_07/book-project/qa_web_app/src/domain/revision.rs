@@ -0,0 +1,162 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use similar::TextDiff;
+use utoipa::ToSchema;
+
+use crate::domain::account::AccountId;
+use crate::domain::answer::AnswerId;
+use crate::domain::question::QuestionId;
+
+/// Snapshot of a question as it was after one of its edits
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuestionRevision {
+    pub question_id: QuestionId,
+    pub revision: i32,
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    pub account_id: Option<AccountId>,
+    pub created_on: NaiveDateTime,
+}
+
+/// Snapshot of an answer as it was after one of its edits
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnswerRevision {
+    pub answer_id: AnswerId,
+    pub revision: i32,
+    pub content: String,
+    pub account_id: Option<AccountId>,
+    pub created_on: NaiveDateTime,
+}
+
+/// Changes between two revisions of the same question or answer.
+/// Text fields hold a unified diff and are left out when unchanged.
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub tags_added: Vec<String>,
+    pub tags_removed: Vec<String>,
+}
+
+/// Revision together with the changes it introduced over the previous one
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[aliases(
+    QuestionHistoryEntry = RevisionHistoryEntry<QuestionRevision>,
+    AnswerHistoryEntry = RevisionHistoryEntry<AnswerRevision>
+)]
+pub struct RevisionHistoryEntry<T> {
+    #[serde(flatten)]
+    pub revision: T,
+    pub changes: Option<RevisionDiff>,
+}
+
+pub trait Revision {
+    fn diff(&self, other: &Self) -> RevisionDiff;
+}
+
+impl Revision for QuestionRevision {
+    fn diff(&self, other: &Self) -> RevisionDiff {
+        let (tags_added, tags_removed) = tags_diff(&self.tags, &other.tags);
+        RevisionDiff {
+            from: self.revision,
+            to: other.revision,
+            title: text_diff(&self.title, &other.title, self.revision, other.revision),
+            content: text_diff(&self.content, &other.content, self.revision, other.revision),
+            tags_added,
+            tags_removed,
+        }
+    }
+}
+
+impl Revision for AnswerRevision {
+    fn diff(&self, other: &Self) -> RevisionDiff {
+        RevisionDiff {
+            from: self.revision,
+            to: other.revision,
+            title: None,
+            content: text_diff(&self.content, &other.content, self.revision, other.revision),
+            tags_added: vec![],
+            tags_removed: vec![],
+        }
+    }
+}
+
+/// Pairs every revision (sorted by number) with the diff against its predecessor
+pub fn history<T: Revision>(revisions: Vec<T>) -> Vec<RevisionHistoryEntry<T>> {
+    let mut entries: Vec<RevisionHistoryEntry<T>> = Vec::with_capacity(revisions.len());
+    for revision in revisions {
+        let changes = entries.last().map(|previous| previous.revision.diff(&revision));
+        entries.push(RevisionHistoryEntry { revision, changes });
+    }
+    entries
+}
+
+fn text_diff(old: &str, new: &str, from: i32, to: i32) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    Some(
+        TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(&format!("revision {}", from), &format!("revision {}", to))
+            .to_string(),
+    )
+}
+
+fn tags_diff(
+    old: &Option<Vec<String>>,
+    new: &Option<Vec<String>>,
+) -> (Vec<String>, Vec<String>) {
+    let old = old.clone().unwrap_or_default();
+    let new = new.clone().unwrap_or_default();
+    let added = new.iter().filter(|tag| !old.contains(tag)).cloned().collect();
+    let removed = old.iter().filter(|tag| !new.contains(tag)).cloned().collect();
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question_revision(revision: i32, title: &str, tags: Vec<&str>) -> QuestionRevision {
+        QuestionRevision {
+            question_id: QuestionId(1),
+            revision,
+            title: title.to_string(),
+            content: "content".to_string(),
+            tags: Some(tags.into_iter().map(String::from).collect()),
+            account_id: Some(AccountId(1)),
+            created_on: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_only_reports_changed_fields() {
+        let first = question_revision(1, "How?", vec!["rust", "warp"]);
+        let second = question_revision(2, "How to?", vec!["rust", "sqlx"]);
+        let diff = first.diff(&second);
+        assert!(diff.title.unwrap().contains("-How?\n\\ No newline at end of file\n+How to?"));
+        assert_eq!(diff.content, None);
+        assert_eq!(diff.tags_added, vec!["sqlx".to_string()]);
+        assert_eq!(diff.tags_removed, vec!["warp".to_string()]);
+    }
+
+    #[test]
+    fn test_history_diffs_against_previous_revision() {
+        let entries = history(vec![
+            question_revision(1, "How?", vec![]),
+            question_revision(2, "How to?", vec![]),
+            question_revision(3, "How to?", vec!["rust"]),
+        ]);
+        assert!(entries[0].changes.is_none());
+        assert_eq!(entries[1].changes.as_ref().unwrap().from, 1);
+        assert!(entries[1].changes.as_ref().unwrap().title.is_some());
+        assert!(entries[2].changes.as_ref().unwrap().title.is_none());
+        assert_eq!(entries[2].changes.as_ref().unwrap().tags_added, vec!["rust".to_string()]);
+    }
+}
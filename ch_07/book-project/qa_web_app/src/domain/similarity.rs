@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::domain::question::{Question, QuestionId};
+
+/// Candidates scoring below this are not reported as similar
+const MIN_SCORE: f64 = 0.3;
+/// Words too common to tell two questions apart
+const STOP_WORDS: [&str; 16] = [
+    "a", "an", "and", "are", "can", "do", "does", "for", "how", "i", "in", "is", "of", "the",
+    "to", "what",
+];
+
+/// Existing question resembling one being written
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SimilarQuestion {
+    pub id: QuestionId,
+    pub title: String,
+    /// Between 0 (nothing in common) and 1 (same words and title)
+    pub score: f64,
+}
+
+/// Scores the candidates found by trigram similarity of their titles against the title
+/// and, when given, content being written, and keeps the best `limit` of them
+pub fn rank_similar(
+    title: &str,
+    content: Option<&str>,
+    candidates: Vec<(Question, f64)>,
+    limit: usize,
+) -> Vec<SimilarQuestion> {
+    let mut words = tokens(title);
+    if let Some(content) = content {
+        words.extend(tokens(content));
+    }
+    let mut similar: Vec<SimilarQuestion> = candidates
+        .into_iter()
+        .map(|(question, trigram)| {
+            let mut candidate_words = tokens(&question.title);
+            if content.is_some() {
+                candidate_words.extend(tokens(&question.content));
+            }
+            SimilarQuestion {
+                score: (trigram + token_overlap(&words, &candidate_words)) / 2.0,
+                id: question.id,
+                title: question.title,
+            }
+        })
+        .filter(|similar| similar.score >= MIN_SCORE)
+        .collect();
+    similar.sort_by(|a, b| b.score.total_cmp(&a.score));
+    similar.truncate(limit);
+    similar
+}
+
+/// Lowercased words of the text, without punctuation and stop words
+fn tokens(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Jaccard index of the two sets of words
+fn token_overlap(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::question::QuestionStatus;
+
+    use super::*;
+
+    fn question(id: i32, title: &str, content: &str) -> Question {
+        Question {
+            id: QuestionId(id),
+            title: title.to_string(),
+            content: content.to_string(),
+            content_html: String::new(),
+            tags: None,
+            account_id: None,
+            accepted_answer_id: None,
+            score: 0,
+            bookmark_count: 0,
+            status: QuestionStatus::Open,
+            close_reason: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_tokens_ignore_case_punctuation_and_stop_words() {
+        let words = tokens("How do I parse JSON in Rust?");
+        let expected: HashSet<String> = ["parse", "json", "rust"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(words, expected);
+    }
+
+    #[test]
+    fn test_rank_similar_orders_by_score_and_drops_unrelated() {
+        let candidates = vec![
+            (question(1, "Parsing JSON with serde", "serde json rust"), 0.4),
+            (question(2, "How to parse JSON in Rust", "parse json rust"), 0.9),
+            (question(3, "Cooking pasta", "water salt"), 0.3),
+        ];
+        let similar = rank_similar("Parse JSON in Rust", Some("How can I parse json in rust"), candidates, 5);
+        let ids: Vec<i32> = similar.iter().map(|s| s.id.0).collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert!(similar[0].score > 0.9);
+    }
+}
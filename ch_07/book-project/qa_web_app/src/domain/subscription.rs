@@ -0,0 +1,51 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::domain::question::QuestionId;
+
+/// Longest tag that can be followed, in characters, matching the `subscriptions.tag` column
+pub const TAG_MAX_LENGTH: usize = 64;
+
+/// Question or tag an account follows
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionTarget {
+    Question(QuestionId),
+    Tag(String),
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Subscription {
+    #[serde(flatten)]
+    pub target: SubscriptionTarget,
+    pub created_on: NaiveDateTime,
+}
+
+impl SubscriptionTarget {
+    /// Tag target normalized like question tags, trimmed and lowercased, rejecting blank or
+    /// overlong tags
+    pub fn tag(tag: &str) -> Result<Self, String> {
+        let tag = tag.trim().to_lowercase();
+        let length = tag.chars().count();
+        if length == 0 || length > TAG_MAX_LENGTH {
+            return Err(format!(
+                "Tag must be between 1 and {} characters, got {}",
+                TAG_MAX_LENGTH, length
+            ));
+        }
+        Ok(SubscriptionTarget::Tag(tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_target() {
+        assert_eq!(SubscriptionTarget::tag(" Rust "), Ok(SubscriptionTarget::Tag("rust".to_string())));
+        assert!(SubscriptionTarget::tag("  ").is_err());
+        assert!(SubscriptionTarget::tag(&"a".repeat(TAG_MAX_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std::ops::RangeInclusive;
+
+use errors::FieldError;
+
+/// Collects the invalid fields of a request body so that they are all reported at once
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    pub fn error(&mut self, field: &'static str, message: String) {
+        self.errors.push(FieldError { field, message });
+    }
+
+    /// Trims the text and checks its length in characters. Control characters are refused,
+    /// apart from line breaks and tabs in `multiline` texts.
+    pub fn text(
+        &mut self,
+        field: &'static str,
+        text: &str,
+        length: RangeInclusive<usize>,
+        multiline: bool,
+    ) -> String {
+        let text = text.trim();
+        let count = text.chars().count();
+        if !length.contains(&count) {
+            self.error(
+                field,
+                format!(
+                    "Must be between {} and {} characters, got {}",
+                    length.start(),
+                    length.end(),
+                    count
+                ),
+            );
+        }
+        let allowed = |c: &char| multiline && matches!(c, '\n' | '\r' | '\t');
+        if text.chars().any(|c| c.is_control() && !allowed(&c)) {
+            self.error(field, "Must not contain control characters".to_string());
+        }
+        text.to_string()
+    }
+
+    /// Returns `value` when no field was refused, the list of invalid fields otherwise
+    pub fn finish<T>(self, value: T) -> Result<T, Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_reports_every_error() {
+        let mut validator = Validator::new();
+        assert_eq!(validator.text("title", "  Title  ", 1..=10, false), "Title");
+        validator.text("title", "Line\nbreak", 1..=5, false);
+        validator.text("content", "Line\nbreak", 1..=20, true);
+        let errors = validator.finish(()).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|error| error.field == "title"));
+    }
+}
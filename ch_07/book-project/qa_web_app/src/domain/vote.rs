@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::answer::AnswerId;
+use crate::domain::question::QuestionId;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteDirection {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct VoteDraft {
+    pub direction: VoteDirection,
+}
+
+/// Question or answer a vote is cast on
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VoteTarget {
+    Question(QuestionId),
+    Answer(AnswerId),
+}
+
+/// Score of a question or answer after a vote, together with the voter's current vote
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Score {
+    pub score: i32,
+    pub vote: Option<VoteDirection>,
+}
+
+impl VoteDirection {
+    pub fn value(&self) -> i16 {
+        match self {
+            VoteDirection::Up => 1,
+            VoteDirection::Down => -1,
+        }
+    }
+
+    pub fn from_value(value: i16) -> Option<Self> {
+        match value {
+            1 => Some(VoteDirection::Up),
+            -1 => Some(VoteDirection::Down),
+            _ => None,
+        }
+    }
+}
+
+impl VoteTarget {
+    /// Table holding the voted content
+    pub fn table(&self) -> &'static str {
+        match self {
+            VoteTarget::Question(_) => "questions",
+            VoteTarget::Answer(_) => "answers",
+        }
+    }
+
+    /// Column of the voted content's table holding the id of the question it belongs to
+    pub fn question_column(&self) -> &'static str {
+        match self {
+            VoteTarget::Question(_) => "id",
+            VoteTarget::Answer(_) => "question_id",
+        }
+    }
+
+    /// Column of the `votes` table referencing the voted content
+    pub fn column(&self) -> &'static str {
+        match self {
+            VoteTarget::Question(_) => "question_id",
+            VoteTarget::Answer(_) => "answer_id",
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            VoteTarget::Question(id) => id.0,
+            VoteTarget::Answer(id) => id.0,
+        }
+    }
+}
+
+/// Change in score when a voter goes from `previous` to `current`
+pub fn score_delta(previous: Option<VoteDirection>, current: Option<VoteDirection>) -> i32 {
+    let value = |vote: Option<VoteDirection>| vote.map_or(0, |vote| vote.value() as i32);
+    value(current) - value(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_delta_is_idempotent() {
+        assert_eq!(score_delta(None, Some(VoteDirection::Up)), 1);
+        assert_eq!(score_delta(Some(VoteDirection::Up), Some(VoteDirection::Up)), 0);
+        assert_eq!(score_delta(Some(VoteDirection::Down), None), 1);
+        assert_eq!(score_delta(None, None), 0);
+    }
+
+    #[test]
+    fn test_score_delta_switching_direction() {
+        assert_eq!(score_delta(Some(VoteDirection::Down), Some(VoteDirection::Up)), 2);
+        assert_eq!(score_delta(Some(VoteDirection::Up), Some(VoteDirection::Down)), -2);
+    }
+}
@@ -0,0 +1,191 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Failed deliveries are retried until this many attempts were made, then dead-lettered
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 8;
+/// Wait before the first retry, doubled for every further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+/// Longest wait between two attempts
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Activity that webhooks can be registered for
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    QuestionCreated,
+    AnswerAdded,
+    AnswerAccepted,
+}
+
+/// Endpoint registered by an admin to receive the selected events. The signing secret
+/// is only returned when the webhook is created.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Webhook {
+    pub id: WebhookId,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub active: bool,
+    pub created_on: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
+pub struct WebhookId(pub i32);
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct WebhookDraft {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Secret used to sign the payloads, generated when not given
+    pub secret: Option<String>,
+}
+
+/// Response to registering a webhook, the only one revealing its secret
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NewWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    /// Gave up after `MAX_DELIVERY_ATTEMPTS` failed attempts
+    Dead,
+}
+
+/// Entry of a webhook's delivery log
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: DeliveryId,
+    pub webhook_id: WebhookId,
+    pub event: WebhookEvent,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_on: NaiveDateTime,
+    pub delivered_on: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Hash, ToSchema)]
+pub struct DeliveryId(pub i32);
+
+/// Delivery claimed by the worker, with what it needs to send it
+#[derive(Debug, Clone)]
+pub struct PendingDelivery {
+    pub id: DeliveryId,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Outcome of one delivery attempt
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DeliveryOutcome {
+    Delivered { status_code: u16 },
+    Failed { status_code: Option<u16>, error: String },
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::QuestionCreated => "question_created",
+            WebhookEvent::AnswerAdded => "answer_added",
+            WebhookEvent::AnswerAccepted => "answer_accepted",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(event: &str) -> Result<Self, Self::Err> {
+        match event {
+            "question_created" => Ok(WebhookEvent::QuestionCreated),
+            "answer_added" => Ok(WebhookEvent::AnswerAdded),
+            "answer_accepted" => Ok(WebhookEvent::AnswerAccepted),
+            _ => Err(format!("Unknown webhook event: {}", event)),
+        }
+    }
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Dead => "dead",
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "dead" => Ok(DeliveryStatus::Dead),
+            _ => Err(format!("Unknown delivery status: {}", status)),
+        }
+    }
+}
+
+impl WebhookDraft {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.url.starts_with("https://") || self.url.starts_with("http://")) {
+            return Err(format!("Webhook URL must use http or https: {}", self.url));
+        }
+        if self.events.is_empty() {
+            return Err("Webhook must subscribe to at least one event".to_string());
+        }
+        if self.secret.as_ref().is_some_and(|secret| secret.len() < 16) {
+            return Err("Webhook secret must be at least 16 characters".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Wait before retrying a delivery that failed for the `attempts`th time
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 16) as u32 - 1;
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.pow(exponent))
+        .min(RETRY_MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_is_exponential_and_capped() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(4), Duration::from_secs(240));
+        assert_eq!(retry_delay(MAX_DELIVERY_ATTEMPTS * 4), RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_webhook_draft_validation() {
+        let draft = |url: &str, events: Vec<WebhookEvent>| WebhookDraft {
+            url: url.to_string(),
+            events,
+            secret: None,
+        };
+        assert!(draft("https://tools.internal/hook", vec![WebhookEvent::AnswerAdded]).validate().is_ok());
+        assert!(draft("ftp://tools.internal/hook", vec![WebhookEvent::AnswerAdded]).validate().is_err());
+        assert!(draft("https://tools.internal/hook", vec![]).validate().is_err());
+    }
+}
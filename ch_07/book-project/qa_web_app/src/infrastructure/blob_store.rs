@@ -0,0 +1,106 @@
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures_util::Stream;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+use tokio_util::io::ReaderStream;
+use warp::hyper::body::Bytes;
+
+/// Content of a blob, read as it is sent
+pub type BlobStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
+
+/// Storage of attachment contents by key, the `attachments` table keeping what they are
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, content: Bytes) -> io::Result<()>;
+
+    /// Streams `length` bytes of the blob starting at `offset`
+    async fn get(&self, key: &str, offset: u64, length: u64) -> io::Result<BlobStream>;
+
+    /// Removes the blob, succeeding if it is already gone
+    async fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+/// Blobs kept as files under a local directory, spread over subdirectories named after the
+/// first two characters of their keys
+#[derive(Debug, Clone)]
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: PathBuf) -> Self {
+        LocalBlobStore { root }
+    }
+
+    /// Keys are generated by the application, anything else could point out of `root`
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        if key.len() < 3 || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid blob key: {}", key),
+            ));
+        }
+        Ok(self.root.join(&key[..2]).join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, content: Bytes) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory).await?;
+        }
+        // Renaming once written so that a blob is never read half written
+        let partial = path.with_extension("part");
+        tokio::fs::write(&partial, &content).await?;
+        tokio::fs::rename(&partial, &path).await
+    }
+
+    async fn get(&self, key: &str, offset: u64, length: u64) -> io::Result<BlobStream> {
+        let mut file = tokio::fs::File::open(self.path(key)?).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        Ok(Box::pin(ReaderStream::new(file.take(length))))
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_blob_store() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let blobs = LocalBlobStore::new(root.clone());
+        let key = uuid::Uuid::new_v4().to_string();
+        blobs.put(&key, Bytes::from_static(b"0123456789")).await.unwrap();
+
+        let read = |offset, length| {
+            let (blobs, key) = (&blobs, &key);
+            async move {
+                let chunks: Vec<Bytes> = blobs.get(key, offset, length).await?.try_collect().await?;
+                Ok::<_, io::Error>(chunks.concat())
+            }
+        };
+        assert_eq!(read(0, 10).await.unwrap(), b"0123456789");
+        assert_eq!(read(3, 4).await.unwrap(), b"3456");
+
+        blobs.delete(&key).await.unwrap();
+        assert!(read(0, 10).await.is_err());
+        blobs.delete(&key).await.unwrap();
+        assert!(blobs.path("../../etc/passwd").is_err());
+        tokio::fs::remove_dir_all(root).await.unwrap();
+    }
+}
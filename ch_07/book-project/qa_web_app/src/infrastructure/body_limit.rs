@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use warp::http::header::CONTENT_LENGTH;
+use warp::{Filter, Rejection};
+
+use errors::Error;
+
+/// Limit of the routes without their own entry and no `default` one, in bytes
+const DEFAULT_BODY_LIMIT: u64 = 16 * 1024;
+
+/// Filter refusing bodies longer than the route's limit, see `route_limit`. Like
+/// `warp::body::content_length_limit`, it requires a `Content-Length` so that chunked
+/// bodies cannot get around the limit.
+pub fn body_limit(
+    limits: &HashMap<String, u64>,
+    route: &str,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let limit = route_limit(limits, route);
+    warp::header::optional::<u64>(CONTENT_LENGTH.as_str())
+        .and_then(move |length: Option<u64>| async move {
+            match length {
+                Some(length) if length <= limit => Ok(()),
+                Some(length) => {
+                    log::warn!("Refused body of {} bytes, limit is {}", length, limit);
+                    Err(warp::reject::custom(Error::PayloadTooLarge(limit)))
+                }
+                None => Err(warp::reject::custom(Error::LengthRequired)),
+            }
+        })
+        .untuple_one()
+}
+
+/// Limit of the route, falling back to the `default` entry
+pub fn route_limit(limits: &HashMap<String, u64>, route: &str) -> u64 {
+    limits
+        .get(route)
+        .or_else(|| limits.get("default"))
+        .copied()
+        .unwrap_or(DEFAULT_BODY_LIMIT)
+}
+
+/// Parses `route=<bytes>` entries separated by commas, e.g. `add_question=65536,default=16384`
+pub fn parse_body_limits(limits: &str) -> Result<HashMap<String, u64>, String> {
+    limits
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (route, limit) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid body limit entry: {}", entry))?;
+            let limit = limit
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid body limit: {}, expected bytes", limit))?;
+            Ok((route.trim().to_string(), limit))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_body_limit() {
+        let limits = parse_body_limits("add_question=10, default=5").unwrap();
+        let filter = body_limit(&limits, "add_question");
+        let request = || warp::test::request().method("POST");
+        assert!(request().header("content-length", "10").filter(&filter).await.is_ok());
+        let rejection = request().header("content-length", "11").filter(&filter).await.unwrap_err();
+        assert!(matches!(rejection.find::<Error>(), Some(Error::PayloadTooLarge(10))));
+        let rejection = request().filter(&filter).await.unwrap_err();
+        assert!(matches!(rejection.find::<Error>(), Some(Error::LengthRequired)));
+
+        let filter = body_limit(&limits, "add_answer");
+        assert!(request().header("content-length", "6").filter(&filter).await.is_err());
+        assert!(parse_body_limits("add_question=lots").is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgListener, PgPoolOptions};
+
+use crate::domain::answer::AnswerId;
+use crate::domain::event::{EventDraft, EventKind};
+use crate::domain::question::QuestionId;
+use crate::infrastructure::read_cache::Invalidation;
+use crate::infrastructure::store::Store;
+
+/// Postgres channel the instances send their changes on
+pub const CHANNEL: &str = "qa_changes";
+/// Longest wait between two attempts to open the `LISTEN` connection
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Write committed by one instance, sent to the others with `NOTIFY` so that they drop
+/// their outdated cached reads and pass the event on to their own listeners
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Change {
+    /// Instance that made the change, which already applied it
+    pub origin: String,
+    pub invalidation: Option<Invalidation>,
+    pub event: Option<ChangeEvent>,
+}
+
+/// Event without the question or answer content, which would not fit in a notification
+/// payload. Receivers read the content back from the database.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChangeEvent {
+    pub kind: EventKind,
+    pub question_id: QuestionId,
+    pub answer_id: Option<AnswerId>,
+    pub tags: Vec<String>,
+    pub score: Option<i32>,
+}
+
+impl From<&EventDraft> for ChangeEvent {
+    fn from(draft: &EventDraft) -> Self {
+        ChangeEvent {
+            kind: draft.kind,
+            question_id: draft.question_id.clone(),
+            answer_id: draft.answer_id.clone(),
+            tags: draft.tags.clone(),
+            score: draft.score,
+        }
+    }
+}
+
+/// Listens for the changes of the other instances and applies them locally, reopening
+/// the connection whenever it is lost. Since changes sent meanwhile are missed, the whole
+/// cache is dropped every time the connection is opened.
+pub async fn run_change_listener(store: Store) {
+    let mut delay = Duration::from_secs(1);
+    loop {
+        let mut listener = match connect(&store).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("Error listening for changes: {}, retrying in {:?}", e, delay);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                continue;
+            }
+        };
+        delay = Duration::from_secs(1);
+        store.cache.invalidate(&Invalidation::All);
+        log::info!("Listening for changes on {}", CHANNEL);
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(notification)) => match serde_json::from_str::<Change>(notification.payload()) {
+                    Ok(change) if change.origin == store.instance_id => {}
+                    Ok(change) => apply(&store, change).await,
+                    Err(e) => log::warn!("Invalid change notification: {}", e),
+                },
+                Ok(None) => {
+                    log::warn!("Change listener connection lost, reconnecting");
+                    break;
+                }
+                Err(e) => {
+                    log::error!("Error receiving changes: {}, reconnecting", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Listens on a connection of its own, as it is held for as long as the listener runs and
+/// would otherwise leave the store's pool one connection short
+async fn connect(store: &Store) -> Result<PgListener, sqlx::Error> {
+    let options = store.connection.connect_options().as_ref().clone();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .max_lifetime(None)
+        .idle_timeout(None)
+        .connect_with(options)
+        .await?;
+    let mut listener = PgListener::connect_with(&pool).await?;
+    listener.listen(CHANNEL).await?;
+    Ok(listener)
+}
+
+/// Applies another instance's change: drops the outdated cached reads first so that the
+/// content read back for the event is current
+async fn apply(store: &Store, change: Change) {
+    if let Some(invalidation) = &change.invalidation {
+        store.cache.invalidate(invalidation);
+    }
+    let event = match change.event {
+        Some(event) => event,
+        None => return,
+    };
+    let mut draft = EventDraft {
+        kind: event.kind,
+        question_id: event.question_id,
+        answer_id: event.answer_id,
+        tags: event.tags,
+        question: None,
+        answer: None,
+        score: event.score,
+    };
+    match draft.kind {
+        EventKind::QuestionCreated | EventKind::QuestionUpdated | EventKind::QuestionRestored => {
+            match store.get_question(draft.question_id.clone()).await {
+                Ok(question) => draft.question = Some(question),
+                // Deleted since, its own event follows
+                Err(e) => {
+                    log::debug!("Dropping change to question {}: {}", draft.question_id.0, e);
+                    return;
+                }
+            }
+        }
+        EventKind::AnswerCreated | EventKind::AnswerUpdated | EventKind::AnswerRestored => {
+            let answer_id = match &draft.answer_id {
+                Some(answer_id) => answer_id.clone(),
+                None => return,
+            };
+            match store.get_answer(answer_id.clone()).await {
+                Ok(answer) => draft.answer = Some(answer),
+                Err(e) => {
+                    log::debug!("Dropping change to answer {}: {}", answer_id.0, e);
+                    return;
+                }
+            }
+        }
+        _ => {}
+    }
+    store.events.publish(draft);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_payload_round_trip() {
+        let change = Change {
+            origin: "instance".to_string(),
+            invalidation: Some(Invalidation::Question(QuestionId(3))),
+            event: Some(ChangeEvent {
+                kind: EventKind::QuestionVoted,
+                question_id: QuestionId(3),
+                answer_id: None,
+                tags: vec!["rust".to_string()],
+                score: Some(2),
+            }),
+        };
+        let payload = serde_json::to_string(&change).unwrap();
+        assert!(payload.contains(r#""invalidation":{"scope":"question","question_id":3}"#));
+        let change: Change = serde_json::from_str(&payload).unwrap();
+        assert_eq!(change.invalidation, Some(Invalidation::Question(QuestionId(3))));
+        assert_eq!(change.event.unwrap().kind, EventKind::QuestionVoted);
+    }
+}
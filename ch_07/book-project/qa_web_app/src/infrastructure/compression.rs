@@ -0,0 +1,135 @@
+use async_compression::tokio::bufread::{BrotliEncoder, DeflateEncoder, GzipEncoder};
+use futures_util::TryStreamExt;
+use tokio_util::io::{ReaderStream, StreamReader};
+use warp::http::header::{ACCEPT_RANGES, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, VARY};
+use warp::http::{HeaderValue, Response, StatusCode};
+use warp::hyper::Body;
+use warp::Reply;
+
+/// Content codings the responses can be compressed with, by order of preference
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+}
+
+/// Picks the coding the client prefers in its `Accept-Encoding`, by quality value and then
+/// by our preference. `*` stands for any coding not listed, `q=0` refuses a coding.
+pub fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+    let mut qualities: Vec<(&str, f32)> = accept_encoding
+        .split(',')
+        .filter_map(|coding| {
+            let mut parts = coding.split(';').map(str::trim);
+            let name = parts.next().filter(|name| !name.is_empty())?;
+            let quality = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+            Some((name, quality))
+        })
+        .collect();
+    qualities.iter_mut().for_each(|(name, _)| {
+        if name.eq_ignore_ascii_case("x-gzip") {
+            *name = "gzip";
+        }
+    });
+    let quality = |encoding: Encoding| {
+        qualities
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(encoding.as_str()))
+            .or_else(|| qualities.iter().find(|(name, _)| *name == "*"))
+            .map_or(0.0, |(_, quality)| *quality)
+    };
+    [Encoding::Brotli, Encoding::Gzip, Encoding::Deflate]
+        .into_iter()
+        .map(|encoding| (encoding, quality(encoding)))
+        .filter(|(_, quality)| *quality > 0.0)
+        // max_by keeps the last of equal qualities, so go through them in reverse
+        .rev()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(encoding, _)| encoding)
+}
+
+/// Compresses the reply with the coding negotiated from the `Accept-Encoding` header.
+/// Event streams, which must reach the client as they are written, replies serving byte
+/// ranges, which refer to the uncompressed content, and replies without a body are sent as
+/// they are.
+pub fn compress(accept_encoding: Option<String>, reply: impl Reply) -> Response<Body> {
+    let mut response = reply.into_response();
+    response.headers_mut().append(VARY, HeaderValue::from_static("accept-encoding"));
+    let encoding = match accept_encoding.as_deref().and_then(negotiate) {
+        Some(encoding) => encoding,
+        None => return response,
+    };
+    let streaming = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"text/event-stream"));
+    let without_body = matches!(
+        response.status(),
+        StatusCode::SWITCHING_PROTOCOLS | StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED
+    );
+    let ranged = response.headers().contains_key(ACCEPT_RANGES);
+    if streaming || ranged || without_body || response.headers().contains_key(CONTENT_ENCODING) {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let reader = StreamReader::new(body.map_err(std::io::Error::other));
+    let body = match encoding {
+        Encoding::Brotli => Body::wrap_stream(ReaderStream::new(BrotliEncoder::new(reader))),
+        Encoding::Gzip => Body::wrap_stream(ReaderStream::new(GzipEncoder::new(reader))),
+        Encoding::Deflate => Body::wrap_stream(ReaderStream::new(DeflateEncoder::new(reader))),
+    };
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use async_compression::tokio::bufread::GzipDecoder;
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(negotiate("deflate, gzip;q=0.8"), Some(Encoding::Deflate));
+        assert_eq!(negotiate("br;q=0, *;q=0.5"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("identity"), None);
+        assert_eq!(negotiate("gzip;q=0"), None);
+        assert_eq!(negotiate(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_compress_gzip() {
+        let body = "question ".repeat(100);
+        let response = compress(Some("gzip".to_string()), body.clone());
+        assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[VARY], "accept-encoding");
+        let compressed = warp::hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(compressed.len() < body.len());
+        let mut decompressed = String::new();
+        GzipDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .await
+            .unwrap();
+        assert_eq!(decompressed, body);
+
+        let response = compress(None, body);
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+    }
+}
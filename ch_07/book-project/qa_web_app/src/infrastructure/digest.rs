@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use crate::domain::email::Email;
+use crate::infrastructure::mailer::Mailer;
+use crate::infrastructure::store::Store;
+
+/// Periodically emails the accounts whose digest is due a summary of their unread
+/// notifications
+pub async fn run_digest_job(store: Store, mailer: Mailer, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let recipients = match store.get_digest_recipients().await {
+            Ok(recipients) => recipients,
+            Err(e) => {
+                log::error!("Error getting digest recipients: {}", e);
+                continue;
+            }
+        };
+        for (account, last_digest_on) in recipients {
+            let items = match store.get_digest_items(account.id, last_digest_on).await {
+                Ok(items) => items,
+                Err(e) => {
+                    log::error!("Error getting digest of account {}: {}", account.id, e);
+                    continue;
+                }
+            };
+            if !items.is_empty() {
+                let email = Email::digest(&items, &mailer.app_url);
+                if let Err(e) = mailer.send(&account.email, email).await {
+                    log::error!("Error sending digest to account {}: {}", account.id, e);
+                    continue;
+                }
+            }
+            if let Err(e) = store.mark_digest_sent(account.id).await {
+                log::error!("Error marking digest of account {} as sent: {}", account.id, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast;
+
+use crate::domain::event::{Event, EventDraft, EventId};
+
+/// Fan-out of content changes to `/events` listeners, keeping the latest events in memory
+/// so that reconnecting listeners can resume from their `Last-Event-ID`
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+    replay: Arc<Mutex<Replay>>,
+}
+
+#[derive(Debug)]
+struct Replay {
+    last_id: u64,
+    capacity: usize,
+    events: VecDeque<Event>,
+}
+
+impl EventBus {
+    /// Creates a bus replaying at most `capacity` events to reconnecting listeners
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+        EventBus {
+            sender,
+            replay: Arc::new(Mutex::new(Replay {
+                last_id: 0,
+                capacity,
+                events: VecDeque::with_capacity(capacity),
+            })),
+        }
+    }
+
+    pub fn publish(&self, draft: EventDraft) -> Event {
+        // Sending while holding the lock keeps `subscribe` from missing or repeating events
+        let mut replay = self.replay.lock().unwrap();
+        replay.last_id += 1;
+        let event = Event::new(EventId(replay.last_id), draft);
+        if replay.events.len() == replay.capacity {
+            replay.events.pop_front();
+        }
+        replay.events.push_back(event.clone());
+        // Sending only fails when nobody is listening
+        let _ = self.sender.send(event.clone());
+        event
+    }
+
+    /// Starts listening, returning the buffered events published after `last_event_id`
+    /// (none without it) followed by a receiver for the ones still to come. Events that
+    /// already left the buffer cannot be replayed.
+    pub fn subscribe(
+        &self,
+        last_event_id: Option<EventId>,
+    ) -> (Vec<Event>, broadcast::Receiver<Event>) {
+        let replay = self.replay.lock().unwrap();
+        let missed = match last_event_id {
+            Some(last_event_id) => replay
+                .events
+                .iter()
+                .filter(|event| event.id > last_event_id)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        (missed, self.sender.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::event::EventDraft;
+    use crate::domain::question::QuestionId;
+
+    use super::*;
+
+    fn draft(question_id: i32) -> EventDraft {
+        EventDraft::question_deleted(QuestionId(question_id), None)
+    }
+
+    #[test]
+    fn test_replay_is_bounded() {
+        let bus = EventBus::new(2);
+        for question_id in 1..=3 {
+            bus.publish(draft(question_id));
+        }
+        let (missed, _) = bus.subscribe(Some(EventId(0)));
+        let ids: Vec<EventId> = missed.iter().map(|event| event.id).collect();
+        assert_eq!(ids, vec![EventId(2), EventId(3)]);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_resumes_after_last_event_id() {
+        let bus = EventBus::new(10);
+        bus.publish(draft(1));
+        bus.publish(draft(2));
+        let (missed, mut receiver) = bus.subscribe(Some(EventId(1)));
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].question_id, QuestionId(2));
+        assert!(bus.subscribe(None).0.is_empty());
+        bus.publish(draft(3));
+        assert_eq!(receiver.recv().await.unwrap().id, EventId(3));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Timelike};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use warp::http::header::{
+    CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use warp::http::{HeaderValue, Response, StatusCode};
+use warp::hyper::Body;
+use warp::{Filter, Rejection};
+
+/// Format of the dates in `Last-Modified` and `If-Modified-Since`, always in GMT
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+/// Directives sent on the routes without their own entry and no `default` one
+const DEFAULT_CACHE_CONTROL: &str = "no-cache";
+
+/// Validators sent by the client for a cacheable route, along with the `Cache-Control`
+/// directives configured for it
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    pub cache_control: String,
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+}
+
+impl HttpCache {
+    /// Serializes the body to JSON with a strong `ETag` of its content and, when known,
+    /// its `Last-Modified` date, in UTC. Replies `304 Not Modified` without a body when the
+    /// client's validators still match.
+    pub fn reply<T: Serialize>(&self, body: &T, last_modified: Option<NaiveDateTime>) -> Response<Body> {
+        let body = match serde_json::to_vec(body) {
+            Ok(body) => body,
+            Err(e) => {
+                log::error!("Error serializing response: {}", e);
+                let mut response = Response::new(Body::empty());
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                return response;
+            }
+        };
+        let etag = etag(&body);
+        // HTTP dates have no fractional seconds
+        let last_modified = last_modified.and_then(|date| date.with_nanosecond(0));
+        let mut response = if self.is_fresh(&etag, last_modified) {
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            response
+        } else {
+            let mut response = Response::new(Body::from(body));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            response
+        };
+        let headers = response.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&etag) {
+            headers.insert(ETAG, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            let date = last_modified.format(HTTP_DATE).to_string();
+            if let Ok(date) = HeaderValue::from_str(&date) {
+                headers.insert(LAST_MODIFIED, date);
+            }
+        }
+        if let Ok(cache_control) = HeaderValue::from_str(&self.cache_control) {
+            headers.insert(CACHE_CONTROL, cache_control);
+        }
+        response
+    }
+
+    /// Whether the client's copy is still current. `If-Modified-Since` is only looked at
+    /// when no `If-None-Match` was sent.
+    fn is_fresh(&self, etag: &str, last_modified: Option<NaiveDateTime>) -> bool {
+        if let Some(if_none_match) = &self.if_none_match {
+            return if_none_match.split(',').map(str::trim).any(|candidate| {
+                candidate == "*" || candidate.trim_start_matches("W/") == etag
+            });
+        }
+        let since = self
+            .if_modified_since
+            .as_deref()
+            .and_then(|date| NaiveDateTime::parse_from_str(date, HTTP_DATE).ok());
+        match (since, last_modified) {
+            (Some(since), Some(last_modified)) => last_modified <= since,
+            _ => false,
+        }
+    }
+}
+
+/// Strong entity tag of a response body, the start of its SHA-256 digest in quotes
+fn etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    format!("\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Filter extracting the `If-None-Match` and `If-Modified-Since` headers of the request
+/// for the route, falling back to the `default` entry for its `Cache-Control` directives
+pub fn http_cache(
+    cache_control: &HashMap<String, String>,
+    route: &str,
+) -> impl Filter<Extract = (HttpCache,), Error = Rejection> + Clone {
+    let cache_control = cache_control
+        .get(route)
+        .or_else(|| cache_control.get("default"))
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_CACHE_CONTROL.to_string());
+    warp::header::optional::<String>(IF_NONE_MATCH.as_str())
+        .and(warp::header::optional::<String>(IF_MODIFIED_SINCE.as_str()))
+        .map(move |if_none_match, if_modified_since| HttpCache {
+            cache_control: cache_control.clone(),
+            if_none_match,
+            if_modified_since,
+        })
+}
+
+/// Parses `route=<directives>` entries separated by semicolons, since directives are
+/// themselves separated by commas, e.g. `get_questions=public, max-age=10;default=no-cache`
+pub fn parse_cache_control(entries: &str) -> Result<HashMap<String, String>, String> {
+    entries
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (route, directives) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid cache control entry: {}", entry))?;
+            HeaderValue::from_str(directives.trim())
+                .map_err(|_| format!("Invalid cache control directives: {}", directives))?;
+            Ok((route.trim().to_string(), directives.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(if_none_match: Option<&str>, if_modified_since: Option<&str>) -> HttpCache {
+        HttpCache {
+            cache_control: "no-cache".to_string(),
+            if_none_match: if_none_match.map(str::to_string),
+            if_modified_since: if_modified_since.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_reply_not_modified_when_etag_matches() {
+        let body = vec!["question"];
+        let etag = etag(&serde_json::to_vec(&body).unwrap());
+        let response = cache(Some(&format!("\"other\", W/{}", etag)), None).reply(&body, None);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], etag.as_str());
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+
+        let response = cache(Some("\"other\""), None).reply(&body, None);
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_reply_not_modified_since() {
+        let last_modified = NaiveDateTime::parse_from_str("2024-02-25 10:00:00.5", "%Y-%m-%d %H:%M:%S%.f").unwrap();
+        let response = cache(None, Some("Sun, 25 Feb 2024 10:00:00 GMT")).reply(&"body", Some(last_modified));
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[LAST_MODIFIED], "Sun, 25 Feb 2024 10:00:00 GMT");
+
+        let response = cache(None, Some("Sun, 25 Feb 2024 09:59:59 GMT")).reply(&"body", Some(last_modified));
+        assert_eq!(response.status(), StatusCode::OK);
+        // If-None-Match takes precedence over If-Modified-Since
+        let response = cache(Some("\"other\""), Some("Sun, 25 Feb 2024 10:00:00 GMT")).reply(&"body", Some(last_modified));
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_parse_cache_control() {
+        let entries = parse_cache_control("get_questions=public, max-age=10; default=no-cache").unwrap();
+        assert_eq!(entries.get("get_questions").map(String::as_str), Some("public, max-age=10"));
+        assert_eq!(entries.get("default").map(String::as_str), Some("no-cache"));
+        assert!(parse_cache_control("get_questions").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::domain::live::LiveMessage;
+use crate::domain::question::QuestionId;
+use crate::infrastructure::events::EventBus;
+
+/// Broadcast hub for live question pages, with one room per question that exists only
+/// while somebody is watching it
+#[derive(Clone, Debug)]
+pub struct LiveHub {
+    rooms: Arc<Mutex<HashMap<QuestionId, broadcast::Sender<LiveMessage>>>>,
+    capacity: usize,
+    /// Time between two pings sent to every connection
+    pub heartbeat: Duration,
+    /// Time without hearing from a client after which its connection is dropped
+    pub timeout: Duration,
+}
+
+impl LiveHub {
+    pub fn new(capacity: usize, heartbeat: Duration, timeout: Duration) -> Self {
+        LiveHub {
+            rooms: Arc::new(Mutex::new(HashMap::new())),
+            capacity: capacity.max(1),
+            heartbeat,
+            timeout,
+        }
+    }
+
+    pub fn join(&self, question_id: &QuestionId) -> broadcast::Receiver<LiveMessage> {
+        let mut rooms = self.rooms.lock().unwrap();
+        match rooms.get(question_id) {
+            Some(room) => room.subscribe(),
+            None => {
+                let (room, receiver) = broadcast::channel(self.capacity);
+                rooms.insert(question_id.clone(), room);
+                receiver
+            }
+        }
+    }
+
+    /// Closes the room of the question once its last receiver is dropped
+    pub fn leave(&self, question_id: &QuestionId, receiver: broadcast::Receiver<LiveMessage>) {
+        let mut rooms = self.rooms.lock().unwrap();
+        drop(receiver);
+        if rooms
+            .get(question_id)
+            .is_some_and(|room| room.receiver_count() == 0)
+        {
+            rooms.remove(question_id);
+        }
+    }
+
+    /// Sends the message to everyone watching the question, if anybody is
+    pub fn send(&self, question_id: &QuestionId, message: LiveMessage) {
+        if let Some(room) = self.rooms.lock().unwrap().get(question_id) {
+            // Sending only fails when the last receiver left in the meantime
+            let _ = room.send(message);
+        }
+    }
+
+    /// Forwards the changes published by the store to the rooms of their questions
+    pub async fn forward(self, events: EventBus) {
+        let (_, mut receiver) = events.subscribe(None);
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let question_id = event.question_id.clone();
+                    self.send(&question_id, LiveMessage::Event(Box::new(event)))
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Live hub lagging, skipped {} events", skipped)
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::account::AccountId;
+
+    use super::*;
+
+    fn typing() -> LiveMessage {
+        LiveMessage::Typing {
+            account_id: AccountId(1),
+            typing: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_messages_stay_in_their_room() {
+        let hub = LiveHub::new(10, Duration::from_secs(30), Duration::from_secs(90));
+        let mut first = hub.join(&QuestionId(1));
+        let mut second = hub.join(&QuestionId(2));
+        hub.send(&QuestionId(1), typing());
+        assert!(matches!(first.recv().await, Ok(LiveMessage::Typing { .. })));
+        assert!(second.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_room_closes_when_empty() {
+        let hub = LiveHub::new(10, Duration::from_secs(30), Duration::from_secs(90));
+        let first = hub.join(&QuestionId(1));
+        let second = hub.join(&QuestionId(1));
+        hub.leave(&QuestionId(1), first);
+        assert!(hub.rooms.lock().unwrap().contains_key(&QuestionId(1)));
+        hub.leave(&QuestionId(1), second);
+        assert!(hub.rooms.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::{AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use errors::Error;
+
+use crate::domain::email::Email;
+
+/// Sends the emails rendered from the templates, through SMTP or into a local directory
+#[derive(Clone, Debug)]
+pub struct Mailer {
+    transport: Transport,
+    from: Mailbox,
+    /// Base URL of the application used in the links of the emails
+    pub app_url: String,
+}
+
+#[derive(Clone, Debug)]
+enum Transport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    /// Writes every email as an `.eml` file, for development without a mail server
+    File(AsyncFileTransport<Tokio1Executor>),
+}
+
+impl Mailer {
+    /// Sends through the SMTP server when a URL is given, otherwise into `mail_dir`
+    pub fn new(
+        smtp_url: Option<&str>,
+        mail_dir: &Path,
+        from: &str,
+        app_url: String,
+    ) -> Result<Self, Error> {
+        let from = from
+            .parse()
+            .map_err(|e| Error::MailError(format!("Invalid sender {}: {}", from, e)))?;
+        let transport = match smtp_url {
+            Some(url) => Transport::Smtp(
+                AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+                    .map_err(|e| Error::MailError(e.to_string()))?
+                    .build(),
+            ),
+            None => {
+                std::fs::create_dir_all(mail_dir)
+                    .map_err(|e| Error::MailError(e.to_string()))?;
+                log::info!("No SMTP server configured, writing emails to {}", mail_dir.display());
+                Transport::File(AsyncFileTransport::<Tokio1Executor>::new(mail_dir))
+            }
+        };
+        Ok(Mailer {
+            transport,
+            from,
+            app_url,
+        })
+    }
+
+    pub async fn send(&self, to: &str, email: Email) -> Result<(), Error> {
+        let to: Mailbox = to
+            .parse()
+            .map_err(|e| Error::MailError(format!("Invalid recipient {}: {}", to, e)))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .multipart(MultiPart::alternative_plain_html(email.text, email.html))
+            .map_err(|e| Error::MailError(e.to_string()))?;
+        match &self.transport {
+            Transport::Smtp(transport) => transport
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| Error::MailError(e.to_string())),
+            Transport::File(transport) => transport
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| Error::MailError(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_transport_writes_eml() {
+        let mail_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().simple().to_string());
+        let mailer = Mailer::new(
+            None,
+            &mail_dir,
+            "Q&A <no-reply@localhost>",
+            "http://localhost:3030".to_string(),
+        )
+        .unwrap();
+        let email = Email {
+            subject: "Hello".to_string(),
+            text: "Plain body".to_string(),
+            html: "<p>HTML body</p>".to_string(),
+        };
+        mailer.send("ada@example.com", email).await.unwrap();
+        let files: Vec<_> = std::fs::read_dir(&mail_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].extension().unwrap(), "eml");
+        let content = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(content.contains("To: ada@example.com"));
+        assert!(content.contains("Subject: Hello"));
+        assert!(content.contains("Plain body"));
+        std::fs::remove_dir_all(&mail_dir).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::OnceLock;
+
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{html, CodeBlockKind, Event, Parser, Tag};
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Prefix of the classes of highlighted code tokens, e.g. `hl-keyword`, which the clients
+/// style with the theme of their choice
+const HIGHLIGHT_CLASS_PREFIX: &str = "hl-";
+
+/// Renders CommonMark content to HTML safe to insert in a page. Fenced code blocks naming
+/// a known language are highlighted, and everything goes through the sanitizer last so
+/// that neither raw HTML in the source nor the highlighting can bring in scripts.
+pub fn render(source: &str) -> String {
+    let mut events = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+    for event in Parser::new(source) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    // Languages are names like `rust`, `c++` or `objective-c`, anything else
+                    // is left out of the class
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .filter(|language| {
+                            language
+                                .chars()
+                                .all(|c| c.is_ascii_alphanumeric() || "+#-._".contains(c))
+                        })
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                if let Some((language, code)) = code_block.take() {
+                    events.push(Event::Html(highlight(&language, &code).into()));
+                }
+            }
+            Event::Text(text) => match &mut code_block {
+                Some((_, code)) => code.push_str(&text),
+                None => events.push(Event::Text(text)),
+            },
+            event => events.push(event),
+        }
+    }
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events.into_iter());
+    ammonia::Builder::default()
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("span", &["class"])
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// Renders on the blocking pool, so that highlighting long code blocks does not hold up
+/// the async workers
+pub async fn render_blocking(source: String) -> String {
+    match tokio::task::spawn_blocking(move || render(&source)).await {
+        Ok(html) => html,
+        Err(e) => {
+            log::error!("Cannot render content: {}", e);
+            String::new()
+        }
+    }
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Code block with its tokens wrapped in classed spans, or only escaped when the language
+/// is unknown
+fn highlight(language: &str, code: &str) -> String {
+    // Writing to a String cannot fail
+    let mut escaped_code = String::new();
+    let _ = escape_html(&mut escaped_code, code);
+    if language.is_empty() {
+        return format!("<pre><code>{}</code></pre>\n", escaped_code);
+    }
+    format!(
+        "<pre><code class=\"language-{}\">{}</code></pre>\n",
+        language,
+        highlight_tokens(language, code).unwrap_or(escaped_code)
+    )
+}
+
+fn highlight_tokens(language: &str, code: &str) -> Option<String> {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes.find_syntax_by_token(language)?;
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(
+        syntax,
+        syntaxes,
+        ClassStyle::SpacedPrefixed {
+            prefix: HIGHLIGHT_CLASS_PREFIX,
+        },
+    );
+    for line in LinesWithEndings::from(code) {
+        if let Err(e) = generator.parse_html_for_line_which_includes_newline(line) {
+            log::warn!("Cannot highlight {} code: {}", language, e);
+            return None;
+        }
+    }
+    Some(generator.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        assert_eq!(render("Use **warp**"), "<p>Use <strong>warp</strong></p>\n");
+        let html = render("```rust\nfn main() {}\n```");
+        assert!(html.starts_with("<pre><code class=\"language-rust\"><span class=\"hl-source hl-rust\">"));
+        assert!(render("```\n<b>\n```").contains("&lt;b&gt;"));
+    }
+
+    #[test]
+    fn test_render_sanitizes_html() {
+        let html = render("<script>alert(1)</script>\n\n[link](javascript:alert(1))\n\n<img src=x onerror=alert(1)>");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("href"));
+        assert!(!html.contains("onerror"));
+        let html = render("```\"><script>alert(1)</script>\nx\n```");
+        assert_eq!(html, "<pre><code>x\n</code></pre>\n");
+    }
+}
@@ -1,3 +1,21 @@
+pub mod blob_store;
+pub mod body_limit;
+pub mod change_feed;
+pub mod compression;
+pub mod config;
+pub mod digest;
+pub mod events;
+pub mod http_cache;
+pub mod live;
+pub mod mailer;
+pub mod markdown;
+pub mod moderation;
+pub mod openapi;
 pub mod pagination;
+pub mod purge;
+pub mod rate_limit;
+pub mod read_cache;
 pub mod router;
 pub mod store;
+pub mod versioning;
+pub mod webhook;
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use errors::{APILayerError, Error};
+
+const BAD_WORDS_URL: &str = "https://api.apilayer.com/bad_words?censor_character=*";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct BadWord {
+    original: String,
+    word: String,
+    deviations: i64,
+    info: i64,
+    #[serde(rename = "replacedLen")]
+    replaced_len: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct BadWordsResponse {
+    content: String,
+    bad_words_total: i64,
+    bad_words_list: Vec<BadWord>,
+    censored_content: String,
+}
+
+/// Profanity filter backed by the APILayer bad words API
+#[derive(Clone, Debug)]
+pub struct Moderation {
+    client: reqwest::Client,
+    api_key: Option<String>,
+}
+
+impl Moderation {
+    pub fn new(client: reqwest::Client, api_key: Option<String>) -> Self {
+        Moderation { client, api_key }
+    }
+
+    /// Returns the content with profanity replaced by `*`. Without an API key configured
+    /// (e.g. in local development) the content is returned unchanged.
+    pub async fn censor(&self, content: String) -> Result<String, Error> {
+        let api_key = match &self.api_key {
+            Some(api_key) => api_key,
+            None => {
+                log::debug!("No bad words API key configured, skipping moderation");
+                return Ok(content);
+            }
+        };
+        let res = self
+            .client
+            .post(BAD_WORDS_URL)
+            .header("apikey", api_key)
+            .body(content)
+            .send()
+            .await
+            .map_err(Error::ExternalAPIError)?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let error = APILayerError {
+                status: status.as_u16(),
+                message: res.text().await.map_err(Error::ExternalAPIError)?,
+            };
+            log::error!("Bad words API error: {}", error);
+            return if status.is_client_error() {
+                Err(Error::ClientError(error))
+            } else {
+                Err(Error::ServerError(error))
+            };
+        }
+
+        match res.json::<BadWordsResponse>().await {
+            Ok(res) => Ok(res.censored_content),
+            Err(e) => Err(Error::ExternalAPIError(e)),
+        }
+    }
+}
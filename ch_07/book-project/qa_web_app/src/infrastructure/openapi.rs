@@ -0,0 +1,429 @@
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::openapi::Server;
+use utoipa::{Modify, OpenApi, ToSchema};
+
+use errors::responses::{
+    BadRequest, Forbidden, InvalidFields, LengthRequired, PayloadTooLarge, TooManyRequests,
+    Unauthorized, ValidationError,
+};
+use errors::{FieldError, ValidationFailure};
+
+use crate::domain::account::{
+    Account, AccountDraft, AccountId, PasswordReset, PasswordResetRequest, Role, Session,
+};
+use crate::domain::answer::{Answer, AnswerId};
+use crate::domain::attachment::{Attachment, AttachmentId};
+use crate::domain::bookmark::Bookmark;
+use crate::domain::comment::{Comment, CommentDraft, CommentId};
+use crate::domain::email::{DigestFrequency, EmailPreferences};
+use crate::domain::event::{Event, EventId, EventKind};
+use crate::domain::flag::{
+    Flag, FlagDraft, FlagId, FlagReason, FlagStatus, FlagTarget, ModerationAction,
+    ModerationDecision, ModerationLogEntry, QueueItem,
+};
+use crate::domain::notification::{Inbox, Notification, NotificationId, NotificationKind};
+use crate::domain::preview::{Preview, PreviewDraft};
+use crate::domain::question::{
+    AnswerThread, CloseDraft, CloseReason, NewQuestion, Question, QuestionDraft, QuestionId,
+    QuestionStatus, QuestionThread,
+};
+use crate::domain::reputation::{
+    Reputation, ReputationBreakdown, ReputationEvent, ReputationReason,
+};
+use crate::domain::revision::{
+    AnswerHistoryEntry, AnswerRevision, QuestionHistoryEntry, QuestionRevision, RevisionDiff,
+};
+use crate::domain::similarity::SimilarQuestion;
+use crate::domain::subscription::{Subscription, SubscriptionTarget};
+use crate::domain::vote::{Score, VoteDirection, VoteDraft};
+use crate::domain::webhook::{
+    DeliveryId, DeliveryStatus, NewWebhook, Webhook, WebhookDelivery, WebhookDraft, WebhookEvent,
+    WebhookId,
+};
+use crate::infrastructure::read_cache::CacheStats;
+use crate::infrastructure::router::{
+    answer, attachment, authentication, bookmark, cache, comment, email, event, flag, health, live,
+    notification, preview, question, reputation, revision, subscription, vote, webhook,
+};
+use crate::infrastructure::versioning::ApiVersion;
+
+/// OpenAPI document of every route, built from the `#[utoipa::path]` of their handlers and
+/// the schemas of the domain types they read and write
+#[derive(OpenApi)]
+#[openapi(
+    info(description = "Questions and answers, with comments, votes, moderation and notifications. \
+Routes are served under `/v1`. Without the prefix they answer the same, with `Deprecation` \
+and `Sunset` headers, until their sunset."),
+    paths(
+        question::get_questions,
+        question::get_similar_questions,
+        question::get_question,
+        question::add_question,
+        question::update_question,
+        question::delete_question,
+        question::restore_question,
+        question::close_question,
+        question::reopen_question,
+        question::lock_question,
+        question::accept_answer,
+        answer::get_answers,
+        answer::add_answer,
+        answer::update_answer,
+        answer::delete_answer,
+        answer::restore_answer,
+        revision::get_question_revisions,
+        revision::get_question_revisions_diff,
+        revision::rollback_question,
+        revision::get_answer_revisions,
+        revision::get_answer_revisions_diff,
+        revision::rollback_answer,
+        vote::vote_question,
+        vote::unvote_question,
+        vote::vote_answer,
+        vote::unvote_answer,
+        comment::add_question_comment,
+        comment::get_question_comments,
+        comment::add_answer_comment,
+        comment::get_answer_comments,
+        comment::update_comment,
+        comment::delete_comment,
+        attachment::add_question_attachment,
+        attachment::get_question_attachments,
+        attachment::add_answer_attachment,
+        attachment::get_answer_attachments,
+        attachment::get_attachment,
+        attachment::delete_attachment,
+        bookmark::add_bookmark,
+        bookmark::delete_bookmark,
+        bookmark::get_bookmarks,
+        subscription::follow_question,
+        subscription::unfollow_question,
+        subscription::follow_tag,
+        subscription::unfollow_tag,
+        subscription::get_subscriptions,
+        notification::get_notifications,
+        notification::mark_notification_read,
+        notification::mark_all_notifications_read,
+        email::get_email_preferences,
+        email::update_email_preferences,
+        flag::add_flag,
+        flag::get_moderation_queue,
+        flag::moderate,
+        flag::get_moderation_log,
+        event::get_events,
+        live::live_question,
+        webhook::add_webhook,
+        webhook::get_webhooks,
+        webhook::delete_webhook,
+        webhook::get_webhook_deliveries,
+        reputation::get_reputation,
+        reputation::recompute_reputation,
+        cache::get_cache_stats,
+        preview::preview,
+        authentication::register,
+        authentication::login,
+        authentication::request_password_reset,
+        authentication::reset_password,
+        health::health,
+    ),
+    components(
+        schemas(
+            Account, AccountDraft, AccountId, PasswordReset, PasswordResetRequest, Role, Session,
+            Answer, AnswerForm, AnswerId,
+            Attachment, AttachmentId, AttachmentUpload,
+            Bookmark,
+            Comment, CommentDraft, CommentId,
+            DigestFrequency, EmailPreferences,
+            Event, EventId, EventKind,
+            Flag, FlagDraft, FlagId, FlagReason, FlagStatus, FlagTarget, ModerationAction,
+            ModerationDecision, ModerationLogEntry, QueueItem,
+            Inbox, Notification, NotificationId, NotificationKind,
+            Preview, PreviewDraft,
+            AnswerThread, CloseDraft, CloseReason, NewQuestion, Question, QuestionDraft,
+            QuestionId, QuestionStatus, QuestionThread,
+            Reputation, ReputationBreakdown, ReputationEvent, ReputationReason,
+            AnswerHistoryEntry, AnswerRevision, QuestionHistoryEntry, QuestionRevision,
+            RevisionDiff,
+            SimilarQuestion,
+            Subscription, SubscriptionTarget,
+            Score, VoteDirection, VoteDraft,
+            DeliveryId, DeliveryStatus, NewWebhook, Webhook, WebhookDelivery, WebhookDraft,
+            WebhookEvent, WebhookId,
+            CacheStats,
+            FieldError, ValidationFailure,
+        ),
+        responses(
+            BadRequest, Forbidden, InvalidFields, LengthRequired, PayloadTooLarge,
+            TooManyRequests, Unauthorized, ValidationError,
+        )
+    ),
+    modifiers(&SessionToken, &Versions),
+    tags(
+        (name = "questions"),
+        (name = "answers"),
+        (name = "revisions", description = "Edit history, diffs and rollbacks"),
+        (name = "votes"),
+        (name = "comments"),
+        (name = "attachments"),
+        (name = "bookmarks"),
+        (name = "subscriptions", description = "Followed questions and tags"),
+        (name = "notifications"),
+        (name = "moderation", description = "Flags and the moderators' decisions"),
+        (name = "events", description = "Live changes over Server-Sent Events and WebSockets"),
+        (name = "webhooks"),
+        (name = "reputation"),
+        (name = "accounts"),
+        (name = "cache"),
+        (name = "health"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Form body of the routes adding and editing answers, read as a map of fields
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AnswerForm {
+    content: String,
+}
+
+/// Multipart body of the attachment uploads, whose `file` part is read as it arrives
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AttachmentUpload {
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+/// Version prefix of the documented routes, apart from the health check served at the root
+struct Versions;
+
+impl Modify for Versions {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.servers = Some(vec![Server::new(format!("/{}", ApiVersion::V1.prefix()))]);
+        if let Some(health) = openapi.paths.paths.get_mut("/health") {
+            health.servers = Some(vec![Server::new("/")]);
+        }
+    }
+}
+
+/// Session token of `POST /login`, sent bare or with a `Bearer` prefix
+struct SessionToken;
+
+impl Modify for SessionToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "token",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Routes served under their own path rather than documented in the spec
+    const UNDOCUMENTED: [&str; 2] = ["openapi", "docs"];
+
+    /// What the spec must say about a route of `main.rs`
+    #[derive(Debug, PartialEq)]
+    struct Route {
+        handler: String,
+        body: Option<&'static str>,
+        authenticated: bool,
+        body_limit: bool,
+        rate_limited: bool,
+    }
+
+    /// Routes declared in `main.rs`, keyed by method and path with their parameters left
+    /// unnamed, read from its source so that the spec cannot fall behind them
+    fn declared_routes() -> BTreeMap<(String, String), Route> {
+        let mut routes = BTreeMap::new();
+        for statement in include_str!("../main.rs").split("\n    let ").skip(1) {
+            let (name, statement) = statement.split_once(' ').unwrap();
+            let statement = statement.split(";\n").next().unwrap();
+            if !statement.contains("warp::path(") || UNDOCUMENTED.contains(&name) {
+                continue;
+            }
+            // Routes without a method filter answer GET, e.g. WebSocket upgrades
+            let method = ["get", "post", "put", "delete", "patch"]
+                .into_iter()
+                .find(|method| statement.contains(&format!("warp::{}()", method)))
+                .unwrap_or("get");
+            let mut path = String::new();
+            let mut rest = statement;
+            while let Some(start) = rest.find("warp::path") {
+                rest = &rest[start + "warp::path".len()..];
+                if let Some(segment) = rest.strip_prefix("(\"") {
+                    path.push('/');
+                    path.push_str(&segment[..segment.find('"').unwrap()]);
+                } else if rest.starts_with("::param") {
+                    path.push_str("/{}");
+                }
+            }
+            let call = match statement.split_once("with_rate_limit(limit, ") {
+                Some((_, call)) => call,
+                None => statement.rsplit(".and_then(").next().unwrap(),
+            };
+            let handler = call
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .next()
+                .unwrap()
+                .to_string();
+            let body = if statement.contains("warp::body::json()") {
+                Some("application/json")
+            } else if statement.contains("warp::body::form()") {
+                Some("application/x-www-form-urlencoded")
+            } else if statement.contains("warp::multipart::form()") {
+                Some("multipart/form-data")
+            } else {
+                None
+            };
+            let route = Route {
+                handler,
+                body,
+                authenticated: statement.contains("auth(store.clone())"),
+                body_limit: statement.contains("body_limit(") || statement.contains("route_limit("),
+                rate_limited: statement.contains("rate_limit("),
+            };
+            let key = (method.to_string(), path);
+            assert!(!routes.contains_key(&key), "{:?} is declared twice", key);
+            routes.insert(key, route);
+        }
+        routes
+    }
+
+    /// Names of the routes `main.rs` serves, read from the `v1` chain and the chain that
+    /// adds the unversioned routes to it
+    fn served_routes() -> Vec<&'static str> {
+        let source = include_str!("../main.rs");
+        ["\n    let v1 = ", "\n    let routes = api"]
+            .into_iter()
+            .flat_map(|start| {
+                let chain = source.split_once(start).unwrap().1;
+                chain.split(';').next().unwrap().split(".or(").map(|route| {
+                    route
+                        .trim_start()
+                        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .next()
+                        .unwrap()
+                })
+            })
+            .filter(|name| !name.is_empty() && !UNDOCUMENTED.contains(name))
+            .collect()
+    }
+
+    fn spec() -> Value {
+        serde_json::to_value(ApiDoc::openapi()).unwrap()
+    }
+
+    #[test]
+    fn test_spec_matches_routes() {
+        let spec = spec();
+        let mut documented = BTreeMap::new();
+        let mut operation_count = 0;
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            // Path items also hold their `servers`, next to one operation per method
+            let operations = item
+                .as_object()
+                .unwrap()
+                .iter()
+                .filter(|(key, _)| *key != "servers");
+            for (method, operation) in operations {
+                for parameter in path.split('/').filter(|segment| segment.starts_with('{')) {
+                    let name = parameter.trim_matches(|c| c == '{' || c == '}');
+                    let declared = operation["parameters"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .any(|parameter| parameter["name"] == name && parameter["in"] == "path");
+                    assert!(
+                        declared,
+                        "{} {} does not describe {}",
+                        method, path, parameter
+                    );
+                }
+                let responses = operation["responses"].as_object().unwrap();
+                let unnamed_path: Vec<&str> = path
+                    .split('/')
+                    .map(|segment| {
+                        if segment.starts_with('{') {
+                            "{}"
+                        } else {
+                            segment
+                        }
+                    })
+                    .collect();
+                let route = Route {
+                    handler: operation["operationId"].as_str().unwrap().to_string(),
+                    body: operation["requestBody"]["content"]
+                        .as_object()
+                        .and_then(|content| content.keys().next())
+                        .map(|content_type| match content_type.as_str() {
+                            "application/json" => "application/json",
+                            "application/x-www-form-urlencoded" => {
+                                "application/x-www-form-urlencoded"
+                            }
+                            "multipart/form-data" => "multipart/form-data",
+                            content_type => panic!("Unexpected request body {}", content_type),
+                        }),
+                    authenticated: operation.get("security").is_some(),
+                    body_limit: responses.contains_key("411") && responses.contains_key("413"),
+                    rate_limited: responses.contains_key("429"),
+                };
+                documented.insert((method.clone(), unnamed_path.join("/")), route);
+                operation_count += 1;
+            }
+        }
+        let declared = declared_routes();
+        // Every served route must have been read, and documented by exactly one operation
+        assert_eq!(declared.len(), served_routes().len());
+        assert_eq!(documented.len(), operation_count);
+        for (route, declared_route) in &declared {
+            assert_eq!(
+                documented.get(route),
+                Some(declared_route),
+                "{:?} differs",
+                route
+            );
+        }
+        for route in documented.keys() {
+            assert!(declared.contains_key(route), "{:?} is not served", route);
+        }
+        assert_eq!(
+            spec["servers"][0]["url"],
+            format!("/{}", ApiVersion::V1.prefix())
+        );
+        assert_eq!(spec["paths"]["/health"]["servers"][0]["url"], "/");
+    }
+
+    #[test]
+    fn test_spec_references_exist() {
+        fn references<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+            match value {
+                Value::Object(object) => {
+                    if let Some(Value::String(reference)) = object.get("$ref") {
+                        found.push(reference);
+                    }
+                    object.values().for_each(|value| references(value, found));
+                }
+                Value::Array(array) => array.iter().for_each(|value| references(value, found)),
+                _ => {}
+            }
+        }
+        let spec = spec();
+        let mut found = Vec::new();
+        references(&spec, &mut found);
+        assert!(!found.is_empty());
+        for reference in found {
+            let pointer = reference.strip_prefix('#').unwrap();
+            assert!(spec.pointer(pointer).is_some(), "Missing {}", reference);
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 
+use utoipa::IntoParams;
+
 use errors::Error;
 
 /// Pagination struct extracted from the query parameters
-#[derive(Default, Debug)]
+#[derive(Default, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct Pagination {
     /// The index where the page starts
     pub offset: Option<u32>,
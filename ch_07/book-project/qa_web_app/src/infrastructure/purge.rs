@@ -0,0 +1,38 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::infrastructure::blob_store::BlobStore;
+use crate::infrastructure::store::Store;
+
+/// Periodically removes soft deleted content older than the retention period, along with
+/// the files attached to it
+pub async fn run_purge_job(
+    store: Store,
+    blobs: Arc<dyn BlobStore>,
+    retention_days: i32,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match store.purge_attachments(retention_days).await {
+            Ok(keys) => {
+                for key in keys {
+                    if let Err(e) = blobs.delete(&key).await {
+                        log::error!("Error deleting blob {}: {}", key, e);
+                    }
+                }
+            }
+            Err(e) => log::error!("Error purging attachments: {}", e),
+        }
+        match store.purge_deleted(retention_days).await {
+            Ok((questions, answers)) => log::info!(
+                "Purged {} questions and {} answers deleted more than {} days ago",
+                questions,
+                answers,
+                retention_days
+            ),
+            Err(e) => log::error!("Error purging deleted content: {}", e),
+        }
+    }
+}
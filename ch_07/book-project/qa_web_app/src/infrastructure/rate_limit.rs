@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use warp::{Filter, Rejection, Reply};
+
+use errors::{Error, RateLimited};
+
+use crate::domain::account::Session;
+
+/// Buckets kept by a sweep, the least recently used being dropped beyond it
+const MAX_BUCKETS: usize = 10_000;
+
+/// Requests a client can send on a route per period, refilled continuously
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+/// Quota left to the client after an accepted request, sent back as `RateLimit-*` headers
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RateLimitStatus {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the quota is full again
+    pub reset: u64,
+}
+
+/// Token buckets of every client, keyed by route and by account or client address
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    limits: Arc<HashMap<String, RateLimit>>,
+    buckets: Arc<Mutex<HashMap<(&'static str, String), Bucket>>>,
+    /// Reverse proxies in front of the server whose `X-Forwarded-For` entries are trusted
+    trusted_proxy_hops: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimit {
+    /// Tokens added back per second
+    fn refill_rate(&self) -> f64 {
+        self.capacity as f64 / self.period.as_secs_f64()
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+
+    /// Parses `<requests>/<seconds>`, e.g. `10/60`
+    fn from_str(limit: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid rate limit: {}, expected <requests>/<seconds>", limit);
+        let (capacity, seconds) = limit.split_once('/').ok_or_else(invalid)?;
+        let capacity: u32 = capacity.trim().parse().map_err(|_| invalid())?;
+        let seconds: u64 = seconds.trim().parse().map_err(|_| invalid())?;
+        if capacity == 0 || seconds == 0 {
+            return Err(invalid());
+        }
+        Ok(RateLimit {
+            capacity,
+            period: Duration::from_secs(seconds),
+        })
+    }
+}
+
+impl Bucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Bucket {
+            tokens: limit.capacity as f64,
+            updated: now,
+        }
+    }
+
+    /// Refills the bucket for the time elapsed and takes a token if one is left
+    fn take(&mut self, limit: &RateLimit, now: Instant) -> Result<RateLimitStatus, RateLimited> {
+        let rate = limit.refill_rate();
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(limit.capacity as f64);
+        self.updated = now;
+        if self.tokens < 1.0 {
+            return Err(RateLimited {
+                limit: limit.capacity,
+                retry_after: ((1.0 - self.tokens) / rate).ceil() as u64,
+                reset: ((limit.capacity as f64 - self.tokens) / rate).ceil() as u64,
+            });
+        }
+        self.tokens -= 1.0;
+        Ok(RateLimitStatus {
+            limit: limit.capacity,
+            remaining: self.tokens.floor() as u32,
+            reset: ((limit.capacity as f64 - self.tokens) / rate).ceil() as u64,
+        })
+    }
+
+    fn is_full(&self, limit: &RateLimit, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * limit.refill_rate() >= limit.capacity as f64
+    }
+}
+
+impl RateLimiter {
+    /// `limits` maps route names to their limit, routes without one use the `default` entry.
+    /// Anonymous clients are told apart by the address the outermost of the
+    /// `trusted_proxy_hops` proxies saw, or by the remote address without proxies.
+    pub fn new(limits: HashMap<String, RateLimit>, trusted_proxy_hops: usize) -> Self {
+        RateLimiter {
+            limits: Arc::new(limits),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+            trusted_proxy_hops,
+        }
+    }
+
+    fn limit(&self, route: &str) -> Option<RateLimit> {
+        self.limits
+            .get(route)
+            .or_else(|| self.limits.get("default"))
+            .copied()
+    }
+
+    fn check(&self, route: &'static str, key: String) -> Result<RateLimitStatus, RateLimited> {
+        let limit = match self.limit(route) {
+            Some(limit) => limit,
+            None => {
+                return Ok(RateLimitStatus {
+                    limit: u32::MAX,
+                    remaining: u32::MAX,
+                    reset: 0,
+                })
+            }
+        };
+        let now = Instant::now();
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry((route, key))
+            .or_insert_with(|| Bucket::full(&limit, now))
+            .take(&limit, now)
+    }
+
+    /// Drops the buckets that refilled, as a new one would be full too, then the least
+    /// recently used ones beyond `MAX_BUCKETS`
+    fn sweep(&self, now: Instant) {
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets.retain(|(route, _), bucket| {
+            self.limit(route)
+                .is_some_and(|limit| !bucket.is_full(&limit, now))
+        });
+        if buckets.len() > MAX_BUCKETS {
+            let mut updated: Vec<Instant> = buckets.values().map(|bucket| bucket.updated).collect();
+            let excess = updated.len() - MAX_BUCKETS;
+            let (_, oldest_kept, _) = updated.select_nth_unstable(excess);
+            let oldest_kept = *oldest_kept;
+            buckets.retain(|_, bucket| bucket.updated >= oldest_kept);
+        }
+    }
+
+    /// Address of the client, read from the `X-Forwarded-For` entry added by the outermost
+    /// trusted proxy, the entries before it being whatever the client sent
+    fn client_ip(&self, forwarded_for: Option<&str>, remote: Option<SocketAddr>) -> Option<IpAddr> {
+        if self.trusted_proxy_hops == 0 {
+            return remote.map(|addr| addr.ip());
+        }
+        let hops: Vec<&str> = forwarded_for
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|hop| !hop.is_empty())
+            .collect();
+        hops.len()
+            .checked_sub(self.trusted_proxy_hops)
+            .and_then(|index| hops[index].parse().ok())
+            .or_else(|| remote.map(|addr| addr.ip()))
+    }
+}
+
+/// Sweeps the buckets of the rate limiter every `interval`, keeping the work and the memory
+/// off the requests
+pub async fn run_bucket_sweeper(limiter: RateLimiter, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        limiter.sweep(Instant::now());
+    }
+}
+
+/// Filter taking a token from the client's bucket for the route, rejecting with
+/// `TooManyRequests` once it is empty. Clients are keyed by address, see [`RateLimiter::new`].
+pub fn rate_limit(
+    limiter: RateLimiter,
+    route: &'static str,
+) -> impl Filter<Extract = (RateLimitStatus,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("X-Forwarded-For")
+        .and(warp::addr::remote())
+        .and_then(move |forwarded_for: Option<String>, addr: Option<SocketAddr>| {
+            let limiter = limiter.clone();
+            async move {
+                let key = match limiter.client_ip(forwarded_for.as_deref(), addr) {
+                    Some(ip) => format!("ip:{}", ip),
+                    None => "unknown".to_string(),
+                };
+                take_token(&limiter, route, key)
+            }
+        })
+}
+
+/// Like [`rate_limit`] for routes requiring a session, keyed by the account `auth` resolves
+/// and passing its session on
+pub fn account_rate_limit(
+    limiter: RateLimiter,
+    route: &'static str,
+    auth: impl Filter<Extract = (Session,), Error = Rejection> + Clone,
+) -> impl Filter<Extract = (RateLimitStatus, Session), Error = Rejection> + Clone {
+    auth.and_then(move |session: Session| {
+        let limiter = limiter.clone();
+        async move {
+            let key = format!("account:{}", session.account_id);
+            take_token(&limiter, route, key).map(|status| (status, session))
+        }
+    })
+    .untuple_one()
+}
+
+fn take_token(
+    limiter: &RateLimiter,
+    route: &'static str,
+    key: String,
+) -> Result<RateLimitStatus, Rejection> {
+    limiter.check(route, key).map_err(|limited| {
+        log::warn!("Rate limit on {} hit: {}", route, limited);
+        warp::reject::custom(Error::TooManyRequests(limited))
+    })
+}
+
+/// Awaits the route's handler and adds the `RateLimit-*` headers of the client's quota to
+/// its reply
+pub async fn with_rate_limit<R: Reply>(
+    status: RateLimitStatus,
+    handler: impl Future<Output = Result<R, Rejection>>,
+) -> Result<impl Reply, Rejection> {
+    let reply = handler.await?;
+    let reply = warp::reply::with_header(reply, "RateLimit-Limit", status.limit.to_string());
+    let reply = warp::reply::with_header(reply, "RateLimit-Remaining", status.remaining.to_string());
+    Ok(warp::reply::with_header(reply, "RateLimit-Reset", status.reset.to_string()))
+}
+
+/// Parses `route=<requests>/<seconds>` entries separated by commas, e.g.
+/// `add_question=5/60,default=30/60`
+pub fn parse_rate_limits(limits: &str) -> Result<HashMap<String, RateLimit>, String> {
+    limits
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (route, limit) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid rate limit entry: {}", entry))?;
+            Ok((route.trim().to_string(), limit.parse()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limit = RateLimit {
+            capacity: 2,
+            period: Duration::from_secs(10),
+        };
+        let start = Instant::now();
+        let mut bucket = Bucket::full(&limit, start);
+        assert_eq!(bucket.take(&limit, start).unwrap().remaining, 1);
+        assert_eq!(bucket.take(&limit, start).unwrap().remaining, 0);
+        let limited = bucket.take(&limit, start).unwrap_err();
+        assert_eq!(limited.retry_after, 5);
+        assert_eq!(limited.reset, 10);
+        assert!(bucket.take(&limit, start + Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn test_client_ip_behind_proxies() {
+        let remote: Option<SocketAddr> = Some("10.0.0.1:443".parse().unwrap());
+        let ip = |ip: &str| Some(ip.parse::<IpAddr>().unwrap());
+        let direct = RateLimiter::new(HashMap::new(), 0);
+        assert_eq!(direct.client_ip(Some("203.0.113.7"), remote), ip("10.0.0.1"));
+        let proxied = RateLimiter::new(HashMap::new(), 1);
+        assert_eq!(proxied.client_ip(Some("1.2.3.4, 203.0.113.7"), remote), ip("203.0.113.7"));
+        assert_eq!(proxied.client_ip(None, remote), ip("10.0.0.1"));
+        let two_proxies = RateLimiter::new(HashMap::new(), 2);
+        let forwarded_for = Some("1.2.3.4, 203.0.113.7, 10.0.0.2");
+        assert_eq!(two_proxies.client_ip(forwarded_for, remote), ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn test_sweep_drops_full_buckets() {
+        let limit = RateLimit {
+            capacity: 2,
+            period: Duration::from_secs(10),
+        };
+        let limiter = RateLimiter::new(HashMap::from([("default".to_string(), limit)]), 0);
+        limiter.check("login", "ip:1".to_string()).unwrap();
+        limiter.sweep(Instant::now());
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+        limiter.sweep(Instant::now() + Duration::from_secs(10));
+        assert!(limiter.buckets.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_rate_limits() {
+        let limits = parse_rate_limits("add_question=5/60, default=30/60").unwrap();
+        assert_eq!(
+            limits.get("add_question"),
+            Some(&RateLimit {
+                capacity: 5,
+                period: Duration::from_secs(60),
+            })
+        );
+        assert_eq!(limits.len(), 2);
+        assert!(parse_rate_limits("add_question=5").is_err());
+        assert!(parse_rate_limits("add_question=0/60").is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::question::{Question, QuestionFilter, QuestionId, QuestionSort};
+
+/// First page of a question listing, the only one worth caching
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct PageKey {
+    pub filter: QuestionFilter,
+    pub sort: QuestionSort,
+    pub limit: i32,
+}
+
+/// Cached reads made outdated by a write
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(tag = "scope", content = "question_id", rename_all = "snake_case")]
+pub enum Invalidation {
+    /// Listing pages, e.g. when answers change which questions are unanswered
+    Pages,
+    /// The question and every listing page, which may all include it
+    Question(QuestionId),
+    /// Everything, when changes may have been missed
+    All,
+    /// Only the `Last-Modified` dates, e.g. when comments change
+    Validators,
+}
+
+/// Hits and misses since startup, for `GET /cache/stats`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub questions: usize,
+    pub pages: usize,
+    pub capacity: usize,
+}
+
+/// Questions, first pages of question listings and their `Last-Modified` dates kept in
+/// memory in front of the database. Entries are dropped by the `Store` on every write to questions or answers,
+/// expire after `ttl` and the least recently used ones are evicted beyond `capacity`.
+#[derive(Clone, Debug)]
+pub struct ReadCache {
+    entries: Arc<Mutex<Entries>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+#[derive(Debug)]
+struct Entries {
+    questions: Lru<QuestionId, Question>,
+    pages: Lru<PageKey, Vec<Question>>,
+    /// `Last-Modified` of a question, or of the listings under `None`
+    last_modified: Lru<Option<QuestionId>, Option<NaiveDateTime>>,
+    /// Bumped on every invalidation so that reads started before it are not cached
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct Lru<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    /// Incremented on every access, the entry with the lowest one is the least recently used
+    clock: u64,
+    entries: HashMap<K, Entry<V>>,
+}
+
+#[derive(Debug)]
+struct Entry<V> {
+    value: V,
+    inserted: Instant,
+    used: u64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Lru<K, V> {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Lru {
+            capacity,
+            ttl,
+            clock: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &K, now: Instant) -> Option<V> {
+        self.clock += 1;
+        let expired = match self.entries.get_mut(key) {
+            Some(entry) if now.saturating_duration_since(entry.inserted) < self.ttl => {
+                entry.used = self.clock;
+                return Some(entry.value.clone());
+            }
+            Some(_) => true,
+            None => false,
+        };
+        if expired {
+            self.entries.remove(key);
+        }
+        None
+    }
+
+    fn put(&mut self, key: K, value: V, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            // Linear scan, the cache is small enough for it to stay cheap next to a query
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, Entry {
+            value,
+            inserted: now,
+            used: self.clock,
+        });
+    }
+}
+
+impl ReadCache {
+    /// Creates a cache of at most `capacity` questions and as many listing pages, `0`
+    /// disabling it
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        ReadCache {
+            entries: Arc::new(Mutex::new(Entries {
+                questions: Lru::new(capacity, ttl),
+                pages: Lru::new(capacity, ttl),
+                last_modified: Lru::new(capacity, ttl),
+                generation: 0,
+            })),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Current generation, to be passed back when caching what was read from the database
+    pub fn generation(&self) -> u64 {
+        self.entries.lock().unwrap().generation
+    }
+
+    pub fn get_question(&self, id: &QuestionId) -> Option<Question> {
+        let question = self.entries.lock().unwrap().questions.get(id, Instant::now());
+        self.count(question.is_some());
+        question
+    }
+
+    /// Caches the question unless something was written since `generation` was taken
+    pub fn put_question(&self, question: Question, generation: u64) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation == generation {
+            entries.questions.put(question.id.clone(), question, Instant::now());
+        }
+    }
+
+    pub fn get_page(&self, key: &PageKey) -> Option<Vec<Question>> {
+        let page = self.entries.lock().unwrap().pages.get(key, Instant::now());
+        self.count(page.is_some());
+        page
+    }
+
+    /// Caches the page unless something was written since `generation` was taken
+    pub fn put_page(&self, key: PageKey, questions: Vec<Question>, generation: u64) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation == generation {
+            entries.pages.put(key, questions, Instant::now());
+        }
+    }
+
+    /// `Last-Modified` of the question, or of the listings when `None`
+    pub fn get_last_modified(&self, id: Option<&QuestionId>) -> Option<Option<NaiveDateTime>> {
+        let key = id.cloned();
+        let last_modified = self.entries.lock().unwrap().last_modified.get(&key, Instant::now());
+        self.count(last_modified.is_some());
+        last_modified
+    }
+
+    /// Caches the `Last-Modified` date unless something was written since `generation` was
+    /// taken
+    pub fn put_last_modified(
+        &self,
+        id: Option<QuestionId>,
+        last_modified: Option<NaiveDateTime>,
+        generation: u64,
+    ) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation == generation {
+            entries.last_modified.put(id, last_modified, Instant::now());
+        }
+    }
+
+    /// Drops the entries outdated by a write, `Last-Modified` dates always being, and
+    /// listing pages unless only those dates changed
+    pub fn invalidate(&self, invalidation: &Invalidation) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.last_modified.entries.clear();
+        match invalidation {
+            Invalidation::Validators => return,
+            Invalidation::Pages => {}
+            Invalidation::Question(id) => {
+                entries.questions.entries.remove(id);
+            }
+            Invalidation::All => entries.questions.entries.clear(),
+        }
+        entries.pages.entries.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries.lock().unwrap();
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            questions: entries.questions.entries.len(),
+            pages: entries.pages.entries.len(),
+            capacity: entries.questions.capacity,
+        }
+    }
+
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::question::QuestionStatus;
+
+    use super::*;
+
+    fn question(id: i32) -> Question {
+        Question {
+            id: QuestionId(id),
+            title: "title".to_string(),
+            content: "content".to_string(),
+            content_html: String::new(),
+            tags: None,
+            account_id: None,
+            accepted_answer_id: None,
+            score: 0,
+            bookmark_count: 0,
+            status: QuestionStatus::Open,
+            close_reason: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn test_lru_evicts_least_recently_used_and_expired() {
+        let start = Instant::now();
+        let mut lru = Lru::new(2, Duration::from_secs(10));
+        lru.put(1, "one", start);
+        lru.put(2, "two", start);
+        assert_eq!(lru.get(&1, start), Some("one"));
+        lru.put(3, "three", start);
+        assert_eq!(lru.get(&2, start), None);
+        assert_eq!(lru.get(&1, start), Some("one"));
+        assert_eq!(lru.get(&3, start + Duration::from_secs(10)), None);
+        assert_eq!(lru.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_invalidation_drops_entries_and_stale_reads() {
+        let cache = ReadCache::new(10, Duration::from_secs(60));
+        let key = PageKey {
+            filter: QuestionFilter::All,
+            sort: QuestionSort::Oldest,
+            limit: 10,
+        };
+        let generation = cache.generation();
+        cache.put_question(question(1), generation);
+        cache.put_page(key, vec![question(1)], generation);
+        assert!(cache.get_question(&QuestionId(1)).is_some());
+
+        cache.invalidate(&Invalidation::Question(QuestionId(1)));
+        assert!(cache.get_question(&QuestionId(1)).is_none());
+        assert!(cache.get_page(&key).is_none());
+        // Read before the invalidation, so possibly outdated
+        cache.put_question(question(1), generation);
+        assert!(cache.get_question(&QuestionId(1)).is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 3));
+    }
+
+    #[test]
+    fn test_validators_dropped_on_every_invalidation() {
+        let cache = ReadCache::new(10, Duration::from_secs(60));
+        let date = chrono::DateTime::from_timestamp(1_700_000_000, 0).map(|date| date.naive_utc());
+        let generation = cache.generation();
+        cache.put_question(question(1), generation);
+        cache.put_last_modified(Some(QuestionId(1)), date, generation);
+        cache.put_last_modified(None, date, generation);
+        assert_eq!(cache.get_last_modified(Some(&QuestionId(1))), Some(date));
+
+        cache.invalidate(&Invalidation::Validators);
+        assert_eq!(cache.get_last_modified(Some(&QuestionId(1))), None);
+        assert_eq!(cache.get_last_modified(None), None);
+        assert!(cache.get_question(&QuestionId(1)).is_some());
+    }
+}
@@ -3,30 +3,288 @@ use std::collections::HashMap;
 use warp::{Rejection, Reply};
 use warp::http::StatusCode;
 
-use crate::domain::answer::AnswerDraft;
+use errors::Error;
+use errors::responses::{
+    BadRequest, Forbidden, InvalidFields, LengthRequired, PayloadTooLarge, TooManyRequests,
+    Unauthorized,
+};
+
+use crate::domain::account::Session;
+use crate::domain::answer::{validate_answer_content, Answer, AnswerDraft, AnswerId};
+use crate::domain::email::Email;
 use crate::domain::question::QuestionId;
+use crate::domain::reputation::{Privilege, PrivilegeThresholds};
+use crate::infrastructure::http_cache::HttpCache;
+use crate::infrastructure::mailer::Mailer;
+use crate::infrastructure::pagination::{extract_pagination, Pagination};
+use crate::infrastructure::router::question::require_unlocked;
+use crate::infrastructure::router::reputation::authorize;
 use crate::infrastructure::store::Store;
 
+#[utoipa::path(
+    get,
+    path = "/questions/{question_id}/answers",
+    tag = "answers",
+    params(
+        ("question_id" = i32, Path, description = "Question id"),
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Answers of the page", body = [Answer]),
+        (status = 304, description = "Unchanged since the `ETag` or `Last-Modified` sent back"),
+        (status = 400, response = BadRequest),
+        (status = 404, description = "Question not found", body = String)
+    )
+)]
+pub async fn get_answers(
+    question_id: i32,
+    params: HashMap<String, String>,
+    store: Store,
+    cache: HttpCache,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying answers...", &id);
+    let mut pagination = Pagination::default();
+    if !params.is_empty() {
+        log::debug!("{} - Pagination used", &id);
+        pagination = extract_pagination(params)?;
+    }
+    let last_modified = store
+        .get_question_last_modified(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    match store
+        .get_answers(QuestionId(question_id), pagination.limit, pagination.offset)
+        .await {
+        Ok(answers) => Ok(cache.reply(&answers, last_modified)),
+        Err(e) => {
+            log::error!("{} - Error getting answers: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/answers",
+    tag = "answers",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    request_body(content = AnswerForm, content_type = "application/x-www-form-urlencoded", description = "Content of the answer"),
+    responses(
+        (status = 201, description = "Answer added", body = String),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Question not found", body = String),
+        (status = 409, description = "Question closed to new answers or locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
 pub async fn add_answer(
     question_id: i32,
+    session: Session,
     store: Store,
+    mailer: Mailer,
     param: HashMap<String, String>,
     id: String,
 ) -> Result<impl Reply, Rejection> {
     log::info!("{} - Adding answer...", &id);
-    let answer_draft = AnswerDraft {
-        content: param.get("content").unwrap().to_string(),
-        question_id: QuestionId(question_id),
+    let content = match param.get("content") {
+        Some(content) => content,
+        None => return Err(warp::reject::custom(Error::MissingParameters)),
     };
-    match store.add_answer(answer_draft).await {
+    let answer_draft = AnswerDraft::new(content, QuestionId(question_id)).map_err(|errors| {
+        log::warn!("{} - Invalid answer: {:?}", &id, errors);
+        warp::reject::custom(Error::InvalidFields(errors))
+    })?;
+    let answer = match store.add_answer(answer_draft, session.account_id).await {
         Ok(answer) => answer,
         Err(e) => {
             log::error!("{} - Error adding answer: {}", &id, e);
             return Err(warp::reject::custom(e));
         }
     };
+    tokio::spawn(email_question_author(store, mailer, answer, id));
     Ok(warp::reply::with_status(
         "Answer added",
         StatusCode::CREATED,
     ))
 }
+
+#[utoipa::path(
+    put,
+    path = "/answers/{answer_id}",
+    tag = "answers",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    request_body(content = AnswerForm, content_type = "application/x-www-form-urlencoded", description = "New content of the answer"),
+    responses(
+        (status = 202, description = "Answer updated", body = String),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Answer not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields)
+    ),
+    security(("token" = []))
+)]
+pub async fn update_answer(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    thresholds: PrivilegeThresholds,
+    param: HashMap<String, String>,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Updating answer...", &id);
+    let content = match param.get("content") {
+        Some(content) => validate_answer_content(content).map_err(|errors| {
+            log::warn!("{} - Invalid answer: {:?}", &id, errors);
+            warp::reject::custom(Error::InvalidFields(errors))
+        })?,
+        None => return Err(warp::reject::custom(Error::MissingParameters)),
+    };
+    let answer = store
+        .get_answer(AnswerId(answer_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if let Err(e) = authorize(&store, &session, answer.account_id, Privilege::EditOthers, thresholds).await {
+        log::warn!("{} - Account {} cannot edit answer: {}", &id, session.account_id, e);
+        return Err(warp::reject::custom(e));
+    }
+    if let Err(e) = require_unlocked(&store, &session, answer.question_id.clone()).await {
+        log::warn!("{} - Cannot edit answer: {}", &id, e);
+        return Err(warp::reject::custom(e));
+    }
+    match store
+        .update_answer(answer.id, content, session.account_id)
+        .await {
+        Ok(_) => Ok(warp::reply::with_status(
+            "Answer updated",
+            StatusCode::ACCEPTED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error updating answer: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/answers/{answer_id}",
+    tag = "answers",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    responses(
+        (status = 204, description = "Answer deleted"),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Answer not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn delete_answer(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    thresholds: PrivilegeThresholds,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Deleting answer...", &id);
+    let answer = store
+        .get_answer(AnswerId(answer_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if let Err(e) = authorize(&store, &session, answer.account_id, Privilege::DeleteOthers, thresholds).await {
+        log::warn!("{} - Account {} cannot delete answer: {}", &id, session.account_id, e);
+        return Err(warp::reject::custom(e));
+    }
+    if let Err(e) = require_unlocked(&store, &session, answer.question_id.clone()).await {
+        log::warn!("{} - Cannot delete answer: {}", &id, e);
+        return Err(warp::reject::custom(e));
+    }
+    match store.delete_answer(answer.id, session.account_id).await {
+        Ok(_) => Ok(warp::reply::with_status(
+            "Answer deleted",
+            StatusCode::NO_CONTENT,
+        )),
+        Err(e) => {
+            log::error!("{} - Error deleting answer: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/answers/{answer_id}/restore",
+    tag = "answers",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    responses(
+        (status = 200, description = "Answer restored", body = Answer),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Answer not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn restore_answer(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Restoring answer...", &id);
+    if !session.is_moderator() {
+        log::warn!("{} - Account {} cannot restore answers", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    match store.restore_answer(AnswerId(answer_id)).await {
+        Ok(answer) => Ok(warp::reply::json(&answer)),
+        Err(e) => {
+            log::error!("{} - Error restoring answer: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Tells the author of the question about a new answer by email, unless they answered
+/// themselves or turned these emails off
+async fn email_question_author(store: Store, mailer: Mailer, answer: Answer, id: String) {
+    let question = match store.get_question(answer.question_id.clone()).await {
+        Ok(question) => question,
+        Err(e) => {
+            log::error!("{} - Error getting question to email: {}", &id, e);
+            return;
+        }
+    };
+    let author_id = match question.account_id {
+        Some(author_id) if Some(author_id) != answer.account_id => author_id,
+        _ => return,
+    };
+    let result = async {
+        if !store.get_email_preferences(author_id).await?.new_answers {
+            return Ok(());
+        }
+        let author = store.get_account(author_id).await?;
+        let email = Email::new_answer(&question, &answer, &mailer.app_url);
+        mailer.send(&author.email, email).await
+    };
+    if let Err(e) = result.await {
+        log::error!("{} - Error emailing account {} about new answer: {}", &id, author_id, e);
+    }
+}
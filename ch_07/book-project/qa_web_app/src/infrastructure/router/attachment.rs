@@ -0,0 +1,356 @@
+use std::sync::Arc;
+
+use futures_util::TryStreamExt;
+use warp::http::header::{
+    ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE,
+    CONTENT_SECURITY_POLICY, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS,
+};
+use warp::http::{HeaderValue, Response, StatusCode};
+use warp::hyper::body::{Body, Buf, Bytes};
+use warp::multipart::FormData;
+use warp::{Rejection, Reply};
+
+use errors::Error;
+use errors::responses::{
+    BadRequest, Forbidden, InvalidFields, LengthRequired, PayloadTooLarge, TooManyRequests,
+    Unauthorized,
+};
+
+use crate::domain::account::{AccountId, Session};
+use crate::domain::answer::AnswerId;
+use crate::domain::attachment::{AttachmentDraft, AttachmentId, AttachmentTarget, ByteRange};
+use crate::domain::question::QuestionId;
+use crate::infrastructure::blob_store::BlobStore;
+use crate::infrastructure::router::question::require_unlocked;
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/attachments",
+    tag = "attachments",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    request_body(content = AttachmentUpload, content_type = "multipart/form-data", description = "File in a `file` part"),
+    responses(
+        (status = 201, description = "File attached", body = Attachment),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
+pub async fn add_question_attachment(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    blobs: Arc<dyn BlobStore>,
+    form: FormData,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Adding question attachment...", &id);
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    check_unlocked(&store, &session, question.id.clone(), &id).await?;
+    let target = AttachmentTarget::Question(question.id);
+    add_attachment(target, question.account_id, session, store, blobs, form, id).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/answers/{answer_id}/attachments",
+    tag = "attachments",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    request_body(content = AttachmentUpload, content_type = "multipart/form-data", description = "File in a `file` part"),
+    responses(
+        (status = 201, description = "File attached", body = Attachment),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Answer not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
+pub async fn add_answer_attachment(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    blobs: Arc<dyn BlobStore>,
+    form: FormData,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Adding answer attachment...", &id);
+    let answer = store
+        .get_answer(AnswerId(answer_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    check_unlocked(&store, &session, answer.question_id.clone(), &id).await?;
+    let target = AttachmentTarget::Answer(answer.id);
+    add_attachment(target, answer.account_id, session, store, blobs, form, id).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/questions/{question_id}/attachments",
+    tag = "attachments",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Files attached to the question", body = [Attachment]),
+        (status = 404, description = "Question not found", body = String)
+    )
+)]
+pub async fn get_question_attachments(
+    question_id: i32,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying question attachments...", &id);
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    get_attachments(AttachmentTarget::Question(question.id), store, id).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/answers/{answer_id}/attachments",
+    tag = "attachments",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    responses(
+        (status = 200, description = "Files attached to the answer", body = [Attachment]),
+        (status = 404, description = "Answer not found", body = String)
+    )
+)]
+pub async fn get_answer_attachments(
+    answer_id: i32,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying answer attachments...", &id);
+    let answer = store
+        .get_answer(AnswerId(answer_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    get_attachments(AttachmentTarget::Answer(answer.id), store, id).await
+}
+
+/// Sends the file, or the single byte range asked for with a `Range` header. The stored
+/// content type is enforced and scripts are kept from running should a file be opened in
+/// the browser.
+#[utoipa::path(
+    get,
+    path = "/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("attachment_id" = i32, Path, description = "Attachment id"),
+        ("Range" = Option<String>, Header, description = "Single `bytes` range, e.g. `bytes=0-1023`")
+    ),
+    responses(
+        (status = 200, description = "Whole file, with its stored type", body = [u8], content_type = "application/octet-stream"),
+        (status = 206, description = "Range of the file, as given by `Content-Range`", body = [u8], content_type = "application/octet-stream"),
+        (status = 404, description = "Attachment not found", body = String),
+        (status = 416, description = "Range starting past the end, the size being given by `Content-Range`", body = String)
+    )
+)]
+pub async fn get_attachment(
+    attachment_id: i32,
+    store: Store,
+    blobs: Arc<dyn BlobStore>,
+    range: Option<String>,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Downloading attachment...", &id);
+    let attachment = store
+        .get_attachment(AttachmentId(attachment_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    let size = attachment.size as u64;
+    let range = ByteRange::parse(range.as_deref(), size).map_err(|e| {
+        log::warn!("{} - {}", &id, e);
+        warp::reject::custom(Error::RangeNotSatisfiable(size))
+    })?;
+    let (offset, length) = range.map_or((0, size), |range| (range.start, range.length()));
+    let content = blobs
+        .get(&attachment.blob_key, offset, length)
+        .await
+        .map_err(|e| {
+            log::error!("{} - Error reading blob {}: {}", &id, attachment.blob_key, e);
+            warp::reject::custom(Error::StorageError(e.to_string()))
+        })?;
+
+    let mut response = Response::new(Body::wrap_stream(content));
+    let headers = response.headers_mut();
+    let content_type = HeaderValue::from_str(&attachment.content_type)
+        .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+    headers.insert(CONTENT_TYPE, content_type);
+    headers.insert(CONTENT_LENGTH, length.into());
+    headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(CONTENT_SECURITY_POLICY, HeaderValue::from_static("sandbox"));
+    if let Ok(disposition) = HeaderValue::from_str(&attachment.content_disposition()) {
+        headers.insert(CONTENT_DISPOSITION, disposition);
+    }
+    if let Some(range) = range {
+        let content_range = format!("bytes {}-{}/{}", range.start, range.end, size);
+        if let Ok(content_range) = HeaderValue::from_str(&content_range) {
+            headers.insert(CONTENT_RANGE, content_range);
+        }
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+    }
+    Ok(response)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("attachment_id" = i32, Path, description = "Attachment id")
+    ),
+    responses(
+        (status = 204, description = "Attachment deleted"),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Attachment not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn delete_attachment(
+    attachment_id: i32,
+    session: Session,
+    store: Store,
+    blobs: Arc<dyn BlobStore>,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Deleting attachment...", &id);
+    let attachment = store
+        .get_attachment(AttachmentId(attachment_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if !session.can_modify(attachment.account_id) {
+        log::warn!("{} - Account {} cannot delete attachment", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    if let Err(e) = store.delete_attachment(attachment.id).await {
+        log::error!("{} - Error deleting attachment: {}", &id, e);
+        return Err(warp::reject::custom(e));
+    }
+    // The attachment is gone either way, a leftover blob only takes space
+    if let Err(e) = blobs.delete(&attachment.blob_key).await {
+        log::error!("{} - Error deleting blob {}: {}", &id, attachment.blob_key, e);
+    }
+    Ok(warp::reply::with_status(warp::reply(), StatusCode::NO_CONTENT))
+}
+
+/// Refuses files attached to a locked question or its answers to all but moderators
+async fn check_unlocked(
+    store: &Store,
+    session: &Session,
+    question_id: QuestionId,
+    id: &str,
+) -> Result<(), Rejection> {
+    require_unlocked(store, session, question_id).await.map_err(|e| {
+        log::warn!("{} - Cannot attach files: {}", id, e);
+        warp::reject::custom(e)
+    })
+}
+
+/// Stores the `file` part of the form, refusing it unless the account may edit the
+/// question or answer
+async fn add_attachment(
+    target: AttachmentTarget,
+    owner: Option<AccountId>,
+    session: Session,
+    store: Store,
+    blobs: Arc<dyn BlobStore>,
+    form: FormData,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    if !session.can_modify(owner) {
+        log::warn!("{} - Account {} cannot attach files", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    let (file_name, content) = read_file(form).await.map_err(|e| {
+        log::warn!("{} - Invalid upload: {}", &id, e);
+        warp::reject::custom(e)
+    })?;
+    let attachment_draft = AttachmentDraft::new(file_name.as_deref(), &content).map_err(|errors| {
+        log::warn!("{} - Invalid attachment: {:?}", &id, errors);
+        warp::reject::custom(Error::InvalidFields(errors))
+    })?;
+    let blob_key = uuid::Uuid::new_v4().to_string();
+    if let Err(e) = blobs.put(&blob_key, Bytes::from(content)).await {
+        log::error!("{} - Error storing blob {}: {}", &id, blob_key, e);
+        return Err(warp::reject::custom(Error::StorageError(e.to_string())));
+    }
+    match store
+        .add_attachment(target, attachment_draft, &blob_key, session.account_id)
+        .await {
+        Ok(attachment) => Ok(warp::reply::with_status(
+            warp::reply::json(&attachment),
+            StatusCode::CREATED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error adding attachment: {}", &id, e);
+            if let Err(e) = blobs.delete(&blob_key).await {
+                log::error!("{} - Error deleting blob {}: {}", &id, blob_key, e);
+            }
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Name and content of the `file` part, the other parts being ignored
+async fn read_file(mut form: FormData) -> Result<(Option<String>, Vec<u8>), Error> {
+    let invalid = |e: warp::Error| Error::InvalidParameter(format!("multipart body: {}", e));
+    while let Some(part) = form.try_next().await.map_err(invalid)? {
+        if part.name() != "file" {
+            continue;
+        }
+        let file_name = part.filename().map(str::to_string);
+        let content = part
+            .stream()
+            .try_fold(Vec::new(), |mut content, chunk| async move {
+                content.extend_from_slice(chunk.chunk());
+                Ok(content)
+            })
+            .await
+            .map_err(invalid)?;
+        return Ok((file_name, content));
+    }
+    Err(Error::MissingParameters)
+}
+
+async fn get_attachments(
+    target: AttachmentTarget,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    match store.get_attachments(target).await {
+        Ok(attachments) => Ok(warp::reply::json(&attachments)),
+        Err(e) => {
+            log::error!("{} - Error getting attachments: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use argon2::Config;
+use chrono::{Duration, Utc};
+use rand::Rng;
+use warp::{Filter, Rejection, Reply};
+use warp::http::StatusCode;
+
+use errors::Error;
+use errors::responses::{InvalidFields, LengthRequired, PayloadTooLarge, TooManyRequests};
+
+use crate::domain::account::{AccountDraft, PasswordReset, PasswordResetRequest, Session};
+use crate::domain::email::Email;
+use crate::infrastructure::mailer::Mailer;
+use crate::infrastructure::store::Store;
+
+/// How long a session token stays valid after login
+const SESSION_DURATION_HOURS: i64 = 24;
+/// How long a password reset token sent by email stays valid
+const PASSWORD_RESET_DURATION_HOURS: i64 = 1;
+
+#[utoipa::path(
+    post,
+    path = "/registration",
+    tag = "accounts",
+    request_body = AccountDraft,
+    responses(
+        (status = 201, description = "Account created", body = Account),
+        (status = 409, description = "Account already exists", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    )
+)]
+pub async fn register(
+    store: Store,
+    account: AccountDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Registering account...", &id);
+    let account = account.validated().map_err(|errors| {
+        log::warn!("{} - Invalid account: {:?}", &id, errors);
+        warp::reject::custom(Error::InvalidFields(errors))
+    })?;
+    let account = AccountDraft {
+        email: account.email,
+        password: hash_password(account.password.as_bytes()),
+    };
+    match store.add_account(account).await {
+        Ok(account) => Ok(warp::reply::with_status(
+            warp::reply::json(&account),
+            StatusCode::CREATED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error registering account: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "accounts",
+    request_body = AccountDraft,
+    responses(
+        (status = 200, description = "Session whose token goes in the `Authorization` header", body = Session),
+        (status = 401, description = "Wrong e-mail/password combination", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 429, response = TooManyRequests)
+    )
+)]
+pub async fn login(
+    store: Store,
+    login: AccountDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Logging in...", &id);
+    let (account, hash) = store
+        .get_account_by_email(&login.email)
+        .await
+        .map_err(warp::reject::custom)?;
+    match verify_password(&hash, login.password.as_bytes()) {
+        Ok(true) => {}
+        Ok(false) => {
+            log::warn!("{} - Wrong password for account {}", &id, account.id);
+            return Err(warp::reject::custom(Error::WrongPassword));
+        }
+        Err(e) => {
+            log::error!("{} - Error verifying password: {}", &id, e);
+            return Err(warp::reject::custom(Error::ArgonLibraryError(e)));
+        }
+    }
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let expires_on = (Utc::now() + Duration::hours(SESSION_DURATION_HOURS)).naive_utc();
+    match store.add_session(&account, token, expires_on).await {
+        Ok(session) => Ok(warp::reply::json(&session)),
+        Err(e) => {
+            log::error!("{} - Error creating session: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Emails a reset token to the account. Always accepted, so that it cannot be used to
+/// find out which e-mails have an account.
+#[utoipa::path(
+    post,
+    path = "/password-reset",
+    tag = "accounts",
+    request_body = PasswordResetRequest,
+    responses(
+        (status = 202, description = "Reset token emailed if the account exists", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 429, response = TooManyRequests)
+    )
+)]
+pub async fn request_password_reset(
+    store: Store,
+    mailer: Mailer,
+    request: PasswordResetRequest,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Requesting password reset...", &id);
+    tokio::spawn(email_password_reset(store, mailer, request.email, id));
+    Ok(warp::reply::with_status("Password reset requested", StatusCode::ACCEPTED))
+}
+
+/// Sets a new password with a token received by email and logs out all sessions
+#[utoipa::path(
+    post,
+    path = "/password-reset/confirm",
+    tag = "accounts",
+    request_body = PasswordReset,
+    responses(
+        (status = 200, description = "Password changed and every session logged out", body = String),
+        (status = 400, description = "Invalid or expired reset token", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge)
+    )
+)]
+pub async fn reset_password(
+    store: Store,
+    reset: PasswordReset,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Resetting password...", &id);
+    let password = hash_password(reset.password.as_bytes());
+    match store.reset_password(&reset.token, password).await {
+        Ok(_) => Ok(warp::reply::with_status("Password reset", StatusCode::OK)),
+        Err(e) => {
+            log::warn!("{} - Error resetting password: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Filter resolving the session of the token sent in the `Authorization` header,
+/// either bare or with a `Bearer` prefix
+pub fn auth(store: Store) -> impl Filter<Extract = (Session,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("Authorization").and_then(move |token: Option<String>| {
+        let store = store.clone();
+        async move { get_session(&store, token).await }
+    })
+}
+
+/// Like [`auth`], but also accepting the token in a `token` query parameter, as browsers
+/// cannot set headers when opening a WebSocket
+pub fn ws_auth(store: Store) -> impl Filter<Extract = (Session,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("Authorization")
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(move |token: Option<String>, mut params: HashMap<String, String>| {
+            let store = store.clone();
+            async move { get_session(&store, token.or_else(|| params.remove("token"))).await }
+        })
+}
+
+async fn get_session(store: &Store, token: Option<String>) -> Result<Session, Rejection> {
+    let token = match token {
+        Some(token) => token,
+        None => return Err(warp::reject::custom(Error::Unauthorized)),
+    };
+    let token = token.strip_prefix("Bearer ").unwrap_or(&token);
+    store
+        .get_session(token)
+        .await
+        .map_err(warp::reject::custom)
+}
+
+/// Looks up the account and emails it a reset token out of the request, so that neither
+/// the reply nor its timing tell whether the e-mail has an account
+async fn email_password_reset(store: Store, mailer: Mailer, email: String, id: String) {
+    let account = match store.get_account_by_email(&email).await {
+        Ok((account, _)) => account,
+        Err(Error::WrongPassword) => {
+            log::info!("{} - No account for password reset", &id);
+            return;
+        }
+        Err(e) => {
+            log::error!("{} - Error getting account: {}", &id, e);
+            return;
+        }
+    };
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let expires_on = (Utc::now() + Duration::hours(PASSWORD_RESET_DURATION_HOURS)).naive_utc();
+    let result = async {
+        store.add_password_reset(account.id, &token, expires_on).await?;
+        let email = Email::password_reset(&token, expires_on, &mailer.app_url);
+        mailer.send(&account.email, email).await
+    };
+    if let Err(e) = result.await {
+        log::error!("{} - Error sending password reset: {}", &id, e);
+    }
+}
+
+pub fn hash_password(password: &[u8]) -> String {
+    let salt = rand::thread_rng().gen::<[u8; 32]>();
+    let config = Config::default();
+    argon2::hash_encoded(password, &salt, &config).unwrap()
+}
+
+fn verify_password(hash: &str, password: &[u8]) -> Result<bool, argon2::Error> {
+    argon2::verify_encoded(hash, password)
+}
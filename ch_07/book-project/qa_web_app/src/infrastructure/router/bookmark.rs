@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use warp::{Rejection, Reply};
+use warp::http::StatusCode;
+
+use errors::Error;
+use errors::responses::{BadRequest, Unauthorized};
+
+use crate::domain::account::Session;
+use crate::domain::bookmark::BookmarkDraft;
+use crate::domain::question::QuestionId;
+use crate::infrastructure::pagination::{extract_pagination, Pagination};
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/bookmark",
+    tag = "bookmarks",
+    params(
+        ("question_id" = i32, Path, description = "Question id"),
+        ("collection" = Option<String>, Query, description = "Collection to file the bookmark under")
+    ),
+    responses(
+        (status = 201, description = "Question bookmarked", body = Bookmark),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Question not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn add_bookmark(
+    question_id: i32,
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Bookmarking question...", &id);
+    let bookmark_draft = BookmarkDraft::new(params.get("collection"))
+        .map_err(|e| warp::reject::custom(Error::InvalidParameter(e)))?;
+    match store
+        .add_bookmark(QuestionId(question_id), session.account_id, bookmark_draft)
+        .await {
+        Ok(bookmark) => Ok(warp::reply::with_status(
+            warp::reply::json(&bookmark),
+            StatusCode::CREATED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error bookmarking question: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/questions/{question_id}/bookmark",
+    tag = "bookmarks",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 204, description = "Bookmark deleted"),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Bookmark not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn delete_bookmark(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Removing bookmark...", &id);
+    match store
+        .delete_bookmark(QuestionId(question_id), session.account_id)
+        .await {
+        Ok(_) => Ok(warp::reply::with_status(
+            warp::reply(),
+            StatusCode::NO_CONTENT,
+        )),
+        Err(e) => {
+            log::error!("{} - Error removing bookmark: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/bookmarks",
+    tag = "bookmarks",
+    params(
+        ("collection" = Option<String>, Query, description = "Only the bookmarks of this collection"),
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Bookmarks, most recent first", body = [Bookmark]),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_bookmarks(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying bookmarks...", &id);
+    let bookmark_draft = BookmarkDraft::new(params.get("collection"))
+        .map_err(|e| warp::reject::custom(Error::InvalidParameter(e)))?;
+    let mut pagination = Pagination::default();
+    if params.contains_key("offset") || params.contains_key("limit") {
+        log::debug!("{} - Pagination used", &id);
+        pagination = extract_pagination(params)?;
+    }
+    match store
+        .get_bookmarks(
+            session.account_id,
+            bookmark_draft.collection,
+            pagination.limit,
+            pagination.offset,
+        )
+        .await {
+        Ok(bookmarks) => Ok(warp::reply::json(&bookmarks)),
+        Err(e) => {
+            log::error!("{} - Error getting bookmarks: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use warp::{Rejection, Reply};
+
+use errors::Error;
+use errors::responses::{Forbidden, Unauthorized};
+
+use crate::domain::account::{Role, Session};
+use crate::infrastructure::store::Store;
+
+/// Hit and miss counters of the read cache, for admins tuning its size
+#[utoipa::path(
+    get,
+    path = "/cache/stats",
+    tag = "cache",
+    responses(
+        (status = 200, description = "Counters since startup", body = CacheStats),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_cache_stats(
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying cache stats...", &id);
+    if session.role != Role::Admin {
+        log::warn!("{} - Account {} cannot see cache stats", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    Ok(warp::reply::json(&store.cache.stats()))
+}
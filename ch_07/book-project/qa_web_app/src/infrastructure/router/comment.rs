@@ -0,0 +1,314 @@
+use warp::{Rejection, Reply};
+use warp::http::StatusCode;
+
+use errors::Error;
+use errors::responses::{
+    Forbidden, InvalidFields, LengthRequired, PayloadTooLarge, TooManyRequests, Unauthorized,
+};
+
+use crate::domain::account::Session;
+use crate::domain::answer::AnswerId;
+use crate::domain::comment::{Comment, CommentDraft, CommentId, CommentTarget};
+use crate::domain::question::QuestionId;
+use crate::infrastructure::moderation::Moderation;
+use crate::infrastructure::router::question::require_unlocked;
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/comments",
+    tag = "comments",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    request_body = CommentDraft,
+    responses(
+        (status = 201, description = "Comment added, with its bad words censored", body = Comment),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Question not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
+pub async fn add_question_comment(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    moderation: Moderation,
+    comment_draft: CommentDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Adding question comment...", &id);
+    let target = CommentTarget::Question(QuestionId(question_id));
+    add_comment(target, session, store, moderation, comment_draft, id).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/answers/{answer_id}/comments",
+    tag = "comments",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    request_body = CommentDraft,
+    responses(
+        (status = 201, description = "Comment added, with its bad words censored", body = Comment),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Answer not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
+pub async fn add_answer_comment(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    moderation: Moderation,
+    comment_draft: CommentDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Adding answer comment...", &id);
+    let target = CommentTarget::Answer(AnswerId(answer_id));
+    add_comment(target, session, store, moderation, comment_draft, id).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/questions/{question_id}/comments",
+    tag = "comments",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Comments, oldest first", body = [Comment]),
+        (status = 404, description = "Question not found", body = String)
+    )
+)]
+pub async fn get_question_comments(
+    question_id: i32,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying question comments...", &id);
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    get_comments(CommentTarget::Question(question.id), store, id).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/answers/{answer_id}/comments",
+    tag = "comments",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    responses(
+        (status = 200, description = "Comments, oldest first", body = [Comment]),
+        (status = 404, description = "Answer not found", body = String)
+    )
+)]
+pub async fn get_answer_comments(
+    answer_id: i32,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying answer comments...", &id);
+    let answer = store
+        .get_answer(AnswerId(answer_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    get_comments(CommentTarget::Answer(answer.id), store, id).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/comments/{comment_id}",
+    tag = "comments",
+    params(
+        ("comment_id" = i32, Path, description = "Comment id")
+    ),
+    request_body = CommentDraft,
+    responses(
+        (status = 202, description = "Comment updated", body = Comment),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Comment not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
+pub async fn update_comment(
+    comment_id: i32,
+    session: Session,
+    store: Store,
+    moderation: Moderation,
+    comment_draft: CommentDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Updating comment...", &id);
+    let comment = store
+        .get_comment(CommentId(comment_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if !session.can_modify(comment.account_id) {
+        log::warn!("{} - Account {} cannot edit comment", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    check_unlocked(&store, &session, &comment_target(&comment)?, &id).await?;
+    let comment_draft = moderate(comment_draft, &moderation, &id).await?;
+    match store.update_comment(comment.id, comment_draft).await {
+        Ok(comment) => Ok(warp::reply::with_status(
+            warp::reply::json(&comment),
+            StatusCode::ACCEPTED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error updating comment: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/comments/{comment_id}",
+    tag = "comments",
+    params(
+        ("comment_id" = i32, Path, description = "Comment id")
+    ),
+    responses(
+        (status = 204, description = "Comment deleted"),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Comment not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn delete_comment(
+    comment_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Deleting comment...", &id);
+    let comment = store
+        .get_comment(CommentId(comment_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if !session.can_modify(comment.account_id) {
+        log::warn!("{} - Account {} cannot delete comment", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    check_unlocked(&store, &session, &comment_target(&comment)?, &id).await?;
+    match store.delete_comment(comment.id, session.account_id).await {
+        Ok(_) => Ok(warp::reply::with_status(
+            warp::reply(),
+            StatusCode::NO_CONTENT,
+        )),
+        Err(e) => {
+            log::error!("{} - Error deleting comment: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+async fn add_comment(
+    target: CommentTarget,
+    session: Session,
+    store: Store,
+    moderation: Moderation,
+    comment_draft: CommentDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    check_unlocked(&store, &session, &target, &id).await?;
+    let comment_draft = moderate(comment_draft, &moderation, &id).await?;
+    match store
+        .add_comment(target, comment_draft, session.account_id)
+        .await {
+        Ok(comment) => Ok(warp::reply::with_status(
+            warp::reply::json(&comment),
+            StatusCode::CREATED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error adding comment: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+async fn get_comments(
+    target: CommentTarget,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    match store.get_comments(target).await {
+        Ok(comments) => Ok(warp::reply::json(&comments)),
+        Err(e) => {
+            log::error!("{} - Error getting comments: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Question or answer the comment is on
+fn comment_target(comment: &Comment) -> Result<CommentTarget, Rejection> {
+    match (comment.question_id.clone(), comment.answer_id.clone()) {
+        (Some(question_id), _) => Ok(CommentTarget::Question(question_id)),
+        (None, Some(answer_id)) => Ok(CommentTarget::Answer(answer_id)),
+        (None, None) => Err(warp::reject::custom(Error::CommentNotFound)),
+    }
+}
+
+/// Refuses comment changes on a locked question or its answers to all but moderators
+async fn check_unlocked(
+    store: &Store,
+    session: &Session,
+    target: &CommentTarget,
+    id: &str,
+) -> Result<(), Rejection> {
+    let question_id = match target {
+        CommentTarget::Question(question_id) => question_id.clone(),
+        CommentTarget::Answer(answer_id) => store
+            .get_answer(answer_id.clone())
+            .await
+            .map_err(warp::reject::custom)?
+            .question_id,
+    };
+    require_unlocked(store, session, question_id).await.map_err(|e| {
+        log::warn!("{} - Cannot change comments: {}", id, e);
+        warp::reject::custom(e)
+    })
+}
+
+/// Validates the comment length and censors its text
+async fn moderate(
+    comment_draft: CommentDraft,
+    moderation: &Moderation,
+    id: &str,
+) -> Result<CommentDraft, Rejection> {
+    let comment_draft = comment_draft.validated().map_err(|errors| {
+        log::warn!("{} - Invalid comment: {:?}", id, errors);
+        warp::reject::custom(Error::InvalidFields(errors))
+    })?;
+    match moderation.censor(comment_draft.content).await {
+        Ok(content) => Ok(CommentDraft { content }),
+        Err(e) => {
+            log::error!("{} - Error moderating comment: {}", id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use utoipa::openapi::OpenApi;
+use utoipa_swagger_ui::Config;
+use warp::http::{header, StatusCode, Uri};
+use warp::path::{FullPath, Tail};
+use warp::{Rejection, Reply};
+
+/// OpenAPI document of every route
+pub async fn get_openapi(api_doc: Arc<OpenApi>) -> Result<impl Reply, Rejection> {
+    Ok(warp::reply::json(api_doc.as_ref()))
+}
+
+/// Documentation UI bundled in the binary, reading the document of `get_openapi`
+pub async fn get_docs(
+    full_path: FullPath,
+    tail: Tail,
+    config: Arc<Config<'static>>,
+) -> Result<Box<dyn Reply>, Rejection> {
+    // Relative links of the UI only resolve under the trailing slash
+    if full_path.as_str() == "/docs" {
+        return Ok(Box::new(warp::redirect::found(Uri::from_static("/docs/"))));
+    }
+    match utoipa_swagger_ui::serve(tail.as_str(), config) {
+        Ok(Some(file)) => Ok(Box::new(warp::reply::with_header(
+            file.bytes.into_owned(),
+            header::CONTENT_TYPE,
+            file.content_type,
+        ))),
+        Ok(None) => Err(warp::reject::not_found()),
+        Err(error) => {
+            log::error!("Cannot serve the documentation UI: {}", error);
+            Ok(Box::new(warp::reply::with_status(
+                "Cannot serve the documentation".to_string(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )))
+        }
+    }
+}
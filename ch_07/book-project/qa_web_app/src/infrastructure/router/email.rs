@@ -0,0 +1,63 @@
+use warp::{Rejection, Reply};
+
+use errors::responses::{LengthRequired, PayloadTooLarge, Unauthorized};
+
+use crate::domain::account::Session;
+use crate::domain::email::EmailPreferences;
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    get,
+    path = "/me/email-preferences",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "Emails the account receives", body = EmailPreferences),
+        (status = 401, response = Unauthorized)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_email_preferences(
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying email preferences...", &id);
+    match store.get_email_preferences(session.account_id).await {
+        Ok(preferences) => Ok(warp::reply::json(&preferences)),
+        Err(e) => {
+            log::error!("{} - Error getting email preferences: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/me/email-preferences",
+    tag = "notifications",
+    request_body = EmailPreferences,
+    responses(
+        (status = 200, description = "Preferences saved", body = EmailPreferences),
+        (status = 401, response = Unauthorized),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge)
+    ),
+    security(("token" = []))
+)]
+pub async fn update_email_preferences(
+    session: Session,
+    store: Store,
+    preferences: EmailPreferences,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Updating email preferences...", &id);
+    match store
+        .update_email_preferences(session.account_id, preferences)
+        .await {
+        Ok(preferences) => Ok(warp::reply::json(&preferences)),
+        Err(e) => {
+            log::error!("{} - Error updating email preferences: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
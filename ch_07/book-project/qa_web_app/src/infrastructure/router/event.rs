@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::StreamExt;
+use warp::{Rejection, Reply};
+use warp::sse::Event as SseEvent;
+
+use errors::Error;
+use errors::responses::BadRequest;
+
+use crate::domain::event::{Event, EventFilter, EventId};
+use crate::infrastructure::store::Store;
+
+/// Streams question and answer changes as Server-Sent Events, optionally restricted to
+/// a `tag` or `question_id`. Events missed since `Last-Event-ID` are replayed first.
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(
+        ("tag" = Option<String>, Query, description = "Only the events on questions with this tag"),
+        ("question_id" = Option<i32>, Query, description = "Only the events on this question"),
+        ("Last-Event-ID" = Option<u64>, Header, description = "Last event received, to replay the ones missed since")
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events named after their kind, with the event as JSON data", body = Event, content_type = "text/event-stream"),
+        (status = 400, response = BadRequest)
+    )
+)]
+pub async fn get_events(
+    params: HashMap<String, String>,
+    last_event_id: Option<u64>,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Streaming events...", &id);
+    let filter = EventFilter::from_params(&params)
+        .map_err(|e| warp::reject::custom(Error::InvalidParameter(e)))?;
+    let (missed, receiver) = store.events.subscribe(last_event_id.map(EventId));
+    let live = BroadcastStream::new(receiver).filter_map(move |event| match event {
+        Ok(event) => Some(event),
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            log::warn!("{} - Event listener lagging, skipped {} events", &id, skipped);
+            None
+        }
+    });
+    let stream = tokio_stream::iter(missed)
+        .chain(live)
+        .filter(move |event| filter.matches(event))
+        .filter_map(|event| sse_event(&event).map(Ok::<_, Infallible>));
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
+}
+
+fn sse_event(event: &Event) -> Option<SseEvent> {
+    match SseEvent::default()
+        .id(event.id.0.to_string())
+        .event(event.kind.as_str())
+        .json_data(event) {
+        Ok(sse_event) => Some(sse_event),
+        Err(e) => {
+            log::error!("Error serializing event {}: {}", event.id.0, e);
+            None
+        }
+    }
+}
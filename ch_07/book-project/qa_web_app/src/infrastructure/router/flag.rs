@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use warp::{Rejection, Reply};
+use warp::http::StatusCode;
+
+use errors::Error;
+use errors::responses::{
+    BadRequest, Forbidden, LengthRequired, PayloadTooLarge, TooManyRequests, Unauthorized,
+    ValidationError,
+};
+
+use crate::domain::account::Session;
+use crate::domain::answer::validate_answer_content;
+use crate::domain::comment::CommentDraft;
+use crate::domain::flag::{FlagDraft, FlagTarget, ModerationAction, ModerationDecision};
+use crate::domain::question::validate_question_content;
+use crate::infrastructure::pagination::{extract_pagination, Pagination};
+use crate::infrastructure::store::Store;
+
+/// Reports a question, answer or comment to the moderators
+#[utoipa::path(
+    post,
+    path = "/flags",
+    tag = "moderation",
+    request_body = FlagDraft,
+    responses(
+        (status = 201, description = "Content flagged", body = Flag),
+        (status = 401, response = Unauthorized),
+        (status = 403, description = "Flag on own content", body = String),
+        (status = 404, description = "Question, answer or comment not found", body = String),
+        (status = 409, description = "Content already flagged by the account", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = ValidationError),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
+pub async fn add_flag(
+    session: Session,
+    store: Store,
+    hide_threshold: i64,
+    flag_draft: FlagDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Adding flag...", &id);
+    let target = match flag_draft.target() {
+        Ok(target) => target,
+        Err(e) => {
+            log::warn!("{} - Invalid flag: {}", &id, e);
+            return Err(warp::reject::custom(Error::ValidationError(e)));
+        }
+    };
+    let (author_id, _) = store
+        .get_flagged_content(&target)
+        .await
+        .map_err(warp::reject::custom)?;
+    if author_id == Some(session.account_id) {
+        log::warn!("{} - Account {} cannot flag own content", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    match store
+        .add_flag(target, flag_draft, session.account_id, hide_threshold)
+        .await {
+        Ok(flag) => Ok(warp::reply::with_status(
+            warp::reply::json(&flag),
+            StatusCode::CREATED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error adding flag: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/moderation/queue",
+    tag = "moderation",
+    params(
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Flagged content, most severe first", body = [QueueItem]),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_moderation_queue(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying moderation queue...", &id);
+    require_moderator(&session, &id)?;
+    let mut pagination = Pagination::default();
+    if params.contains_key("offset") || params.contains_key("limit") {
+        log::debug!("{} - Pagination used", &id);
+        pagination = extract_pagination(params)?;
+    }
+    match store
+        .get_moderation_queue(pagination.limit, pagination.offset)
+        .await {
+        Ok(queue) => Ok(warp::reply::json(&queue)),
+        Err(e) => {
+            log::error!("{} - Error getting moderation queue: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Carries out the moderator's decision on flagged content and closes its open flags
+#[utoipa::path(
+    post,
+    path = "/moderation/{kind}/{target_id}",
+    tag = "moderation",
+    params(
+        ("kind" = String, Path, description = "`question`, `answer` or `comment`"),
+        ("target_id" = i32, Path, description = "Id of the flagged content")
+    ),
+    request_body = ModerationDecision,
+    responses(
+        (status = 200, description = "Decision carried out and logged", body = ModerationLogEntry),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Content not found or without open flags", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = ValidationError)
+    ),
+    security(("token" = []))
+)]
+pub async fn moderate(
+    kind: String,
+    target_id: i32,
+    session: Session,
+    store: Store,
+    decision: ModerationDecision,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Moderating flagged content...", &id);
+    require_moderator(&session, &id)?;
+    let target = FlagTarget::from_path(&kind, target_id).ok_or_else(|| {
+        warp::reject::custom(Error::InvalidParameter(format!("Cannot moderate {}", kind)))
+    })?;
+    if let Err(e) = decision.validate() {
+        log::warn!("{} - Invalid moderation decision: {}", &id, e);
+        return Err(warp::reject::custom(Error::ValidationError(e)));
+    }
+    let (author_id, question_id) = store
+        .get_flagged_content(&target)
+        .await
+        .map_err(warp::reject::custom)?;
+    let mut decision = decision;
+    if decision.action == ModerationAction::Edit {
+        let content = decision.content.take().unwrap_or_default();
+        match edited_content(&target, content) {
+            Ok(content) => decision.content = Some(content),
+            Err(e) => {
+                log::warn!("{} - Invalid content for {} {}: {}", &id, kind, target_id, e);
+                return Err(warp::reject::custom(e));
+            }
+        }
+    }
+    match store
+        .moderate(&target, decision, session.account_id, author_id, &question_id)
+        .await {
+        Ok(entry) => Ok(warp::reply::json(&entry)),
+        Err(e) => {
+            log::error!("{} - Error moderating {} {}: {}", &id, kind, target_id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Audit of the moderators' decisions, most recent first
+#[utoipa::path(
+    get,
+    path = "/moderation/log",
+    tag = "moderation",
+    params(
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Decisions, most recent first", body = [ModerationLogEntry]),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_moderation_log(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying moderation log...", &id);
+    require_moderator(&session, &id)?;
+    let mut pagination = Pagination::default();
+    if params.contains_key("offset") || params.contains_key("limit") {
+        log::debug!("{} - Pagination used", &id);
+        pagination = extract_pagination(params)?;
+    }
+    match store
+        .get_moderation_log(pagination.limit, pagination.offset)
+        .await {
+        Ok(log) => Ok(warp::reply::json(&log)),
+        Err(e) => {
+            log::error!("{} - Error getting moderation log: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Validates the moderator's new content for the flagged question, answer or comment
+fn edited_content(target: &FlagTarget, content: String) -> Result<String, Error> {
+    let content = match target {
+        FlagTarget::Question(_) => validate_question_content(&content),
+        FlagTarget::Answer(_) => validate_answer_content(&content),
+        FlagTarget::Comment(_) => {
+            CommentDraft { content }.validated().map(|comment| comment.content)
+        }
+    };
+    content.map_err(Error::InvalidFields)
+}
+
+fn require_moderator(session: &Session, id: &str) -> Result<(), Rejection> {
+    if !session.is_moderator() {
+        log::warn!("{} - Account {} cannot moderate", id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    Ok(())
+}
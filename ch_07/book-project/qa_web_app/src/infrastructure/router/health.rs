@@ -0,0 +1,12 @@
+use warp::{Rejection, Reply};
+
+/// Liveness probe for load balancers
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "Server up", body = String, example = json!("Alive")))
+)]
+pub async fn health() -> Result<impl Reply, Rejection> {
+    Ok("Alive".to_string())
+}
@@ -0,0 +1,123 @@
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+use warp::{Rejection, Reply};
+use warp::ws::{Message, WebSocket, Ws};
+
+use errors::responses::Unauthorized;
+
+use crate::domain::account::Session;
+use crate::domain::live::{ClientMessage, LiveMessage};
+use crate::domain::question::QuestionId;
+use crate::infrastructure::live::LiveHub;
+use crate::infrastructure::store::Store;
+
+/// Upgrades to a WebSocket pushing the changes to a question and its answers, and
+/// relaying which visitors are typing an answer
+#[utoipa::path(
+    get,
+    path = "/questions/{question_id}/live",
+    tag = "events",
+    params(
+        ("question_id" = i32, Path, description = "Question id"),
+        ("token" = Option<String>, Query, description = "Session token, for browsers that cannot send `Authorization`")
+    ),
+    responses(
+        (status = 101, description = "WebSocket pushing the changes to the question and who is typing an answer"),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Question not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn live_question(
+    question_id: i32,
+    ws: Ws,
+    session: Session,
+    store: Store,
+    hub: LiveHub,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Opening live question...", &id);
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    Ok(ws.on_upgrade(move |socket| watch_question(socket, question.id, session, hub, id)))
+}
+
+async fn watch_question(
+    socket: WebSocket,
+    question_id: QuestionId,
+    session: Session,
+    hub: LiveHub,
+    id: String,
+) {
+    let (mut sender, mut client) = socket.split();
+    let mut room = hub.join(&question_id);
+    let mut heartbeat = tokio::time::interval(hub.heartbeat);
+    let mut last_seen = Instant::now();
+    loop {
+        tokio::select! {
+            message = client.next() => match message {
+                Some(Ok(message)) => {
+                    // Pongs answering the heartbeat count as activity too
+                    last_seen = Instant::now();
+                    if message.is_close() {
+                        break;
+                    }
+                    if let Ok(text) = message.to_str() {
+                        match serde_json::from_str::<ClientMessage>(text) {
+                            Ok(ClientMessage::Typing { typing }) => hub.send(
+                                &question_id,
+                                LiveMessage::Typing { account_id: session.account_id, typing },
+                            ),
+                            Err(e) => log::warn!("{} - Invalid live message: {}", &id, e),
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    log::warn!("{} - Live connection error: {}", &id, e);
+                    break;
+                }
+                None => break,
+            },
+            message = room.recv() => match message {
+                Ok(message) => {
+                    let text = match serde_json::to_string(&message) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            log::error!("{} - Error serializing live message: {}", &id, e);
+                            continue;
+                        }
+                    };
+                    if sender.send(Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("{} - Live connection lagging, skipped {} messages", &id, skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > hub.timeout {
+                    log::info!("{} - Live connection timed out", &id);
+                    break;
+                }
+                if sender.send(Message::ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    // Whatever the client was typing will not be posted from this connection
+    hub.send(
+        &question_id,
+        LiveMessage::Typing {
+            account_id: session.account_id,
+            typing: false,
+        },
+    );
+    hub.leave(&question_id, room);
+    log::info!("{} - Closed live question", &id);
+}
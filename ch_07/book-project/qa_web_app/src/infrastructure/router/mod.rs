@@ -1,2 +1,20 @@
 pub mod answer;
+pub mod attachment;
+pub mod authentication;
+pub mod bookmark;
+pub mod cache;
+pub mod comment;
+pub mod docs;
+pub mod email;
+pub mod event;
+pub mod flag;
+pub mod health;
+pub mod live;
+pub mod notification;
+pub mod preview;
 pub mod question;
+pub mod reputation;
+pub mod revision;
+pub mod subscription;
+pub mod vote;
+pub mod webhook;
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use warp::{Rejection, Reply};
+
+use errors::Error;
+use errors::responses::{BadRequest, Unauthorized};
+
+use crate::domain::account::Session;
+use crate::domain::notification::NotificationId;
+use crate::infrastructure::pagination::{extract_pagination, Pagination};
+use crate::infrastructure::store::Store;
+
+/// Lists the session's notifications with the unread count; `unread=true` leaves out
+/// those already read
+#[utoipa::path(
+    get,
+    path = "/me/notifications",
+    tag = "notifications",
+    params(
+        ("unread" = Option<bool>, Query, description = "Only the unread notifications"),
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Notifications of the page with the unread count", body = Inbox),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_notifications(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying notifications...", &id);
+    let unread_only = match params.get("unread") {
+        Some(unread) => unread
+            .parse::<bool>()
+            .map_err(|e| warp::reject::custom(Error::InvalidParameter(e.to_string())))?,
+        None => false,
+    };
+    let mut pagination = Pagination::default();
+    if params.contains_key("offset") || params.contains_key("limit") {
+        log::debug!("{} - Pagination used", &id);
+        pagination = extract_pagination(params)?;
+    }
+    match store
+        .get_notifications(session.account_id, unread_only, pagination.limit, pagination.offset)
+        .await {
+        Ok(inbox) => Ok(warp::reply::json(&inbox)),
+        Err(e) => {
+            log::error!("{} - Error getting notifications: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/me/notifications/{notification_id}/read",
+    tag = "notifications",
+    params(
+        ("notification_id" = i32, Path, description = "Notification id")
+    ),
+    responses(
+        (status = 200, description = "Notification read", body = Notification),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Notification not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn mark_notification_read(
+    notification_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Marking notification as read...", &id);
+    match store
+        .mark_notification_read(session.account_id, NotificationId(notification_id))
+        .await {
+        Ok(notification) => Ok(warp::reply::json(&notification)),
+        Err(e) => {
+            log::error!("{} - Error marking notification as read: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/me/notifications/read",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "First page of the notifications, all read", body = Inbox),
+        (status = 401, response = Unauthorized)
+    ),
+    security(("token" = []))
+)]
+pub async fn mark_all_notifications_read(
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Marking all notifications as read...", &id);
+    match store.mark_all_notifications_read(session.account_id).await {
+        Ok(_) => get_notifications(HashMap::new(), session, store, id).await,
+        Err(e) => {
+            log::error!("{} - Error marking notifications as read: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use warp::{Rejection, Reply};
+
+use errors::responses::{LengthRequired, PayloadTooLarge, TooManyRequests, Unauthorized};
+
+use crate::domain::account::Session;
+use crate::domain::preview::{Preview, PreviewDraft};
+use crate::infrastructure::markdown;
+
+/// Renders content the same way as saved questions and answers, for editors to preview
+#[utoipa::path(
+    post,
+    path = "/preview",
+    tag = "questions",
+    request_body = PreviewDraft,
+    responses(
+        (status = 200, description = "Content rendered to sanitized HTML", body = Preview),
+        (status = 401, response = Unauthorized),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
+pub async fn preview(
+    session: Session,
+    draft: PreviewDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Rendering preview for account {}...", &id, session.account_id);
+    Ok(warp::reply::json(&Preview {
+        content_html: markdown::render_blocking(draft.content).await,
+    }))
+}
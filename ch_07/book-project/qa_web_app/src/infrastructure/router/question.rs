@@ -4,51 +4,257 @@ use warp::{Rejection, Reply};
 use warp::http::StatusCode;
 
 use errors::{Error, InvalidId};
+use errors::responses::{
+    BadRequest, Forbidden, InvalidFields, LengthRequired, PayloadTooLarge, TooManyRequests,
+    Unauthorized, ValidationError,
+};
 
-use crate::domain::question::{Question, QuestionDraft, QuestionId};
+use crate::domain::account::Session;
+use crate::domain::answer::AnswerId;
+use crate::domain::question::{
+    CloseDraft, NewQuestion, Question, QuestionDraft, QuestionFilter, QuestionId, QuestionSort,
+    QuestionStatus, QuestionThread,
+};
+use crate::domain::reputation::{Privilege, PrivilegeThresholds};
+use crate::domain::similarity::rank_similar;
+use crate::infrastructure::http_cache::HttpCache;
 use crate::infrastructure::pagination::{extract_pagination, Pagination};
+use crate::infrastructure::router::reputation::authorize;
 use crate::infrastructure::store::Store;
 
+/// Similar questions suggested when none are asked for explicitly
+const SIMILAR_DEFAULT_LIMIT: usize = 5;
+const SIMILAR_MAX_LIMIT: usize = 20;
+/// Trigram candidates fetched per suggestion, before scoring them on their words too
+const SIMILAR_CANDIDATES_FACTOR: usize = 4;
+
+#[utoipa::path(
+    get,
+    path = "/questions",
+    tag = "questions",
+    params(
+        ("filter" = Option<String>, Query, description = "`all`, `unanswered` or `unsolved`"),
+        ("sort" = Option<String>, Query, description = "`oldest`, `newest` or `score`"),
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Questions of the page", body = [Question]),
+        (status = 304, description = "Unchanged since the `ETag` or `Last-Modified` sent back"),
+        (status = 400, response = BadRequest)
+    )
+)]
 pub async fn get_questions(
     params: HashMap<String, String>,
     store: Store,
+    cache: HttpCache,
     id: String,
 ) -> Result<impl Reply, Rejection> {
     log::info!("{} - Querying questions...", &id);
+    let filter = match params.get("filter") {
+        Some(filter) => filter
+            .parse::<QuestionFilter>()
+            .map_err(|e| warp::reject::custom(Error::InvalidParameter(e)))?,
+        None => QuestionFilter::All,
+    };
+    let sort = match params.get("sort") {
+        Some(sort) => sort
+            .parse::<QuestionSort>()
+            .map_err(|e| warp::reject::custom(Error::InvalidParameter(e)))?,
+        None => QuestionSort::Oldest,
+    };
     let mut pagination = Pagination::default();
-    if !params.is_empty() {
+    if params.contains_key("offset") || params.contains_key("limit") {
         log::debug!("{} - Pagination used", &id);
         pagination = extract_pagination(params)?;
     }
-    let response = match store.get_questions(pagination.limit, pagination.offset).await {
+    let last_modified = store
+        .get_questions_last_modified()
+        .await
+        .map_err(warp::reject::custom)?;
+    let response = match store
+        .get_questions(pagination.limit, pagination.offset, filter, sort)
+        .await {
         Ok(questions) => questions,
         Err(e) => {
             log::error!("{} - Error getting questions: {}", &id, e);
             return Err(warp::reject::custom(e));
         }
     };
-    Ok(warp::reply::json(&response))
+    Ok(cache.reply(&response, last_modified))
+}
+
+/// Returns the question with its answers and the comments on both
+#[utoipa::path(
+    get,
+    path = "/questions/{question_id}",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Question with its answers and comments", body = QuestionThread),
+        (status = 304, description = "Unchanged since the `ETag` or `Last-Modified` sent back"),
+        (status = 404, description = "Question not found", body = String)
+    )
+)]
+pub async fn get_question(
+    question_id: i32,
+    store: Store,
+    cache: HttpCache,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying question...", &id);
+    let last_modified = store
+        .get_question_last_modified(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    let answers = store
+        .get_question_thread_answers(question.id.clone())
+        .await
+        .map_err(warp::reject::custom)?;
+    let comments = store
+        .get_question_thread_comments(question.id.clone())
+        .await
+        .map_err(warp::reject::custom)?;
+    Ok(cache.reply(&QuestionThread::new(question, answers, comments), last_modified))
+}
+
+/// Questions resembling the `title` being typed, for suggestions while asking
+#[utoipa::path(
+    get,
+    path = "/questions/similar",
+    tag = "questions",
+    params(
+        ("title" = String, Query, description = "Title being typed"),
+        ("limit" = Option<u32>, Query, description = "Most questions returned, 20 at most")
+    ),
+    responses(
+        (status = 200, description = "Most similar questions first", body = [SimilarQuestion]),
+        (status = 400, response = BadRequest)
+    )
+)]
+pub async fn get_similar_questions(
+    params: HashMap<String, String>,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying similar questions...", &id);
+    let title = match params.get("title") {
+        Some(title) if !title.trim().is_empty() => title.trim(),
+        _ => return Err(warp::reject::custom(Error::MissingParameters)),
+    };
+    let limit = match params.get("limit") {
+        Some(limit) => limit
+            .parse::<usize>()
+            .map_err(|e| warp::reject::custom(Error::InvalidParameter(e.to_string())))?
+            .min(SIMILAR_MAX_LIMIT),
+        None => SIMILAR_DEFAULT_LIMIT,
+    };
+    match store
+        .get_similar_questions(title, (limit * SIMILAR_CANDIDATES_FACTOR) as i32)
+        .await {
+        Ok(candidates) => Ok(warp::reply::json(&rank_similar(title, None, candidates, limit))),
+        Err(e) => {
+            log::error!("{} - Error getting similar questions: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
 }
 
+/// Adds the question and reports the existing ones it may duplicate. With
+/// `strict_duplicates`, a question with the same title and content as an existing one is
+/// refused.
+#[utoipa::path(
+    post,
+    path = "/questions",
+    tag = "questions",
+    request_body = QuestionDraft,
+    responses(
+        (status = 201, description = "Question added, with the existing ones it may duplicate", body = NewQuestion),
+        (status = 401, response = Unauthorized),
+        (status = 409, description = "Same title and content as an existing question", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields),
+        (status = 429, response = TooManyRequests)
+    ),
+    security(("token" = []))
+)]
 pub async fn add_question(
+    session: Session,
     store: Store,
+    strict_duplicates: bool,
     question_draft: QuestionDraft,
     id: String,
 ) -> Result<impl Reply, Rejection> {
     log::info!("{} - Adding question...", &id);
-    store.add_question(question_draft).await.map_err(|e| {
-        log::error!("{} - Error adding question: {}", &id, e);
-        warp::reject::custom(e)
+    let question_draft = QuestionDraft::new(
+        &question_draft.title,
+        &question_draft.content,
+        question_draft.tags,
+    )
+    .map_err(|errors| {
+        log::warn!("{} - Invalid question: {:?}", &id, errors);
+        warp::reject::custom(Error::InvalidFields(errors))
     })?;
+    let candidates = store
+        .get_similar_questions(
+            &question_draft.title,
+            (SIMILAR_DEFAULT_LIMIT * SIMILAR_CANDIDATES_FACTOR) as i32,
+        )
+        .await
+        .map_err(warp::reject::custom)?;
+    let similar = rank_similar(
+        &question_draft.title,
+        Some(&question_draft.content),
+        candidates,
+        SIMILAR_DEFAULT_LIMIT,
+    );
+    let question = store
+        .add_question(question_draft, session.account_id, strict_duplicates)
+        .await
+        .map_err(|e| {
+            match e {
+                Error::QuestionAlreadyExists => log::warn!("{} - Question already exists", &id),
+                _ => log::error!("{} - Error adding question: {}", &id, e),
+            }
+            warp::reject::custom(e)
+        })?;
     Ok(warp::reply::with_status(
-        "Question added",
+        warp::reply::json(&NewQuestion { question, similar }),
         StatusCode::CREATED,
     ))
 }
 
+#[utoipa::path(
+    put,
+    path = "/questions/{question_id}",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    request_body = Question,
+    responses(
+        (status = 202, description = "Question updated", body = String),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = InvalidFields)
+    ),
+    security(("token" = []))
+)]
 pub async fn update_question(
     question_id: i32,
+    session: Session,
     store: Store,
+    thresholds: PrivilegeThresholds,
     question: Question,
     id: String,
 ) -> Result<impl Reply, Rejection> {
@@ -57,8 +263,24 @@ pub async fn update_question(
         log::warn!("{} - Invalid question id", &id);
         return Err(warp::reject::custom(Error::InvalidId(InvalidId)));
     }
+    let question = question.validated().map_err(|errors| {
+        log::warn!("{} - Invalid question: {:?}", &id, errors);
+        warp::reject::custom(Error::InvalidFields(errors))
+    })?;
+    let current = store
+        .get_question(question.id.clone())
+        .await
+        .map_err(warp::reject::custom)?;
+    if let Err(e) = authorize(&store, &session, current.account_id, Privilege::EditOthers, thresholds).await {
+        log::warn!("{} - Account {} cannot edit question: {}", &id, session.account_id, e);
+        return Err(warp::reject::custom(e));
+    }
+    if !current.status.accepts_changes(session.is_moderator()) {
+        log::warn!("{} - Question {} is locked", &id, current.id);
+        return Err(warp::reject::custom(Error::QuestionLocked));
+    }
     match store
-        .update_question(question)
+        .update_question(question, session.account_id)
         .await {
         Ok(_) => Ok(warp::reply::with_status(
             "Question updated",
@@ -71,14 +293,44 @@ pub async fn update_question(
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/questions/{question_id}",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 204, description = "Question deleted"),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
 pub async fn delete_question(
     question_id: i32,
+    session: Session,
     store: Store,
+    thresholds: PrivilegeThresholds,
     id: String,
 ) -> Result<impl Reply, Rejection> {
     log::info!("{} - Deleting question...", &id);
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if let Err(e) = authorize(&store, &session, question.account_id, Privilege::DeleteOthers, thresholds).await {
+        log::warn!("{} - Account {} cannot delete question: {}", &id, session.account_id, e);
+        return Err(warp::reject::custom(e));
+    }
+    if !question.status.accepts_changes(session.is_moderator()) {
+        log::warn!("{} - Question {} is locked", &id, question.id);
+        return Err(warp::reject::custom(Error::QuestionLocked));
+    }
     match store
-        .delete_question(QuestionId(question_id))
+        .delete_question(question.id, session.account_id)
         .await {
         Ok(_) => Ok(warp::reply::with_status(
             "Question deleted",
@@ -90,3 +342,232 @@ pub async fn delete_question(
         }
     }
 }
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/restore",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Question restored", body = Question),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn restore_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Restoring question...", &id);
+    if !session.is_moderator() {
+        log::warn!("{} - Account {} cannot restore questions", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    match store.restore_question(QuestionId(question_id)).await {
+        Ok(question) => Ok(warp::reply::json(&question)),
+        Err(e) => {
+            log::error!("{} - Error restoring question: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/close",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    request_body = CloseDraft,
+    responses(
+        (status = 200, description = "Question closed", body = Question),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question not found", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = ValidationError)
+    ),
+    security(("token" = []))
+)]
+pub async fn close_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    close_draft: CloseDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Closing question...", &id);
+    if !session.is_moderator() {
+        log::warn!("{} - Account {} cannot close questions", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    let question_id = QuestionId(question_id);
+    if let Err(e) = close_draft.validate(&question_id) {
+        log::warn!("{} - Invalid close request: {}", &id, e);
+        return Err(warp::reject::custom(Error::ValidationError(e)));
+    }
+    if let Some(duplicate_of) = &close_draft.duplicate_of {
+        store
+            .get_question(duplicate_of.clone())
+            .await
+            .map_err(warp::reject::custom)?;
+    }
+    match store
+        .set_question_status(
+            question_id,
+            QuestionStatus::Closed,
+            Some(close_draft.reason),
+            close_draft.duplicate_of,
+        )
+        .await {
+        Ok(question) => Ok(warp::reply::json(&question)),
+        Err(e) => {
+            log::error!("{} - Error closing question: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Opens a closed or locked question again
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/reopen",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Question open again", body = Question),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn reopen_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Reopening question...", &id);
+    if !session.is_moderator() {
+        log::warn!("{} - Account {} cannot reopen questions", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    match store
+        .set_question_status(QuestionId(question_id), QuestionStatus::Open, None, None)
+        .await {
+        Ok(question) => Ok(warp::reply::json(&question)),
+        Err(e) => {
+            log::error!("{} - Error reopening question: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/lock",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Question locked", body = Question),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn lock_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Locking question...", &id);
+    if !session.is_moderator() {
+        log::warn!("{} - Account {} cannot lock questions", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    match store
+        .set_question_status(QuestionId(question_id), QuestionStatus::Locked, None, None)
+        .await {
+        Ok(question) => Ok(warp::reply::json(&question)),
+        Err(e) => {
+            log::error!("{} - Error locking question: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/accept/{answer_id}",
+    tag = "questions",
+    params(
+        ("question_id" = i32, Path, description = "Question id"),
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    responses(
+        (status = 200, description = "Answer accepted", body = Question),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question or answer not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn accept_answer(
+    question_id: i32,
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Accepting answer...", &id);
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if question.account_id != Some(session.account_id) {
+        log::warn!("{} - Account {} is not the question author", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    if !question.status.accepts_changes(session.is_moderator()) {
+        log::warn!("{} - Question {} is locked", &id, question.id);
+        return Err(warp::reject::custom(Error::QuestionLocked));
+    }
+    match store.accept_answer(question.id, AnswerId(answer_id)).await {
+        Ok(question) => Ok(warp::reply::json(&question)),
+        Err(e) => {
+            log::error!("{} - Error accepting answer: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Refuses changes to a locked question, its answers and comments unless the session is a
+/// moderator's
+pub async fn require_unlocked(
+    store: &Store,
+    session: &Session,
+    question_id: QuestionId,
+) -> Result<(), Error> {
+    let question = store.get_question(question_id).await?;
+    if question.status.accepts_changes(session.is_moderator()) {
+        Ok(())
+    } else {
+        Err(Error::QuestionLocked)
+    }
+}
@@ -0,0 +1,88 @@
+use warp::{Rejection, Reply};
+
+use errors::Error;
+use errors::responses::{Forbidden, Unauthorized};
+
+use crate::domain::account::{AccountId, Role, Session};
+use crate::domain::reputation::{Privilege, PrivilegeThresholds};
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    get,
+    path = "/users/{account_id}/reputation",
+    tag = "reputation",
+    params(
+        ("account_id" = i32, Path, description = "Account id")
+    ),
+    responses(
+        (status = 200, description = "Reputation with its breakdown and recent events", body = Reputation)
+    )
+)]
+pub async fn get_reputation(
+    account_id: i32,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying reputation...", &id);
+    match store.get_reputation(AccountId(account_id)).await {
+        Ok(reputation) => Ok(warp::reply::json(&reputation)),
+        Err(e) => {
+            log::error!("{} - Error getting reputation: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/{account_id}/reputation/recompute",
+    tag = "reputation",
+    params(
+        ("account_id" = i32, Path, description = "Account id")
+    ),
+    responses(
+        (status = 200, description = "Reputation recomputed from the ledger", body = Reputation),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden)
+    ),
+    security(("token" = []))
+)]
+pub async fn recompute_reputation(
+    account_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Recomputing reputation...", &id);
+    if session.role != Role::Admin {
+        log::warn!("{} - Account {} cannot recompute reputation", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    match store.recompute_reputation(AccountId(account_id)).await {
+        Ok(_) => get_reputation(account_id, store, id).await,
+        Err(e) => {
+            log::error!("{} - Error recomputing reputation: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Checks that the session may act on content owned by `owner`: owners and moderators
+/// always can, other accounts need enough reputation for the privilege
+pub async fn authorize(
+    store: &Store,
+    session: &Session,
+    owner: Option<AccountId>,
+    privilege: Privilege,
+    thresholds: PrivilegeThresholds,
+) -> Result<(), Error> {
+    if session.can_modify(owner) {
+        return Ok(());
+    }
+    let reputation = store.get_reputation_points(session.account_id).await?;
+    if thresholds.allows(privilege, reputation) {
+        Ok(())
+    } else {
+        Err(Error::Forbidden)
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use warp::{Rejection, Reply};
+use warp::http::StatusCode;
+
+use errors::Error;
+use errors::responses::{BadRequest, Forbidden, Unauthorized};
+
+use crate::domain::account::Session;
+use crate::domain::answer::AnswerId;
+use crate::domain::question::{Question, QuestionId};
+use crate::domain::revision::{history, Revision};
+use crate::infrastructure::router::question::require_unlocked;
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    get,
+    path = "/questions/{question_id}/revisions",
+    tag = "revisions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Revisions, oldest first", body = [QuestionHistoryEntry]),
+        (status = 404, description = "Question not found", body = String)
+    )
+)]
+pub async fn get_question_revisions(
+    question_id: i32,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying question revisions...", &id);
+    let question_id = QuestionId(question_id);
+    store.get_question(question_id.clone()).await.map_err(warp::reject::custom)?;
+    match store.get_question_revisions(question_id).await {
+        Ok(revisions) => Ok(warp::reply::json(&history(revisions))),
+        Err(e) => {
+            log::error!("{} - Error getting question revisions: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/questions/{question_id}/revisions/diff",
+    tag = "revisions",
+    params(
+        ("question_id" = i32, Path, description = "Question id"),
+        ("from" = i32, Query, description = "Revision compared"),
+        ("to" = i32, Query, description = "Revision compared to")
+    ),
+    responses(
+        (status = 200, description = "Changes between the two revisions", body = RevisionDiff),
+        (status = 400, response = BadRequest),
+        (status = 404, description = "Revision not found", body = String)
+    )
+)]
+pub async fn get_question_revisions_diff(
+    question_id: i32,
+    params: HashMap<String, String>,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Comparing question revisions...", &id);
+    let (from, to) = extract_revision_range(params)?;
+    let question_id = QuestionId(question_id);
+    let from = store
+        .get_question_revision(question_id.clone(), from)
+        .await
+        .map_err(warp::reject::custom)?;
+    let to = store
+        .get_question_revision(question_id, to)
+        .await
+        .map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&from.diff(&to)))
+}
+
+/// Restores an earlier revision by saving its content as a new revision
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/revisions/{revision}/rollback",
+    tag = "revisions",
+    params(
+        ("question_id" = i32, Path, description = "Question id"),
+        ("revision" = i32, Path, description = "Revision restored")
+    ),
+    responses(
+        (status = 202, description = "Question as of the revision, saved as a new one", body = Question),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Question or revision not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn rollback_question(
+    question_id: i32,
+    revision: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Rolling back question to revision {}...", &id, revision);
+    let question = store
+        .get_question(QuestionId(question_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if !session.can_modify(question.account_id) {
+        log::warn!("{} - Account {} cannot roll back question", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    if !question.status.accepts_changes(session.is_moderator()) {
+        log::warn!("{} - Question {} is locked", &id, question.id);
+        return Err(warp::reject::custom(Error::QuestionLocked));
+    }
+    let revision = store
+        .get_question_revision(question.id.clone(), revision)
+        .await
+        .map_err(warp::reject::custom)?;
+    let question = Question {
+        title: revision.title,
+        content: revision.content,
+        tags: revision.tags,
+        ..question
+    };
+    match store.update_question(question, session.account_id).await {
+        Ok(question) => Ok(warp::reply::with_status(
+            warp::reply::json(&question),
+            StatusCode::ACCEPTED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error rolling back question: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/answers/{answer_id}/revisions",
+    tag = "revisions",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    responses(
+        (status = 200, description = "Revisions, oldest first", body = [AnswerHistoryEntry]),
+        (status = 404, description = "Answer not found", body = String)
+    )
+)]
+pub async fn get_answer_revisions(
+    answer_id: i32,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying answer revisions...", &id);
+    let answer_id = AnswerId(answer_id);
+    store.get_answer(answer_id.clone()).await.map_err(warp::reject::custom)?;
+    match store.get_answer_revisions(answer_id).await {
+        Ok(revisions) => Ok(warp::reply::json(&history(revisions))),
+        Err(e) => {
+            log::error!("{} - Error getting answer revisions: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/answers/{answer_id}/revisions/diff",
+    tag = "revisions",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id"),
+        ("from" = i32, Query, description = "Revision compared"),
+        ("to" = i32, Query, description = "Revision compared to")
+    ),
+    responses(
+        (status = 200, description = "Changes between the two revisions", body = RevisionDiff),
+        (status = 400, response = BadRequest),
+        (status = 404, description = "Revision not found", body = String)
+    )
+)]
+pub async fn get_answer_revisions_diff(
+    answer_id: i32,
+    params: HashMap<String, String>,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Comparing answer revisions...", &id);
+    let (from, to) = extract_revision_range(params)?;
+    let answer_id = AnswerId(answer_id);
+    let from = store
+        .get_answer_revision(answer_id.clone(), from)
+        .await
+        .map_err(warp::reject::custom)?;
+    let to = store
+        .get_answer_revision(answer_id, to)
+        .await
+        .map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&from.diff(&to)))
+}
+
+/// Restores an earlier revision by saving its content as a new revision
+#[utoipa::path(
+    post,
+    path = "/answers/{answer_id}/revisions/{revision}/rollback",
+    tag = "revisions",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id"),
+        ("revision" = i32, Path, description = "Revision restored")
+    ),
+    responses(
+        (status = 202, description = "Answer as of the revision, saved as a new one", body = Answer),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Answer or revision not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn rollback_answer(
+    answer_id: i32,
+    revision: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Rolling back answer to revision {}...", &id, revision);
+    let answer = store
+        .get_answer(AnswerId(answer_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    if !session.can_modify(answer.account_id) {
+        log::warn!("{} - Account {} cannot roll back answer", &id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    if let Err(e) = require_unlocked(&store, &session, answer.question_id.clone()).await {
+        log::warn!("{} - Cannot roll back answer: {}", &id, e);
+        return Err(warp::reject::custom(e));
+    }
+    let revision = store
+        .get_answer_revision(answer.id.clone(), revision)
+        .await
+        .map_err(warp::reject::custom)?;
+    match store
+        .update_answer(answer.id, revision.content, session.account_id)
+        .await {
+        Ok(answer) => Ok(warp::reply::with_status(
+            warp::reply::json(&answer),
+            StatusCode::ACCEPTED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error rolling back answer: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Extracts the `from` and `to` revision numbers from the query parameters
+fn extract_revision_range(params: HashMap<String, String>) -> Result<(i32, i32), Error> {
+    match (params.get("from"), params.get("to")) {
+        (Some(from), Some(to)) => Ok((
+            from.parse::<i32>().map_err(Error::ParseError)?,
+            to.parse::<i32>().map_err(Error::ParseError)?,
+        )),
+        _ => Err(Error::MissingParameters),
+    }
+}
@@ -0,0 +1,181 @@
+use percent_encoding::percent_decode_str;
+use warp::{Rejection, Reply};
+use warp::http::StatusCode;
+
+use errors::Error;
+use errors::responses::{BadRequest, Unauthorized};
+
+use crate::domain::account::Session;
+use crate::domain::question::QuestionId;
+use crate::domain::subscription::SubscriptionTarget;
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/follow",
+    tag = "subscriptions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 201, description = "Question followed", body = Subscription),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Question not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn follow_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Following question...", &id);
+    let target = SubscriptionTarget::Question(QuestionId(question_id));
+    subscribe(target, session, store, id).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/questions/{question_id}/follow",
+    tag = "subscriptions",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 204, description = "Question no longer followed"),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Subscription not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn unfollow_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Unfollowing question...", &id);
+    let target = SubscriptionTarget::Question(QuestionId(question_id));
+    unsubscribe(target, session, store, id).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/tags/{tag}/follow",
+    tag = "subscriptions",
+    params(
+        ("tag" = String, Path, description = "Tag followed")
+    ),
+    responses(
+        (status = 201, description = "Tag followed", body = Subscription),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized)
+    ),
+    security(("token" = []))
+)]
+pub async fn follow_tag(
+    tag: String,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Following tag...", &id);
+    let target = tag_target(&tag).map_err(warp::reject::custom)?;
+    subscribe(target, session, store, id).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/tags/{tag}/follow",
+    tag = "subscriptions",
+    params(
+        ("tag" = String, Path, description = "Tag followed")
+    ),
+    responses(
+        (status = 204, description = "Tag no longer followed"),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Subscription not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn unfollow_tag(
+    tag: String,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Unfollowing tag...", &id);
+    let target = tag_target(&tag).map_err(warp::reject::custom)?;
+    unsubscribe(target, session, store, id).await
+}
+
+/// Tag of the path, which warp leaves percent-encoded, as a subscription target
+fn tag_target(tag: &str) -> Result<SubscriptionTarget, Error> {
+    let tag = percent_decode_str(tag)
+        .decode_utf8()
+        .map_err(|e| Error::InvalidParameter(format!("Invalid tag: {}", e)))?;
+    SubscriptionTarget::tag(&tag).map_err(Error::InvalidParameter)
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/subscriptions",
+    tag = "subscriptions",
+    responses(
+        (status = 200, description = "Questions and tags followed", body = [Subscription]),
+        (status = 401, response = Unauthorized)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_subscriptions(
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying subscriptions...", &id);
+    match store.get_subscriptions(session.account_id).await {
+        Ok(subscriptions) => Ok(warp::reply::json(&subscriptions)),
+        Err(e) => {
+            log::error!("{} - Error getting subscriptions: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+async fn subscribe(
+    target: SubscriptionTarget,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    match store.subscribe(session.account_id, target).await {
+        Ok(subscription) => Ok(warp::reply::with_status(
+            warp::reply::json(&subscription),
+            StatusCode::CREATED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error following: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+async fn unsubscribe(
+    target: SubscriptionTarget,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    match store.unsubscribe(session.account_id, target).await {
+        Ok(_) => Ok(warp::reply::with_status(
+            warp::reply(),
+            StatusCode::NO_CONTENT,
+        )),
+        Err(e) => {
+            log::error!("{} - Error unfollowing: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
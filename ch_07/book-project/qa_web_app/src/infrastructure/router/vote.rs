@@ -0,0 +1,152 @@
+use warp::{Rejection, Reply};
+
+use errors::responses::{LengthRequired, PayloadTooLarge, Unauthorized};
+
+use crate::domain::account::Session;
+use crate::domain::answer::AnswerId;
+use crate::domain::question::QuestionId;
+use crate::domain::vote::{VoteDirection, VoteDraft, VoteTarget};
+use crate::infrastructure::router::question::require_unlocked;
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    post,
+    path = "/questions/{question_id}/vote",
+    tag = "votes",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    request_body = VoteDraft,
+    responses(
+        (status = 200, description = "Score after the vote", body = Score),
+        (status = 401, response = Unauthorized),
+        (status = 403, description = "Vote on own question", body = String),
+        (status = 404, description = "Question not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge)
+    ),
+    security(("token" = []))
+)]
+pub async fn vote_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    vote_draft: VoteDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Voting question...", &id);
+    let target = VoteTarget::Question(QuestionId(question_id));
+    save_vote(target, session, store, Some(vote_draft.direction), id).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/questions/{question_id}/vote",
+    tag = "votes",
+    params(
+        ("question_id" = i32, Path, description = "Question id")
+    ),
+    responses(
+        (status = 200, description = "Score without the vote", body = Score),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Question not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn unvote_question(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Removing question vote...", &id);
+    let target = VoteTarget::Question(QuestionId(question_id));
+    save_vote(target, session, store, None, id).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/answers/{answer_id}/vote",
+    tag = "votes",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    request_body = VoteDraft,
+    responses(
+        (status = 200, description = "Score after the vote", body = Score),
+        (status = 401, response = Unauthorized),
+        (status = 403, description = "Vote on own answer", body = String),
+        (status = 404, description = "Answer not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge)
+    ),
+    security(("token" = []))
+)]
+pub async fn vote_answer(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    vote_draft: VoteDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Voting answer...", &id);
+    let target = VoteTarget::Answer(AnswerId(answer_id));
+    save_vote(target, session, store, Some(vote_draft.direction), id).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/answers/{answer_id}/vote",
+    tag = "votes",
+    params(
+        ("answer_id" = i32, Path, description = "Answer id")
+    ),
+    responses(
+        (status = 200, description = "Score without the vote", body = Score),
+        (status = 401, response = Unauthorized),
+        (status = 404, description = "Answer not found", body = String),
+        (status = 409, description = "Question locked by moderators", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn unvote_answer(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Removing answer vote...", &id);
+    let target = VoteTarget::Answer(AnswerId(answer_id));
+    save_vote(target, session, store, None, id).await
+}
+
+async fn save_vote(
+    target: VoteTarget,
+    session: Session,
+    store: Store,
+    direction: Option<VoteDirection>,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    let question_id = match &target {
+        VoteTarget::Question(question_id) => question_id.clone(),
+        VoteTarget::Answer(answer_id) => store
+            .get_answer(answer_id.clone())
+            .await
+            .map_err(warp::reject::custom)?
+            .question_id,
+    };
+    if let Err(e) = require_unlocked(&store, &session, question_id).await {
+        log::warn!("{} - Cannot vote: {}", &id, e);
+        return Err(warp::reject::custom(e));
+    }
+    match store.vote(target, session.account_id, direction).await {
+        Ok(score) => Ok(warp::reply::json(&score)),
+        Err(e) => {
+            log::error!("{} - Error voting: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use warp::{Rejection, Reply};
+use warp::http::StatusCode;
+
+use errors::Error;
+use errors::responses::{
+    BadRequest, Forbidden, LengthRequired, PayloadTooLarge, Unauthorized, ValidationError,
+};
+
+use crate::domain::account::{Role, Session};
+use crate::domain::webhook::{NewWebhook, WebhookDraft, WebhookId};
+use crate::infrastructure::pagination::{extract_pagination, Pagination};
+use crate::infrastructure::store::Store;
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    request_body = WebhookDraft,
+    responses(
+        (status = 201, description = "Webhook registered, with its signing secret", body = NewWebhook),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 411, response = LengthRequired),
+        (status = 413, response = PayloadTooLarge),
+        (status = 422, response = ValidationError)
+    ),
+    security(("token" = []))
+)]
+pub async fn add_webhook(
+    session: Session,
+    store: Store,
+    webhook_draft: WebhookDraft,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Adding webhook...", &id);
+    require_admin(&session, &id)?;
+    if let Err(e) = webhook_draft.validate() {
+        log::warn!("{} - Invalid webhook: {}", &id, e);
+        return Err(warp::reject::custom(Error::ValidationError(e)));
+    }
+    let secret = match &webhook_draft.secret {
+        Some(secret) => secret.clone(),
+        None => hex::encode(rand::thread_rng().gen::<[u8; 32]>()),
+    };
+    match store
+        .add_webhook(webhook_draft, &secret, session.account_id)
+        .await {
+        Ok(webhook) => Ok(warp::reply::with_status(
+            warp::reply::json(&NewWebhook { webhook, secret }),
+            StatusCode::CREATED,
+        )),
+        Err(e) => {
+            log::error!("{} - Error adding webhook: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Registered webhooks", body = [Webhook]),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_webhooks(
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying webhooks...", &id);
+    require_admin(&session, &id)?;
+    match store.get_webhooks().await {
+        Ok(webhooks) => Ok(warp::reply::json(&webhooks)),
+        Err(e) => {
+            log::error!("{} - Error getting webhooks: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{webhook_id}",
+    tag = "webhooks",
+    params(
+        ("webhook_id" = i32, Path, description = "Webhook id")
+    ),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Webhook not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn delete_webhook(
+    webhook_id: i32,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Deleting webhook...", &id);
+    require_admin(&session, &id)?;
+    match store.delete_webhook(WebhookId(webhook_id)).await {
+        Ok(_) => Ok(warp::reply::with_status(
+            warp::reply(),
+            StatusCode::NO_CONTENT,
+        )),
+        Err(e) => {
+            log::error!("{} - Error deleting webhook: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+/// Delivery log of a webhook, most recent first
+#[utoipa::path(
+    get,
+    path = "/webhooks/{webhook_id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("webhook_id" = i32, Path, description = "Webhook id"),
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Deliveries, most recent first", body = [WebhookDelivery]),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, description = "Webhook not found", body = String)
+    ),
+    security(("token" = []))
+)]
+pub async fn get_webhook_deliveries(
+    webhook_id: i32,
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+    id: String,
+) -> Result<impl Reply, Rejection> {
+    log::info!("{} - Querying webhook deliveries...", &id);
+    require_admin(&session, &id)?;
+    let mut pagination = Pagination::default();
+    if params.contains_key("offset") || params.contains_key("limit") {
+        log::debug!("{} - Pagination used", &id);
+        pagination = extract_pagination(params)?;
+    }
+    let webhook = store
+        .get_webhook(WebhookId(webhook_id))
+        .await
+        .map_err(warp::reject::custom)?;
+    match store
+        .get_webhook_deliveries(webhook.id, pagination.limit, pagination.offset)
+        .await {
+        Ok(deliveries) => Ok(warp::reply::json(&deliveries)),
+        Err(e) => {
+            log::error!("{} - Error getting webhook deliveries: {}", &id, e);
+            Err(warp::reject::custom(e))
+        }
+    }
+}
+
+fn require_admin(session: &Session, id: &str) -> Result<(), Rejection> {
+    if session.role != Role::Admin {
+        log::warn!("{} - Account {} cannot manage webhooks", id, session.account_id);
+        return Err(warp::reject::custom(Error::Forbidden));
+    }
+    Ok(())
+}
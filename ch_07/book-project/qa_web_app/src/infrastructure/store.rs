@@ -1,18 +1,57 @@
-use sqlx::{PgPool, Row};
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Row, Transaction};
 use sqlx::postgres::{PgPoolOptions, PgRow};
+use uuid::Uuid;
 
 use errors::Error;
 
+use crate::domain::account::{Account, AccountDraft, AccountId, Role, Session};
 use crate::domain::answer::{Answer, AnswerDraft, AnswerId};
-use crate::domain::question::{Question, QuestionDraft, QuestionId};
+use crate::domain::attachment::{Attachment, AttachmentDraft, AttachmentId, AttachmentTarget};
+use crate::domain::bookmark::{Bookmark, BookmarkDraft};
+use crate::domain::comment::{Comment, CommentDraft, CommentId, CommentTarget};
+use crate::domain::email::{DigestFrequency, DigestItem, EmailPreferences};
+use crate::domain::event::{EventDraft, EventKind};
+use crate::domain::flag::{
+    Flag, FlagDraft, FlagId, FlagReason, FlagStatus, FlagTarget, ModerationAction,
+    ModerationDecision, ModerationLogEntry, QueueItem,
+};
+use crate::domain::notification::{Inbox, Notification, NotificationId, NotificationKind};
+use crate::domain::question::{
+    CloseReason, Question, QuestionDraft, QuestionFilter, QuestionId, QuestionSort, QuestionStatus,
+};
+use crate::domain::reputation::{
+    vote_reputation_changes, Reputation, ReputationBreakdown, ReputationEvent, ReputationReason,
+};
+use crate::domain::revision::{AnswerRevision, QuestionRevision};
+use crate::domain::subscription::{Subscription, SubscriptionTarget};
+use crate::domain::vote::{score_delta, Score, VoteDirection, VoteTarget};
+use crate::domain::webhook::{
+    retry_delay, DeliveryId, DeliveryOutcome, DeliveryStatus, PendingDelivery, Webhook,
+    WebhookDelivery, WebhookDraft, WebhookEvent, WebhookId, MAX_DELIVERY_ATTEMPTS,
+};
+use crate::infrastructure::change_feed::{Change, ChangeEvent, CHANNEL};
+use crate::infrastructure::events::EventBus;
+use crate::infrastructure::markdown;
+use crate::infrastructure::read_cache::{Invalidation, PageKey, ReadCache};
 
 #[derive(Clone, Debug)]
 pub struct Store {
     pub connection: PgPool,
+    /// Receives the changes to questions and answers once they are committed
+    pub events: EventBus,
+    /// Questions and first listing pages, dropped on every write to questions or answers
+    pub cache: ReadCache,
+    /// Tells this instance's own changes apart from the other instances' ones
+    pub instance_id: String,
 }
 
 impl Store {
-    pub async fn new(db_url: &str) -> Self {
+    pub async fn new(db_url: &str, events: EventBus, cache: ReadCache) -> Self {
         let db_pool = match PgPoolOptions::new()
             .max_connections(5)
             .connect(db_url)
@@ -22,6 +61,9 @@ impl Store {
         };
         Store {
             connection: db_pool,
+            events,
+            cache,
+            instance_id: Uuid::new_v4().to_string(),
         }
     }
 
@@ -29,19 +71,44 @@ impl Store {
         &self,
         limit: Option<u32>,
         offset: Option<u32>,
+        filter: QuestionFilter,
+        sort: QuestionSort,
     ) -> Result<Vec<Question>, Error> {
-        match sqlx::query("SELECT * FROM questions LIMIT $1 OFFSET $2")
-            .bind(limit.unwrap_or(10) as i32)
-            .bind(offset.unwrap_or(0) as i32)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
+        let condition = match filter {
+            QuestionFilter::All => "",
+            QuestionFilter::Unanswered => "AND NOT EXISTS (SELECT 1 FROM answers a WHERE a.question_id = q.id AND a.deleted_at IS NULL)",
+            QuestionFilter::Unsolved => "AND NOT EXISTS (SELECT 1 FROM answers a WHERE a.id = q.accepted_answer_id AND a.deleted_at IS NULL)",
+        };
+        let order = match sort {
+            QuestionSort::Oldest => "q.id",
+            QuestionSort::Newest => "q.id DESC",
+            QuestionSort::Score => "q.score DESC, q.id",
+        };
+        let limit = limit.unwrap_or(10) as i32;
+        let offset = offset.unwrap_or(0) as i32;
+        let page = PageKey { filter, sort, limit };
+        if offset == 0 {
+            if let Some(questions) = self.cache.get_page(&page) {
+                return Ok(questions);
+            }
+        }
+        let generation = self.cache.generation();
+        let query = format!(
+            "SELECT q.* FROM questions q WHERE q.deleted_at IS NULL AND NOT q.hidden {} ORDER BY {} LIMIT $1 OFFSET $2",
+            condition, order
+        );
+        match sqlx::query(&query)
+            .bind(limit)
+            .bind(offset)
+            .map(|row: PgRow| question_from_row(&row))
             .fetch_all(&self.connection)
             .await {
-            Ok(questions) => Ok(questions),
+            Ok(questions) => {
+                if offset == 0 {
+                    self.cache.put_page(page, questions.clone(), generation);
+                }
+                Ok(questions)
+            }
             Err(e) => {
                 log::error!("Error getting questions: {}", e);
                 Err(Error::DatabaseQueryError)
@@ -49,92 +116,2454 @@ impl Store {
         }
     }
 
+    /// Question that is neither deleted nor hidden by flags, the latter being reviewed from
+    /// the moderation queue only
+    pub async fn get_question(&self, id: QuestionId) -> Result<Question, Error> {
+        if let Some(question) = self.cache.get_question(&id) {
+            return Ok(question);
+        }
+        let generation = self.cache.generation();
+        match sqlx::query("SELECT * FROM questions WHERE id = $1 AND deleted_at IS NULL AND NOT hidden")
+            .bind(id.0)
+            .map(|row: PgRow| question_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(question) => {
+                self.cache.put_question(question.clone(), generation);
+                Ok(question)
+            }
+            Err(sqlx::Error::RowNotFound) => Err(Error::QuestionNotFound),
+            Err(e) => {
+                log::error!("Error getting question: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Latest change to any question or answer, deleted ones included so that removals
+    /// move it forward too. The columns hold the database's local time from `NOW()`, which
+    /// is converted to UTC for HTTP dates.
+    pub async fn get_questions_last_modified(&self) -> Result<Option<NaiveDateTime>, Error> {
+        if let Some(last_modified) = self.cache.get_last_modified(None) {
+            return Ok(last_modified);
+        }
+        let generation = self.cache.generation();
+        let last_modified = sqlx::query("SELECT GREATEST((SELECT MAX(updated_on) FROM questions), (SELECT MAX(updated_on) FROM answers))::TIMESTAMPTZ AT TIME ZONE 'UTC' AS last_modified")
+            .map(|row: PgRow| row.get("last_modified"))
+            .fetch_one(&self.connection)
+            .await
+            .map_err(|e| database_error("getting questions last modification", e))?;
+        self.cache.put_last_modified(None, last_modified, generation);
+        Ok(last_modified)
+    }
+
+    /// Latest change to the question, its answers or the comments on both, in UTC
+    pub async fn get_question_last_modified(
+        &self,
+        id: QuestionId,
+    ) -> Result<Option<NaiveDateTime>, Error> {
+        if let Some(last_modified) = self.cache.get_last_modified(Some(&id)) {
+            return Ok(last_modified);
+        }
+        let generation = self.cache.generation();
+        match sqlx::query("SELECT GREATEST(q.updated_on, (SELECT MAX(a.updated_on) FROM answers a WHERE a.question_id = q.id), (SELECT MAX(GREATEST(c.created_on, c.updated_on, c.deleted_at)) FROM comments c LEFT JOIN answers a ON a.id = c.answer_id WHERE c.question_id = q.id OR a.question_id = q.id))::TIMESTAMPTZ AT TIME ZONE 'UTC' AS last_modified FROM questions q WHERE q.id = $1 AND q.deleted_at IS NULL")
+            .bind(id.0)
+            .map(|row: PgRow| row.get::<Option<NaiveDateTime>, _>("last_modified"))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(last_modified) => {
+                self.cache.put_last_modified(Some(id), last_modified, generation);
+                Ok(last_modified)
+            }
+            Err(sqlx::Error::RowNotFound) => Err(Error::QuestionNotFound),
+            Err(e) => Err(database_error("getting question last modification", e)),
+        }
+    }
+
+    /// Questions whose title is close to the given one by trigram similarity, with that
+    /// similarity, closest first
+    pub async fn get_similar_questions(
+        &self,
+        title: &str,
+        limit: i32,
+    ) -> Result<Vec<(Question, f64)>, Error> {
+        sqlx::query("SELECT *, similarity(title, $1) AS similarity FROM questions WHERE deleted_at IS NULL AND NOT hidden AND title % $1 ORDER BY similarity DESC, id LIMIT $2")
+            .bind(title)
+            .bind(limit)
+            .map(|row: PgRow| (question_from_row(&row), row.get::<f32, _>("similarity") as f64))
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting similar questions", e))
+    }
+
+    /// Adds the question, refusing it with `strict_duplicates` when an existing one has the
+    /// same title and content
     pub async fn add_question(
         &self,
         question: QuestionDraft,
+        account_id: AccountId,
+        strict_duplicates: bool,
     ) -> Result<Question, Error> {
-        match sqlx::query("INSERT INTO questions (title, content, tags) VALUES ($1, $2, $3) RETURNING id, title, content, tags")
+        let content_html = markdown::render_blocking(question.content.clone()).await;
+        let mut tx = self.begin().await?;
+        if strict_duplicates {
+            if let Some(duplicate) = find_duplicate_question(&mut tx, &question).await? {
+                log::warn!("Question duplicates question {}", duplicate);
+                return Err(Error::QuestionAlreadyExists);
+            }
+        }
+        let question = sqlx::query("INSERT INTO questions (title, content, content_html, tags, account_id) VALUES ($1, $2, $3, $4, $5) RETURNING *")
             .bind(question.title)
             .bind(question.content)
+            .bind(content_html)
             .bind(question.tags)
-            .map(|row| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
+            .bind(account_id.0)
+            .map(|row: PgRow| question_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| database_error("adding question", e))?;
+        add_question_revision(&mut tx, &question, account_id).await?;
+        // Askers follow their own questions to hear about answers
+        sqlx::query("INSERT INTO subscriptions (account_id, question_id) VALUES ($1, $2)")
+            .bind(account_id.0)
+            .bind(question.id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("following question", e))?;
+        enqueue_webhooks(&mut tx, WebhookEvent::QuestionCreated, &question).await?;
+        commit(tx).await?;
+        self.changed(
+            Some(Invalidation::Question(question.id.clone())),
+            Some(EventDraft::question(EventKind::QuestionCreated, &question)),
+        )
+        .await;
+        Ok(question)
+    }
+
+    /// Overwrites the question and records the new content as its latest revision
+    pub async fn update_question(
+        &self,
+        question: Question,
+        account_id: AccountId,
+    ) -> Result<Question, Error> {
+        let content_html = markdown::render_blocking(question.content.clone()).await;
+        let mut tx = self.begin().await?;
+        let question = write_question(&mut tx, &question, &content_html, account_id).await?;
+        commit(tx).await?;
+        self.changed(
+            Some(Invalidation::Question(question.id.clone())),
+            Some(EventDraft::question(EventKind::QuestionUpdated, &question)),
+        )
+        .await;
+        Ok(question)
+    }
+
+    /// Marks one of the question's answers as the accepted one, replacing any previous choice
+    /// and moving the acceptance reputation to the new answer's author
+    pub async fn accept_answer(
+        &self,
+        question_id: QuestionId,
+        answer_id: AnswerId,
+    ) -> Result<Question, Error> {
+        let mut tx = self.begin().await?;
+        // Locks the question even without an accepted answer, so that concurrent first
+        // acceptances wait for each other instead of both awarding reputation
+        let previous_id = match sqlx::query("SELECT accepted_answer_id FROM questions WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+            .bind(question_id.0)
+            .map(|row: PgRow| row.get::<Option<i32>, _>("accepted_answer_id").map(AnswerId))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(previous_id) => previous_id,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+            Err(e) => return Err(database_error("getting accepted answer", e)),
+        };
+        let previous = match previous_id {
+            Some(previous_id) => {
+                let owner = sqlx::query("SELECT account_id FROM answers WHERE id = $1")
+                    .bind(previous_id.0)
+                    .map(|row: PgRow| row.get::<Option<i32>, _>("account_id"))
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(|e| database_error("getting accepted answer author", e))?;
+                Some((previous_id, owner.flatten()))
+            }
+            None => None,
+        };
+        let question = match sqlx::query("UPDATE questions q SET accepted_answer_id = a.id FROM answers a WHERE q.id = $1 AND q.deleted_at IS NULL AND a.id = $2 AND a.question_id = q.id AND a.deleted_at IS NULL RETURNING q.*")
+            .bind(question_id.0)
+            .bind(answer_id.0)
+            .map(|row: PgRow| question_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(question) => question,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::AnswerNotFound),
+            Err(e) => return Err(database_error("accepting answer", e)),
+        };
+        if previous.as_ref().map(|(id, _)| id) != Some(&answer_id) {
+            let reason = ReputationReason::AnswerAccepted;
+            let question_id = Some(&question.id);
+            // Accepting your own answer earns nothing
+            if let Some((previous_id, Some(owner))) = previous {
+                let owner = AccountId(owner);
+                if Some(owner) != question.account_id {
+                    let points = -reason.points();
+                    add_reputation_event(&mut tx, owner, reason, points, question_id, Some(&previous_id))
+                        .await?;
+                }
+            }
+            let owner: Option<i32> = sqlx::query("SELECT account_id FROM answers WHERE id = $1")
+                .bind(answer_id.0)
+                .map(|row: PgRow| row.get("account_id"))
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| database_error("getting answer author", e))?;
+            if let Some(owner) = owner.map(AccountId) {
+                if Some(owner) != question.account_id {
+                    let points = reason.points();
+                    add_reputation_event(&mut tx, owner, reason, points, question_id, Some(&answer_id))
+                        .await?;
+                }
+            }
+            let kind = NotificationKind::AcceptedAnswer;
+            notify_followers(&mut tx, kind, question.account_id, &question.id, Some(&answer_id), None)
+                .await?;
+            enqueue_webhooks(&mut tx, WebhookEvent::AnswerAccepted, &question).await?;
+        }
+        commit(tx).await?;
+        self.changed(
+            Some(Invalidation::Question(question.id.clone())),
+            Some(EventDraft::question(EventKind::QuestionUpdated, &question)),
+        )
+        .await;
+        Ok(question)
+    }
+
+    /// Opens, closes or locks the question. The reason and canonical question are only kept
+    /// while it is closed.
+    pub async fn set_question_status(
+        &self,
+        id: QuestionId,
+        status: QuestionStatus,
+        reason: Option<CloseReason>,
+        duplicate_of: Option<QuestionId>,
+    ) -> Result<Question, Error> {
+        let question = match sqlx::query("UPDATE questions SET status = $2, close_reason = $3, duplicate_of = $4 WHERE id = $1 AND deleted_at IS NULL RETURNING *")
+            .bind(id.0)
+            .bind(status.as_str())
+            .bind(reason.map(|reason| reason.as_str()))
+            .bind(duplicate_of.map(|duplicate_of| duplicate_of.0))
+            .map(|row: PgRow| question_from_row(&row))
             .fetch_one(&self.connection)
             .await {
-            Ok(question) => Ok(question),
+            Ok(question) => question,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+            Err(e) => return Err(database_error("changing question status", e)),
+        };
+        self.changed(
+            Some(Invalidation::Question(question.id.clone())),
+            Some(EventDraft::question(EventKind::QuestionUpdated, &question)),
+        )
+        .await;
+        Ok(question)
+    }
+
+    /// Soft deletes the question together with its answers, keeping them restorable
+    /// until the purge job removes them
+    pub async fn delete_question(
+        &self,
+        id: QuestionId,
+        account_id: AccountId,
+    ) -> Result<bool, Error> {
+        let mut tx = self.begin().await?;
+        let tags = soft_delete_question(&mut tx, &id, account_id).await?;
+        commit(tx).await?;
+        self.changed(
+            Some(Invalidation::Question(id.clone())),
+            Some(EventDraft::question_deleted(id, tags)),
+        )
+        .await;
+
+        Ok(true)
+    }
+
+    /// Restores a soft deleted question and the answers deleted along with it
+    pub async fn restore_question(&self, id: QuestionId) -> Result<Question, Error> {
+        let mut tx = self.begin().await?;
+        let deleted_at: NaiveDateTime = match sqlx::query("SELECT deleted_at FROM questions WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE")
+            .bind(id.0)
+            .map(|row: PgRow| row.get("deleted_at"))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(deleted_at) => deleted_at,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+            Err(e) => return Err(database_error("restoring question", e)),
+        };
+        sqlx::query("UPDATE answers SET deleted_at = NULL, deleted_by = NULL WHERE question_id = $1 AND deleted_at = $2")
+            .bind(id.0)
+            .bind(deleted_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("restoring question answers", e))?;
+        let question = sqlx::query("UPDATE questions SET deleted_at = NULL, deleted_by = NULL WHERE id = $1 RETURNING *")
+            .bind(id.0)
+            .map(|row: PgRow| question_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| database_error("restoring question", e))?;
+        commit(tx).await?;
+        self.changed(
+            Some(Invalidation::Question(id)),
+            Some(EventDraft::question(EventKind::QuestionRestored, &question)),
+        )
+        .await;
+        Ok(question)
+    }
+
+    /// Lists the answers of a question, the accepted one first and the rest by score.
+    /// Without a limit the first 10 answers are returned.
+    pub async fn get_answers(
+        &self,
+        question_id: QuestionId,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Answer>, Error> {
+        match sqlx::query("SELECT a.* FROM answers a JOIN questions q ON q.id = a.question_id WHERE a.question_id = $1 AND a.deleted_at IS NULL AND NOT a.hidden ORDER BY a.id = q.accepted_answer_id IS TRUE DESC, a.score DESC, a.id LIMIT $2 OFFSET $3")
+            .bind(question_id.0)
+            .bind(limit.unwrap_or(10) as i32)
+            .bind(offset.unwrap_or(0) as i32)
+            .map(|row: PgRow| answer_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(answers) => Ok(answers),
             Err(e) => {
-                log::error!("Error adding question: {}", e);
+                log::error!("Error getting answers: {}", e);
                 Err(Error::DatabaseQueryError)
             }
         }
     }
 
-    pub async fn update_question(
+    /// Every answer of a question, in the order of `get_answers`, for the question's page
+    pub async fn get_question_thread_answers(
         &self,
-        question: Question,
-    ) -> Result<Question, Error> {
-        match sqlx::query("UPDATE questions SET title = $1, content = $2, tags = $3 WHERE id = $4 RETURNING *")
-            .bind(&question.title)
-            .bind(&question.content)
-            .bind(&question.tags)
-            .bind(question.id.0)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
+        question_id: QuestionId,
+    ) -> Result<Vec<Answer>, Error> {
+        match sqlx::query("SELECT a.* FROM answers a JOIN questions q ON q.id = a.question_id WHERE a.question_id = $1 AND a.deleted_at IS NULL AND NOT a.hidden ORDER BY a.id = q.accepted_answer_id IS TRUE DESC, a.score DESC, a.id")
+            .bind(question_id.0)
+            .map(|row: PgRow| answer_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(answers) => Ok(answers),
+            Err(e) => {
+                log::error!("Error getting question thread answers: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Answer that is neither deleted nor hidden by flags
+    pub async fn get_answer(&self, id: AnswerId) -> Result<Answer, Error> {
+        match sqlx::query("SELECT * FROM answers WHERE id = $1 AND deleted_at IS NULL AND NOT hidden")
+            .bind(id.0)
+            .map(|row: PgRow| answer_from_row(&row))
             .fetch_one(&self.connection)
             .await {
-            Ok(question) => Ok(question),
+            Ok(answer) => Ok(answer),
+            Err(sqlx::Error::RowNotFound) => Err(Error::AnswerNotFound),
             Err(e) => {
-                log::error!("Error updating question: {}", e);
+                log::error!("Error getting answer: {}", e);
                 Err(Error::DatabaseQueryError)
             }
         }
     }
 
-    pub async fn delete_question(&self, id: QuestionId) -> Result<bool, Error> {
-        let result = sqlx::query("DELETE FROM questions WHERE id = $1")
+    pub async fn add_answer(
+        &self,
+        answer: AnswerDraft,
+        account_id: AccountId,
+    ) -> Result<Answer, Error> {
+        let content_html = markdown::render_blocking(answer.content.clone()).await;
+        let mut tx = self.begin().await?;
+        // Locks the question so that it cannot be closed while the answer is added
+        let status: QuestionStatus = match sqlx::query("SELECT status FROM questions WHERE id = $1 AND deleted_at IS NULL FOR SHARE")
+            .bind(answer.question_id.0)
+            .map(|row: PgRow| row.get::<String, _>("status"))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(status) => status.parse().unwrap_or(QuestionStatus::Open),
+            Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+            Err(e) => return Err(database_error("getting question status", e)),
+        };
+        if status == QuestionStatus::Locked {
+            return Err(Error::QuestionLocked);
+        }
+        if !status.accepts_answers() {
+            return Err(Error::QuestionClosed);
+        }
+        let answer = sqlx::query("INSERT INTO answers (content, content_html, question_id, account_id) VALUES ($1, $2, $3, $4) RETURNING *")
+            .bind(answer.content)
+            .bind(content_html)
+            .bind(answer.question_id.0)
+            .bind(account_id.0)
+            .map(|row: PgRow| answer_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| database_error("adding answer", e))?;
+        add_answer_revision(&mut tx, &answer, account_id).await?;
+        let actor_id = Some(account_id);
+        notify_followers(&mut tx, NotificationKind::Answer, actor_id, &answer.question_id, Some(&answer.id), None)
+            .await?;
+        enqueue_webhooks(&mut tx, WebhookEvent::AnswerAdded, &answer).await?;
+        commit(tx).await?;
+        let tags = self.question_tags(&answer.question_id).await;
+        self.changed(
+            Some(Invalidation::Pages),
+            Some(EventDraft::answer(EventKind::AnswerCreated, &answer, tags)),
+        )
+        .await;
+        Ok(answer)
+    }
+
+    /// Overwrites the answer content and records it as the answer's latest revision
+    pub async fn update_answer(
+        &self,
+        id: AnswerId,
+        content: String,
+        account_id: AccountId,
+    ) -> Result<Answer, Error> {
+        let content_html = markdown::render_blocking(content.clone()).await;
+        let mut tx = self.begin().await?;
+        let answer = write_answer(&mut tx, &id, content, &content_html, account_id).await?;
+        commit(tx).await?;
+        let tags = self.question_tags(&answer.question_id).await;
+        let event = EventDraft::answer(EventKind::AnswerUpdated, &answer, tags);
+        self.changed(Some(Invalidation::Validators), Some(event)).await;
+        Ok(answer)
+    }
+
+    pub async fn delete_answer(
+        &self,
+        id: AnswerId,
+        account_id: AccountId,
+    ) -> Result<bool, Error> {
+        let mut tx = self.begin().await?;
+        let question_id = soft_delete_answer(&mut tx, &id, account_id).await?;
+        commit(tx).await?;
+        let tags = self.question_tags(&question_id).await;
+        // The question may be unanswered or unsolved again
+        self.changed(
+            Some(Invalidation::Pages),
+            Some(EventDraft::answer_deleted(question_id, id, tags)),
+        )
+        .await;
+        Ok(true)
+    }
+
+    /// Restores a soft deleted answer as long as its question is not deleted
+    pub async fn restore_answer(&self, id: AnswerId) -> Result<Answer, Error> {
+        match sqlx::query("UPDATE answers a SET deleted_at = NULL, deleted_by = NULL FROM questions q WHERE a.id = $1 AND a.deleted_at IS NOT NULL AND q.id = a.question_id AND q.deleted_at IS NULL RETURNING a.*")
             .bind(id.0)
-            .execute(&self.connection)
+            .map(|row: PgRow| answer_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(answer) => {
+                let tags = self.question_tags(&answer.question_id).await;
+                // The question may be answered or solved again
+                self.changed(
+                    Some(Invalidation::Pages),
+                    Some(EventDraft::answer(EventKind::AnswerRestored, &answer, tags)),
+                )
+                .await;
+                Ok(answer)
+            }
+            Err(sqlx::Error::RowNotFound) => Err(Error::AnswerNotFound),
+            Err(e) => {
+                log::error!("Error restoring answer: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Permanently removes questions, answers and comments soft deleted more than
+    /// `retention_days` ago, returning how many questions and answers were purged
+    pub async fn purge_deleted(&self, retention_days: i32) -> Result<(u64, u64), Error> {
+        let mut tx = self.begin().await?;
+        sqlx::query("DELETE FROM comments WHERE deleted_at < NOW() - make_interval(days => $1)")
+            .bind(retention_days)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("purging comments", e))?;
+        let answers = sqlx::query("DELETE FROM answers WHERE deleted_at < NOW() - make_interval(days => $1)")
+            .bind(retention_days)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("purging answers", e))?
+            .rows_affected();
+        let questions = sqlx::query("DELETE FROM questions WHERE deleted_at < NOW() - make_interval(days => $1)")
+            .bind(retention_days)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("purging questions", e))?
+            .rows_affected();
+        commit(tx).await?;
+        Ok((questions, answers))
+    }
+
+    /// Casts, changes or (with `None`) withdraws the account's vote on a question or answer,
+    /// keeping the denormalised score of the target in sync within the same transaction
+    pub async fn vote(
+        &self,
+        target: VoteTarget,
+        account_id: AccountId,
+        direction: Option<VoteDirection>,
+    ) -> Result<Score, Error> {
+        let not_found = match target {
+            VoteTarget::Question(_) => Error::QuestionNotFound,
+            VoteTarget::Answer(_) => Error::AnswerNotFound,
+        };
+        let mut tx = self.begin().await?;
+        // Locking the target serialises concurrent votes on it
+        let (owner, question_id): (Option<i32>, QuestionId) = match sqlx::query(&format!(
+            "SELECT account_id, {} AS question_id FROM {} WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+            target.question_column(),
+            target.table()
+        ))
+            .bind(target.id())
+            .map(|row: PgRow| (row.get("account_id"), QuestionId(row.get("question_id"))))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(target) => target,
+            Err(sqlx::Error::RowNotFound) => return Err(not_found),
+            Err(e) => return Err(database_error("getting vote target", e)),
+        };
+        if owner == Some(account_id.0) {
+            return Err(Error::SelfVote);
+        }
+        let previous: Option<VoteDirection> = sqlx::query(&format!(
+            "SELECT value FROM votes WHERE account_id = $1 AND {} = $2",
+            target.column()
+        ))
+            .bind(account_id.0)
+            .bind(target.id())
+            .map(|row: PgRow| VoteDirection::from_value(row.get("value")))
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| database_error("getting vote", e))?
+            .flatten();
+        if let Some(owner) = owner.map(AccountId) {
+            let (question_id, answer_id) = match &target {
+                VoteTarget::Question(id) => (Some(id), None),
+                VoteTarget::Answer(id) => (None, Some(id)),
+            };
+            for (reason, points) in vote_reputation_changes(&target, previous, direction) {
+                add_reputation_event(&mut tx, owner, reason, points, question_id, answer_id)
+                    .await?;
+            }
+        }
+        if previous != direction {
+            let column = target.column();
+            let statement = match (previous, direction) {
+                (None, _) => format!(
+                    "INSERT INTO votes (account_id, {}, value) VALUES ($1, $2, $3)",
+                    column
+                ),
+                (_, Some(_)) => format!(
+                    "UPDATE votes SET value = $3, created_on = NOW() WHERE account_id = $1 AND {} = $2",
+                    column
+                ),
+                (_, None) => format!(
+                    "DELETE FROM votes WHERE account_id = $1 AND {} = $2",
+                    column
+                ),
+            };
+            let mut query = sqlx::query(&statement)
+                .bind(account_id.0)
+                .bind(target.id());
+            if let Some(direction) = direction {
+                query = query.bind(direction.value());
+            }
+            query
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| database_error("saving vote", e))?;
+        }
+        let score: i32 = sqlx::query(&format!(
+            "UPDATE {} SET score = score + $2 WHERE id = $1 RETURNING score",
+            target.table()
+        ))
+            .bind(target.id())
+            .bind(score_delta(previous, direction))
+            .map(|row: PgRow| row.get("score"))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| database_error("updating score", e))?;
+        commit(tx).await?;
+        let invalidation = match &target {
+            VoteTarget::Question(id) => Some(Invalidation::Question(id.clone())),
+            VoteTarget::Answer(_) => Some(Invalidation::Validators),
+        };
+        let tags = self.question_tags(&question_id).await;
+        self.changed(invalidation, Some(EventDraft::voted(&target, question_id, score, tags)))
             .await;
+        Ok(Score {
+            score,
+            vote: direction,
+        })
+    }
+
+    /// Adds a comment to a question or answer, failing when the target does not exist,
+    /// and notifies the followers of the question
+    pub async fn add_comment(
+        &self,
+        target: CommentTarget,
+        comment: CommentDraft,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        let mut tx = self.begin().await?;
+        let query = format!(
+            "WITH comment AS (INSERT INTO comments ({}, content, account_id) SELECT id, $2, $3 FROM {} WHERE id = $1 AND deleted_at IS NULL RETURNING *) SELECT comment.*, COALESCE(comment.question_id, a.question_id) AS thread_id FROM comment LEFT JOIN answers a ON a.id = comment.answer_id",
+            target.column(),
+            target.table()
+        );
+        let (comment, question_id) = match sqlx::query(&query)
+            .bind(target.id())
+            .bind(comment.content)
+            .bind(account_id.0)
+            .map(|row: PgRow| (comment_from_row(&row), QuestionId(row.get("thread_id"))))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(comment) => comment,
+            Err(sqlx::Error::RowNotFound) => return match target {
+                CommentTarget::Question(_) => Err(Error::QuestionNotFound),
+                CommentTarget::Answer(_) => Err(Error::AnswerNotFound),
+            },
+            Err(e) => return Err(database_error("adding comment", e)),
+        };
+        let (kind, actor_id) = (NotificationKind::Comment, Some(account_id));
+        notify_followers(&mut tx, kind, actor_id, &question_id, comment.answer_id.as_ref(), Some(&comment.id))
+            .await?;
+        commit(tx).await?;
+        self.changed(Some(Invalidation::Validators), None).await;
+        Ok(comment)
+    }
 
-        let rows_affected = match result {
-            Ok(r) => r.rows_affected(),
+    pub async fn get_comments(&self, target: CommentTarget) -> Result<Vec<Comment>, Error> {
+        let query = format!(
+            "SELECT * FROM comments WHERE {} = $1 AND deleted_at IS NULL AND NOT hidden ORDER BY id",
+            target.column()
+        );
+        match sqlx::query(&query)
+            .bind(target.id())
+            .map(|row: PgRow| comment_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(comments) => Ok(comments),
             Err(e) => {
-                log::error!("Error deleting question: {}", e);
-                return Err(Error::DatabaseQueryError);
+                log::error!("Error getting comments: {}", e);
+                Err(Error::DatabaseQueryError)
             }
-        };
+        }
+    }
+
+    /// Comments on a question and on all of its answers
+    pub async fn get_question_thread_comments(
+        &self,
+        question_id: QuestionId,
+    ) -> Result<Vec<Comment>, Error> {
+        match sqlx::query("SELECT * FROM comments WHERE (question_id = $1 OR answer_id IN (SELECT id FROM answers WHERE question_id = $1 AND deleted_at IS NULL AND NOT hidden)) AND deleted_at IS NULL AND NOT hidden ORDER BY id")
+            .bind(question_id.0)
+            .map(|row: PgRow| comment_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(comments) => Ok(comments),
+            Err(e) => {
+                log::error!("Error getting question thread comments: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
 
-        if rows_affected == 0 {
-            return Err(Error::QuestionNotFound);
+    pub async fn get_comment(&self, id: CommentId) -> Result<Comment, Error> {
+        match sqlx::query("SELECT * FROM comments WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id.0)
+            .map(|row: PgRow| comment_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(comment) => Ok(comment),
+            Err(sqlx::Error::RowNotFound) => Err(Error::CommentNotFound),
+            Err(e) => {
+                log::error!("Error getting comment: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
         }
+    }
+
+    pub async fn update_comment(
+        &self,
+        id: CommentId,
+        comment: CommentDraft,
+    ) -> Result<Comment, Error> {
+        let mut tx = self.begin().await?;
+        let comment = write_comment(&mut tx, &id, comment.content).await?;
+        commit(tx).await?;
+        self.changed(Some(Invalidation::Validators), None).await;
+        Ok(comment)
+    }
 
+    pub async fn delete_comment(
+        &self,
+        id: CommentId,
+        account_id: AccountId,
+    ) -> Result<bool, Error> {
+        let mut tx = self.begin().await?;
+        soft_delete_comment(&mut tx, &id, account_id).await?;
+        commit(tx).await?;
+        self.changed(Some(Invalidation::Validators), None).await;
         Ok(true)
     }
 
-    pub async fn add_answer(&self, answer: AnswerDraft) -> Result<Answer, Error> {
-        match sqlx::query("INSERT INTO answers (content, question_id) VALUES ($1, $2) RETURNING id, content, question_id")
-            .bind(answer.content)
-            .bind(answer.question_id.0)
-            .map(|row: PgRow| Answer {
-                id: AnswerId(row.get("id")),
-                content: row.get("content"),
-                question_id: QuestionId(row.get("question_id")),
+    /// Records a file stored under `blob_key` as attached to the question or answer
+    pub async fn add_attachment(
+        &self,
+        target: AttachmentTarget,
+        attachment: AttachmentDraft,
+        blob_key: &str,
+        account_id: AccountId,
+    ) -> Result<Attachment, Error> {
+        let query = format!(
+            "INSERT INTO attachments ({}, account_id, file_name, content_type, size, blob_key) SELECT id, $2, $3, $4, $5, $6 FROM {} WHERE id = $1 AND deleted_at IS NULL RETURNING *",
+            target.column(),
+            target.table()
+        );
+        match sqlx::query(&query)
+            .bind(target.id())
+            .bind(account_id.0)
+            .bind(attachment.file_name)
+            .bind(attachment.content_type)
+            .bind(attachment.size)
+            .bind(blob_key)
+            .map(|row: PgRow| attachment_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(attachment) => Ok(attachment),
+            Err(sqlx::Error::RowNotFound) => match target {
+                AttachmentTarget::Question(_) => Err(Error::QuestionNotFound),
+                AttachmentTarget::Answer(_) => Err(Error::AnswerNotFound),
+            },
+            Err(e) => Err(database_error("adding attachment", e)),
+        }
+    }
+
+    /// Attachments of the question or answer, none when it, or the answer's question, is
+    /// deleted or hidden
+    pub async fn get_attachments(&self, target: AttachmentTarget) -> Result<Vec<Attachment>, Error> {
+        let query = format!(
+            "SELECT attachments.* FROM attachments LEFT JOIN questions q ON q.id = attachments.question_id LEFT JOIN answers a ON a.id = attachments.answer_id LEFT JOIN questions aq ON aq.id = a.question_id WHERE attachments.{} = $1 AND COALESCE(q.deleted_at, a.deleted_at, aq.deleted_at) IS NULL AND NOT COALESCE(q.hidden, a.hidden OR aq.hidden) ORDER BY attachments.id",
+            target.column()
+        );
+        sqlx::query(&query)
+            .bind(target.id())
+            .map(|row: PgRow| attachment_from_row(&row))
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting attachments", e))
+    }
+
+    /// Attachment of a question, or of an answer and its question, that is neither deleted nor
+    /// hidden
+    pub async fn get_attachment(&self, id: AttachmentId) -> Result<Attachment, Error> {
+        match sqlx::query("SELECT attachments.* FROM attachments LEFT JOIN questions q ON q.id = attachments.question_id LEFT JOIN answers a ON a.id = attachments.answer_id LEFT JOIN questions aq ON aq.id = a.question_id WHERE attachments.id = $1 AND COALESCE(q.deleted_at, a.deleted_at, aq.deleted_at) IS NULL AND NOT COALESCE(q.hidden, a.hidden OR aq.hidden)")
+            .bind(id.0)
+            .map(|row: PgRow| attachment_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(attachment) => Ok(attachment),
+            Err(sqlx::Error::RowNotFound) => Err(Error::AttachmentNotFound),
+            Err(e) => Err(database_error("getting attachment", e)),
+        }
+    }
+
+    pub async fn delete_attachment(&self, id: AttachmentId) -> Result<bool, Error> {
+        match sqlx::query("DELETE FROM attachments WHERE id = $1")
+            .bind(id.0)
+            .execute(&self.connection)
+            .await {
+            Ok(r) if r.rows_affected() == 0 => Err(Error::AttachmentNotFound),
+            Ok(_) => Ok(true),
+            Err(e) => Err(database_error("deleting attachment", e)),
+        }
+    }
+
+    /// Deletes the attachments of the content `purge_deleted` is about to remove, returning
+    /// the keys of their blobs
+    pub async fn purge_attachments(&self, retention_days: i32) -> Result<Vec<String>, Error> {
+        sqlx::query("WITH purged AS (SELECT id FROM questions WHERE deleted_at < NOW() - make_interval(days => $1)) DELETE FROM attachments WHERE question_id IN (SELECT id FROM purged) OR answer_id IN (SELECT id FROM answers WHERE deleted_at < NOW() - make_interval(days => $1) OR question_id IN (SELECT id FROM purged)) RETURNING blob_key")
+            .bind(retention_days)
+            .map(|row: PgRow| row.get("blob_key"))
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("purging attachments", e))
+    }
+
+    /// Bookmarks the question for the account, or moves an existing bookmark to another
+    /// collection, keeping the question's denormalised bookmark count in sync
+    pub async fn add_bookmark(
+        &self,
+        question_id: QuestionId,
+        account_id: AccountId,
+        bookmark: BookmarkDraft,
+    ) -> Result<Bookmark, Error> {
+        let mut tx = self.begin().await?;
+        // Locking the question serialises concurrent bookmarks on it
+        let question = match sqlx::query("SELECT * FROM questions WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+            .bind(question_id.0)
+            .map(|row: PgRow| question_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(question) => question,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+            Err(e) => return Err(database_error("getting bookmarked question", e)),
+        };
+        let updated: Option<NaiveDateTime> = sqlx::query("UPDATE bookmarks SET collection = $3 WHERE account_id = $1 AND question_id = $2 RETURNING created_on")
+            .bind(account_id.0)
+            .bind(question.id.0)
+            .bind(&bookmark.collection)
+            .map(|row: PgRow| row.get("created_on"))
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| database_error("updating bookmark", e))?;
+        let (question, bookmarked_on) = match updated {
+            Some(bookmarked_on) => (question, bookmarked_on),
+            None => {
+                let bookmarked_on = sqlx::query("INSERT INTO bookmarks (account_id, question_id, collection) VALUES ($1, $2, $3) RETURNING created_on")
+                    .bind(account_id.0)
+                    .bind(question.id.0)
+                    .bind(&bookmark.collection)
+                    .map(|row: PgRow| row.get("created_on"))
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(|e| database_error("adding bookmark", e))?;
+                let question = sqlx::query("UPDATE questions SET bookmark_count = bookmark_count + 1 WHERE id = $1 RETURNING *")
+                    .bind(question.id.0)
+                    .map(|row: PgRow| question_from_row(&row))
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(|e| database_error("updating bookmark count", e))?;
+                (question, bookmarked_on)
+            }
+        };
+        commit(tx).await?;
+        self.changed(Some(Invalidation::Question(question.id.clone())), None).await;
+        Ok(Bookmark {
+            question,
+            collection: bookmark.collection,
+            bookmarked_on,
+        })
+    }
+
+    pub async fn delete_bookmark(
+        &self,
+        question_id: QuestionId,
+        account_id: AccountId,
+    ) -> Result<bool, Error> {
+        let mut tx = self.begin().await?;
+        let result = sqlx::query("DELETE FROM bookmarks WHERE account_id = $1 AND question_id = $2")
+            .bind(account_id.0)
+            .bind(question_id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("deleting bookmark", e))?;
+        if result.rows_affected() == 0 {
+            return Err(Error::BookmarkNotFound);
+        }
+        sqlx::query("UPDATE questions SET bookmark_count = bookmark_count - 1 WHERE id = $1")
+            .bind(question_id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("updating bookmark count", e))?;
+        commit(tx).await?;
+        self.changed(Some(Invalidation::Question(question_id)), None).await;
+        Ok(true)
+    }
+
+    /// Lists the account's bookmarks, most recent first, optionally only those of one
+    /// collection. Bookmarks on deleted questions are left out.
+    pub async fn get_bookmarks(
+        &self,
+        account_id: AccountId,
+        collection: Option<String>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Bookmark>, Error> {
+        match sqlx::query("SELECT q.*, b.collection, b.created_on AS bookmarked_on FROM bookmarks b JOIN questions q ON q.id = b.question_id WHERE b.account_id = $1 AND ($2::VARCHAR IS NULL OR b.collection = $2) AND q.deleted_at IS NULL ORDER BY b.created_on DESC, b.id DESC LIMIT $3 OFFSET $4")
+            .bind(account_id.0)
+            .bind(collection)
+            .bind(limit.unwrap_or(10) as i32)
+            .bind(offset.unwrap_or(0) as i32)
+            .map(|row: PgRow| Bookmark {
+                question: question_from_row(&row),
+                collection: row.get("collection"),
+                bookmarked_on: row.get("bookmarked_on"),
             })
+            .fetch_all(&self.connection)
+            .await {
+            Ok(bookmarks) => Ok(bookmarks),
+            Err(e) => {
+                log::error!("Error getting bookmarks: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Follows a question or tag, doing nothing when the account already follows it
+    pub async fn subscribe(
+        &self,
+        account_id: AccountId,
+        target: SubscriptionTarget,
+    ) -> Result<Subscription, Error> {
+        let query = match &target {
+            SubscriptionTarget::Question(id) => sqlx::query("INSERT INTO subscriptions (account_id, question_id) SELECT $1, id FROM questions WHERE id = $2 AND deleted_at IS NULL ON CONFLICT (account_id, question_id) WHERE question_id IS NOT NULL DO UPDATE SET created_on = subscriptions.created_on RETURNING created_on")
+                .bind(account_id.0)
+                .bind(id.0),
+            SubscriptionTarget::Tag(tag) => sqlx::query("INSERT INTO subscriptions (account_id, tag) VALUES ($1, $2) ON CONFLICT (account_id, tag) WHERE tag IS NOT NULL DO UPDATE SET created_on = subscriptions.created_on RETURNING created_on")
+                .bind(account_id.0)
+                .bind(tag.clone()),
+        };
+        match query
+            .map(|row: PgRow| row.get("created_on"))
             .fetch_one(&self.connection)
             .await {
-            Ok(answer) => Ok(answer),
+            Ok(created_on) => Ok(Subscription { target, created_on }),
+            Err(sqlx::Error::RowNotFound) => Err(Error::QuestionNotFound),
+            Err(e) => {
+                log::error!("Error following: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn unsubscribe(
+        &self,
+        account_id: AccountId,
+        target: SubscriptionTarget,
+    ) -> Result<bool, Error> {
+        let query = match target {
+            SubscriptionTarget::Question(id) => sqlx::query("DELETE FROM subscriptions WHERE account_id = $1 AND question_id = $2")
+                .bind(account_id.0)
+                .bind(id.0),
+            SubscriptionTarget::Tag(tag) => sqlx::query("DELETE FROM subscriptions WHERE account_id = $1 AND tag = $2")
+                .bind(account_id.0)
+                .bind(tag),
+        };
+        match query.execute(&self.connection).await {
+            Ok(r) if r.rows_affected() == 0 => Err(Error::SubscriptionNotFound),
+            Ok(_) => Ok(true),
+            Err(e) => {
+                log::error!("Error unfollowing: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_subscriptions(&self, account_id: AccountId) -> Result<Vec<Subscription>, Error> {
+        match sqlx::query("SELECT * FROM subscriptions WHERE account_id = $1 ORDER BY id")
+            .bind(account_id.0)
+            .map(|row: PgRow| subscription_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(subscriptions) => Ok(subscriptions),
             Err(e) => {
-                log::error!("Error adding answer: {}", e);
+                log::error!("Error getting subscriptions: {}", e);
                 Err(Error::DatabaseQueryError)
             }
         }
     }
+
+    /// Lists the account's notifications, newest first, with the number still unread
+    pub async fn get_notifications(
+        &self,
+        account_id: AccountId,
+        unread_only: bool,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Inbox, Error> {
+        let notifications = sqlx::query("SELECT * FROM notifications WHERE account_id = $1 AND (NOT $2 OR read_on IS NULL) ORDER BY id DESC LIMIT $3 OFFSET $4")
+            .bind(account_id.0)
+            .bind(unread_only)
+            .bind(limit.unwrap_or(10) as i32)
+            .bind(offset.unwrap_or(0) as i32)
+            .map(|row: PgRow| notification_from_row(&row))
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting notifications", e))?
+            .into_iter()
+            .flatten()
+            .collect();
+        let unread = sqlx::query("SELECT COUNT(*) AS unread FROM notifications WHERE account_id = $1 AND read_on IS NULL")
+            .bind(account_id.0)
+            .map(|row: PgRow| row.get("unread"))
+            .fetch_one(&self.connection)
+            .await
+            .map_err(|e| database_error("counting unread notifications", e))?;
+        Ok(Inbox {
+            unread,
+            notifications,
+        })
+    }
+
+    /// Marks one of the account's notifications as read, keeping the time of the first read
+    pub async fn mark_notification_read(
+        &self,
+        account_id: AccountId,
+        id: NotificationId,
+    ) -> Result<Notification, Error> {
+        match sqlx::query("UPDATE notifications SET read_on = COALESCE(read_on, NOW()) WHERE id = $1 AND account_id = $2 RETURNING *")
+            .bind(id.0)
+            .bind(account_id.0)
+            .map(|row: PgRow| notification_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(Some(notification)) => Ok(notification),
+            // Read all the same, but of a kind this version cannot show
+            Ok(None) | Err(sqlx::Error::RowNotFound) => Err(Error::NotificationNotFound),
+            Err(e) => {
+                log::error!("Error marking notification as read: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Marks all unread notifications of the account as read, returning how many changed
+    pub async fn mark_all_notifications_read(&self, account_id: AccountId) -> Result<u64, Error> {
+        match sqlx::query("UPDATE notifications SET read_on = NOW() WHERE account_id = $1 AND read_on IS NULL")
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await {
+            Ok(r) => Ok(r.rows_affected()),
+            Err(e) => {
+                log::error!("Error marking notifications as read: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Author of the flagged content and the question it belongs to, failing when the
+    /// content does not exist or was deleted
+    pub async fn get_flagged_content(
+        &self,
+        target: &FlagTarget,
+    ) -> Result<(Option<AccountId>, QuestionId), Error> {
+        let mut tx = self.begin().await?;
+        let content = flagged_content(&mut tx, target).await?;
+        commit(tx).await?;
+        Ok(content)
+    }
+
+    /// Records the flag and hides the content once it has `hide_threshold` open flags
+    pub async fn add_flag(
+        &self,
+        target: FlagTarget,
+        flag: FlagDraft,
+        account_id: AccountId,
+        hide_threshold: i64,
+    ) -> Result<Flag, Error> {
+        let mut tx = self.begin().await?;
+        flagged_content(&mut tx, &target).await?;
+        let query = format!(
+            "INSERT INTO flags (account_id, {}, reason, severity, note) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+            target.column()
+        );
+        let flag = match sqlx::query(&query)
+            .bind(account_id.0)
+            .bind(target.id())
+            .bind(flag.reason.as_str())
+            .bind(flag.reason.severity())
+            .bind(flag.note)
+            .map(|row: PgRow| flag_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(flag) => flag,
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                return Err(Error::AlreadyFlagged)
+            }
+            Err(e) => return Err(database_error("adding flag", e)),
+        };
+        let query = format!(
+            "UPDATE {} SET hidden = TRUE WHERE id = $1 AND NOT hidden AND (SELECT COUNT(*) FROM flags WHERE {} = $1 AND status = 'open') >= $2",
+            target.table(),
+            target.column()
+        );
+        let hidden = sqlx::query(&query)
+            .bind(target.id())
+            .bind(hide_threshold)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("hiding flagged content", e))?;
+        if hidden.rows_affected() > 0 {
+            log::info!("Hid {} {} after {} flags", target.table(), target.id(), hide_threshold);
+        }
+        commit(tx).await?;
+        match &target {
+            FlagTarget::Question(question_id) => {
+                self.changed(Some(Invalidation::Question(question_id.clone())), None).await
+            }
+            _ if hidden.rows_affected() > 0 => {
+                self.changed(Some(Invalidation::Validators), None).await
+            }
+            _ => {}
+        }
+        Ok(flag)
+    }
+
+    /// Content with open flags, the most severe and most flagged first
+    pub async fn get_moderation_queue(
+        &self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<QueueItem>, Error> {
+        sqlx::query("SELECT f.question_id, f.answer_id, f.comment_id, COUNT(*) AS flags, MAX(f.severity) AS severity, array_agg(DISTINCT f.reason) AS reasons, MIN(f.created_on) AS first_flagged_on, COALESCE(q.content, a.content, c.content) AS content, COALESCE(q.account_id, a.account_id, c.account_id) AS author_id, COALESCE(q.hidden, a.hidden, c.hidden) AS hidden FROM flags f LEFT JOIN questions q ON q.id = f.question_id LEFT JOIN answers a ON a.id = f.answer_id LEFT JOIN comments c ON c.id = f.comment_id WHERE f.status = 'open' AND COALESCE(q.deleted_at, a.deleted_at, c.deleted_at) IS NULL GROUP BY f.question_id, f.answer_id, f.comment_id, q.id, a.id, c.id ORDER BY severity DESC, flags DESC, first_flagged_on LIMIT $1 OFFSET $2")
+            .bind(limit.unwrap_or(10) as i32)
+            .bind(offset.unwrap_or(0) as i32)
+            .try_map(|row: PgRow| {
+                let target = flag_target_from_row(&row)
+                    .ok_or_else(|| sqlx::Error::ColumnNotFound("question_id".to_string()))?;
+                Ok(QueueItem {
+                    target,
+                    content: row.get("content"),
+                    author_id: row.get::<Option<i32>, _>("author_id").map(AccountId),
+                    hidden: row.get("hidden"),
+                    flags: row.get("flags"),
+                    severity: row.get("severity"),
+                    reasons: row
+                        .get::<Vec<String>, _>("reasons")
+                        .iter()
+                        .filter_map(|reason| reason.parse().ok())
+                        .collect(),
+                    first_flagged_on: row.get("first_flagged_on"),
+                })
+            })
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting moderation queue", e))
+    }
+
+    /// Carries out the moderator's decision and closes the open flags on the content in one
+    /// transaction, so that the flags stay open when the action fails. The content is shown
+    /// again unless it was deleted, its author warned if asked to and the decision logged.
+    /// Edited content must already be validated.
+    pub async fn moderate(
+        &self,
+        target: &FlagTarget,
+        decision: ModerationDecision,
+        moderator_id: AccountId,
+        author_id: Option<AccountId>,
+        question_id: &QuestionId,
+    ) -> Result<ModerationLogEntry, Error> {
+        let action = decision.action;
+        let content = decision.content.unwrap_or_default();
+        let content_html = match (action, target) {
+            (ModerationAction::Edit, FlagTarget::Question(_) | FlagTarget::Answer(_)) => {
+                markdown::render_blocking(content.clone()).await
+            }
+            _ => String::new(),
+        };
+        let mut tx = self.begin().await?;
+        let query = format!(
+            "UPDATE flags SET status = $2, resolved_on = NOW() WHERE {} = $1 AND status = 'open'",
+            target.column()
+        );
+        let resolved = sqlx::query(&query)
+            .bind(target.id())
+            .bind(action.flag_status().as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("resolving flags", e))?
+            .rows_affected();
+        if resolved == 0 {
+            return Err(Error::FlagNotFound);
+        }
+        let moderated = match (action, target) {
+            (ModerationAction::Delete, FlagTarget::Question(id)) => {
+                let tags = soft_delete_question(&mut tx, id, moderator_id).await?;
+                Moderated::QuestionDeleted(id.clone(), tags)
+            }
+            (ModerationAction::Delete, FlagTarget::Answer(id)) => {
+                let question_id = soft_delete_answer(&mut tx, id, moderator_id).await?;
+                Moderated::AnswerDeleted(question_id, id.clone())
+            }
+            (ModerationAction::Delete, FlagTarget::Comment(id)) => {
+                soft_delete_comment(&mut tx, id, moderator_id).await?;
+                Moderated::Unchanged
+            }
+            (ModerationAction::Edit, FlagTarget::Question(id)) => {
+                let question = match sqlx::query("UPDATE questions SET content = $1, content_html = $2 WHERE id = $3 AND deleted_at IS NULL RETURNING *")
+                    .bind(content)
+                    .bind(content_html)
+                    .bind(id.0)
+                    .map(|row: PgRow| question_from_row(&row))
+                    .fetch_one(&mut *tx)
+                    .await {
+                    Ok(question) => question,
+                    Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+                    Err(e) => return Err(database_error("editing flagged question", e)),
+                };
+                add_question_revision(&mut tx, &question, moderator_id).await?;
+                Moderated::Question(question)
+            }
+            (ModerationAction::Edit, FlagTarget::Answer(id)) => {
+                let answer = write_answer(&mut tx, id, content, &content_html, moderator_id).await?;
+                Moderated::Answer(answer)
+            }
+            (ModerationAction::Edit, FlagTarget::Comment(id)) => {
+                write_comment(&mut tx, id, content).await?;
+                Moderated::Unchanged
+            }
+            (ModerationAction::Dismiss, _) | (ModerationAction::Warn, _) => Moderated::Unchanged,
+        };
+        if action != ModerationAction::Delete {
+            let query = format!("UPDATE {} SET hidden = FALSE WHERE id = $1", target.table());
+            sqlx::query(&query)
+                .bind(target.id())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| database_error("showing moderated content", e))?;
+        }
+        let (answer_id, comment_id) = match target {
+            FlagTarget::Question(_) => (None, None),
+            FlagTarget::Answer(id) => (Some(id.0), None),
+            FlagTarget::Comment(id) => (None, Some(id.0)),
+        };
+        if let (ModerationAction::Warn, Some(author_id)) = (action, author_id) {
+            sqlx::query("INSERT INTO notifications (account_id, kind, actor_id, question_id, answer_id, comment_id) VALUES ($1, $2, $3, $4, $5, $6)")
+                .bind(author_id.0)
+                .bind(NotificationKind::Warning.as_str())
+                .bind(moderator_id.0)
+                .bind(question_id.0)
+                .bind(answer_id)
+                .bind(comment_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| database_error("warning author", e))?;
+        }
+        let query = format!(
+            "INSERT INTO moderation_log (moderator_id, author_id, {}, action, note, flags_resolved) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            target.column()
+        );
+        let entry = sqlx::query(&query)
+            .bind(moderator_id.0)
+            .bind(author_id.map(|id| id.0))
+            .bind(target.id())
+            .bind(action.as_str())
+            .bind(decision.note)
+            .bind(resolved as i32)
+            .map(|row: PgRow| moderation_log_entry_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| database_error("logging moderation decision", e))?;
+        commit(tx).await?;
+        match moderated {
+            Moderated::Question(question) => {
+                self.changed(
+                    Some(Invalidation::Question(question.id.clone())),
+                    Some(EventDraft::question(EventKind::QuestionUpdated, &question)),
+                )
+                .await
+            }
+            Moderated::QuestionDeleted(id, tags) => {
+                self.changed(
+                    Some(Invalidation::Question(id.clone())),
+                    Some(EventDraft::question_deleted(id, tags)),
+                )
+                .await
+            }
+            Moderated::Answer(answer) => {
+                let tags = self.question_tags(&answer.question_id).await;
+                let event = EventDraft::answer(EventKind::AnswerUpdated, &answer, tags);
+                self.changed(Some(Invalidation::Validators), Some(event)).await
+            }
+            Moderated::AnswerDeleted(question_id, answer_id) => {
+                let tags = self.question_tags(&question_id).await;
+                self.changed(
+                    Some(Invalidation::Pages),
+                    Some(EventDraft::answer_deleted(question_id, answer_id, tags)),
+                )
+                .await
+            }
+            Moderated::Unchanged => {
+                let invalidation = match target {
+                    FlagTarget::Question(question_id) => Invalidation::Question(question_id.clone()),
+                    _ => Invalidation::Validators,
+                };
+                self.changed(Some(invalidation), None).await;
+            }
+        }
+        Ok(entry)
+    }
+
+    /// Moderation decisions, most recent first
+    pub async fn get_moderation_log(
+        &self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<ModerationLogEntry>, Error> {
+        sqlx::query("SELECT * FROM moderation_log ORDER BY id DESC LIMIT $1 OFFSET $2")
+            .bind(limit.unwrap_or(10) as i32)
+            .bind(offset.unwrap_or(0) as i32)
+            .map(|row: PgRow| moderation_log_entry_from_row(&row))
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting moderation log", e))
+    }
+
+    /// Email preferences of the account, the defaults until it changes them
+    pub async fn get_email_preferences(
+        &self,
+        account_id: AccountId,
+    ) -> Result<EmailPreferences, Error> {
+        match sqlx::query("SELECT * FROM email_preferences WHERE account_id = $1")
+            .bind(account_id.0)
+            .map(|row: PgRow| email_preferences_from_row(&row))
+            .fetch_optional(&self.connection)
+            .await {
+            Ok(preferences) => Ok(preferences.unwrap_or_default()),
+            Err(e) => Err(database_error("getting email preferences", e)),
+        }
+    }
+
+    pub async fn update_email_preferences(
+        &self,
+        account_id: AccountId,
+        preferences: EmailPreferences,
+    ) -> Result<EmailPreferences, Error> {
+        sqlx::query("INSERT INTO email_preferences (account_id, new_answers, digest) VALUES ($1, $2, $3) ON CONFLICT (account_id) DO UPDATE SET new_answers = $2, digest = $3 RETURNING *")
+            .bind(account_id.0)
+            .bind(preferences.new_answers)
+            .bind(preferences.digest.as_str())
+            .map(|row: PgRow| email_preferences_from_row(&row))
+            .fetch_one(&self.connection)
+            .await
+            .map_err(|e| database_error("updating email preferences", e))
+    }
+
+    /// Accounts whose daily or weekly digest is due, with the time of their last digest
+    pub async fn get_digest_recipients(
+        &self,
+    ) -> Result<Vec<(Account, Option<NaiveDateTime>)>, Error> {
+        sqlx::query("SELECT a.*, p.last_digest_on FROM email_preferences p JOIN accounts a ON a.id = p.account_id WHERE p.digest <> 'off' AND (p.last_digest_on IS NULL OR p.last_digest_on <= NOW() - CASE p.digest WHEN 'daily' THEN INTERVAL '1 day' ELSE INTERVAL '7 days' END)")
+            .map(|row: PgRow| (account_from_row(&row), row.get("last_digest_on")))
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting digest recipients", e))
+    }
+
+    /// Unread notifications of the account created after its last digest, oldest first
+    pub async fn get_digest_items(
+        &self,
+        account_id: AccountId,
+        since: Option<NaiveDateTime>,
+    ) -> Result<Vec<DigestItem>, Error> {
+        sqlx::query("SELECT n.kind, n.question_id, q.title FROM notifications n JOIN questions q ON q.id = n.question_id WHERE n.account_id = $1 AND n.read_on IS NULL AND ($2::TIMESTAMP IS NULL OR n.created_on > $2) ORDER BY n.id")
+            .bind(account_id.0)
+            .bind(since)
+            .map(|row: PgRow| {
+                Some(DigestItem {
+                    kind: notification_kind(&row)?,
+                    question_id: QuestionId(row.get("question_id")),
+                    title: row.get("title"),
+                })
+            })
+            .fetch_all(&self.connection)
+            .await
+            .map(|items| items.into_iter().flatten().collect())
+            .map_err(|e| database_error("getting digest items", e))
+    }
+
+    pub async fn mark_digest_sent(&self, account_id: AccountId) -> Result<(), Error> {
+        sqlx::query("UPDATE email_preferences SET last_digest_on = NOW() WHERE account_id = $1")
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await
+            .map(|_| ())
+            .map_err(|e| database_error("marking digest as sent", e))
+    }
+
+    pub async fn add_webhook(
+        &self,
+        webhook: WebhookDraft,
+        secret: &str,
+        account_id: AccountId,
+    ) -> Result<Webhook, Error> {
+        let events: Vec<&str> = webhook.events.iter().map(|event| event.as_str()).collect();
+        match sqlx::query("INSERT INTO webhooks (url, secret, events, created_by) VALUES ($1, $2, $3, $4) RETURNING *")
+            .bind(webhook.url)
+            .bind(secret)
+            .bind(events)
+            .bind(account_id.0)
+            .map(|row: PgRow| webhook_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(webhook) => Ok(webhook),
+            Err(e) => {
+                log::error!("Error adding webhook: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_webhooks(&self) -> Result<Vec<Webhook>, Error> {
+        match sqlx::query("SELECT * FROM webhooks ORDER BY id")
+            .map(|row: PgRow| webhook_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(webhooks) => Ok(webhooks),
+            Err(e) => {
+                log::error!("Error getting webhooks: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_webhook(&self, id: WebhookId) -> Result<Webhook, Error> {
+        match sqlx::query("SELECT * FROM webhooks WHERE id = $1")
+            .bind(id.0)
+            .map(|row: PgRow| webhook_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(webhook) => Ok(webhook),
+            Err(sqlx::Error::RowNotFound) => Err(Error::WebhookNotFound),
+            Err(e) => {
+                log::error!("Error getting webhook: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Removes the webhook together with its delivery log
+    pub async fn delete_webhook(&self, id: WebhookId) -> Result<bool, Error> {
+        match sqlx::query("DELETE FROM webhooks WHERE id = $1")
+            .bind(id.0)
+            .execute(&self.connection)
+            .await {
+            Ok(r) if r.rows_affected() == 0 => Err(Error::WebhookNotFound),
+            Ok(_) => Ok(true),
+            Err(e) => {
+                log::error!("Error deleting webhook: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Lists the deliveries of a webhook, most recent first
+    pub async fn get_webhook_deliveries(
+        &self,
+        id: WebhookId,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<WebhookDelivery>, Error> {
+        match sqlx::query("SELECT * FROM webhook_deliveries WHERE webhook_id = $1 ORDER BY id DESC LIMIT $2 OFFSET $3")
+            .bind(id.0)
+            .bind(limit.unwrap_or(10) as i32)
+            .bind(offset.unwrap_or(0) as i32)
+            .map(|row: PgRow| webhook_delivery_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(deliveries) => Ok(deliveries),
+            Err(e) => {
+                log::error!("Error getting webhook deliveries: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Claims up to `limit` pending deliveries that are due, postponing their next attempt
+    /// by `lease` so that no other worker sends them while they are in flight
+    pub async fn claim_webhook_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<PendingDelivery>, Error> {
+        match sqlx::query("WITH claimed AS (UPDATE webhook_deliveries SET next_attempt_at = NOW() + make_interval(secs => $2) WHERE id IN (SELECT id FROM webhook_deliveries WHERE status = 'pending' AND next_attempt_at <= NOW() ORDER BY next_attempt_at LIMIT $1 FOR UPDATE SKIP LOCKED) RETURNING *) SELECT c.id, c.event, c.payload, c.attempts, w.url, w.secret FROM claimed c JOIN webhooks w ON w.id = c.webhook_id WHERE w.active")
+            .bind(limit)
+            .bind(lease.as_secs_f64())
+            .map(|row: PgRow| PendingDelivery {
+                id: DeliveryId(row.get("id")),
+                event: row.get::<String, _>("event").parse().unwrap_or(WebhookEvent::QuestionCreated),
+                payload: row.get("payload"),
+                attempts: row.get("attempts"),
+                url: row.get("url"),
+                secret: row.get("secret"),
+            })
+            .fetch_all(&self.connection)
+            .await {
+            Ok(deliveries) => Ok(deliveries),
+            Err(e) => {
+                log::error!("Error claiming webhook deliveries: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Records the outcome of an attempt, scheduling a retry with exponential backoff or
+    /// dead-lettering the delivery once it ran out of attempts
+    pub async fn record_webhook_delivery(
+        &self,
+        delivery: &PendingDelivery,
+        outcome: &DeliveryOutcome,
+    ) -> Result<DeliveryStatus, Error> {
+        let attempts = delivery.attempts + 1;
+        let (status, status_code, error) = match outcome {
+            DeliveryOutcome::Delivered { status_code } => (DeliveryStatus::Delivered, Some(*status_code), None),
+            DeliveryOutcome::Failed { status_code, error } if attempts >= MAX_DELIVERY_ATTEMPTS => {
+                (DeliveryStatus::Dead, *status_code, Some(error))
+            }
+            DeliveryOutcome::Failed { status_code, error } => (DeliveryStatus::Pending, *status_code, Some(error)),
+        };
+        match sqlx::query("UPDATE webhook_deliveries SET status = $2, attempts = $3, last_status_code = $4, last_error = $5, next_attempt_at = NOW() + make_interval(secs => $6), delivered_on = CASE WHEN $2 = 'delivered' THEN NOW() END WHERE id = $1")
+            .bind(delivery.id.0)
+            .bind(status.as_str())
+            .bind(attempts)
+            .bind(status_code.map(i32::from))
+            .bind(error)
+            .bind(retry_delay(attempts).as_secs_f64())
+            .execute(&self.connection)
+            .await {
+            Ok(_) => Ok(status),
+            Err(e) => {
+                log::error!("Error recording webhook delivery: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_question_revisions(
+        &self,
+        id: QuestionId,
+    ) -> Result<Vec<QuestionRevision>, Error> {
+        match sqlx::query("SELECT r.* FROM question_revisions r JOIN questions q ON q.id = r.question_id WHERE r.question_id = $1 AND q.deleted_at IS NULL ORDER BY r.revision")
+            .bind(id.0)
+            .map(|row: PgRow| question_revision_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(revisions) => Ok(revisions),
+            Err(e) => {
+                log::error!("Error getting question revisions: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_question_revision(
+        &self,
+        id: QuestionId,
+        revision: i32,
+    ) -> Result<QuestionRevision, Error> {
+        match sqlx::query("SELECT r.* FROM question_revisions r JOIN questions q ON q.id = r.question_id WHERE r.question_id = $1 AND r.revision = $2 AND q.deleted_at IS NULL")
+            .bind(id.0)
+            .bind(revision)
+            .map(|row: PgRow| question_revision_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(revision) => Ok(revision),
+            Err(sqlx::Error::RowNotFound) => Err(Error::RevisionNotFound),
+            Err(e) => {
+                log::error!("Error getting question revision: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_answer_revisions(
+        &self,
+        id: AnswerId,
+    ) -> Result<Vec<AnswerRevision>, Error> {
+        match sqlx::query("SELECT r.* FROM answer_revisions r JOIN answers a ON a.id = r.answer_id WHERE r.answer_id = $1 AND a.deleted_at IS NULL ORDER BY r.revision")
+            .bind(id.0)
+            .map(|row: PgRow| answer_revision_from_row(&row))
+            .fetch_all(&self.connection)
+            .await {
+            Ok(revisions) => Ok(revisions),
+            Err(e) => {
+                log::error!("Error getting answer revisions: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_answer_revision(
+        &self,
+        id: AnswerId,
+        revision: i32,
+    ) -> Result<AnswerRevision, Error> {
+        match sqlx::query("SELECT r.* FROM answer_revisions r JOIN answers a ON a.id = r.answer_id WHERE r.answer_id = $1 AND r.revision = $2 AND a.deleted_at IS NULL")
+            .bind(id.0)
+            .bind(revision)
+            .map(|row: PgRow| answer_revision_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(revision) => Ok(revision),
+            Err(sqlx::Error::RowNotFound) => Err(Error::RevisionNotFound),
+            Err(e) => {
+                log::error!("Error getting answer revision: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Adds an account whose password has already been hashed
+    pub async fn add_account(&self, account: AccountDraft) -> Result<Account, Error> {
+        match sqlx::query("INSERT INTO accounts (email, password) VALUES ($1, $2) RETURNING *")
+            .bind(account.email)
+            .bind(account.password)
+            .map(|row: PgRow| account_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(account) => Ok(account),
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                Err(Error::AccountAlreadyExists)
+            }
+            Err(e) => {
+                log::error!("Error adding account: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Returns the account registered with the e-mail together with its password hash
+    pub async fn get_account_by_email(&self, email: &str) -> Result<(Account, String), Error> {
+        match sqlx::query("SELECT * FROM accounts WHERE email = $1")
+            .bind(email)
+            .map(|row: PgRow| (account_from_row(&row), row.get("password")))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(account) => Ok(account),
+            Err(sqlx::Error::RowNotFound) => Err(Error::WrongPassword),
+            Err(e) => {
+                log::error!("Error getting account: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    pub async fn get_account(&self, id: AccountId) -> Result<Account, Error> {
+        match sqlx::query("SELECT * FROM accounts WHERE id = $1")
+            .bind(id.0)
+            .map(|row: PgRow| account_from_row(&row))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(account) => Ok(account),
+            Err(sqlx::Error::RowNotFound) => Err(Error::AccountNotFound),
+            Err(e) => Err(database_error("getting account", e)),
+        }
+    }
+
+    /// Saves the hash of a reset token, the token itself only being sent by e-mail
+    pub async fn add_password_reset(
+        &self,
+        account_id: AccountId,
+        token: &str,
+        expires_on: NaiveDateTime,
+    ) -> Result<(), Error> {
+        sqlx::query("INSERT INTO password_resets (token, account_id, expires_on) VALUES ($1, $2, $3)")
+            .bind(hash_token(token))
+            .bind(account_id.0)
+            .bind(expires_on)
+            .execute(&self.connection)
+            .await
+            .map(|_| ())
+            .map_err(|e| database_error("adding password reset", e))
+    }
+
+    /// Uses up a valid reset token to replace the password with the given hash, ending
+    /// all sessions of the account
+    pub async fn reset_password(&self, token: &str, password: String) -> Result<(), Error> {
+        let mut tx = self.begin().await?;
+        let account_id: i32 = match sqlx::query("UPDATE password_resets SET used_on = NOW() WHERE token = $1 AND used_on IS NULL AND expires_on > $2 RETURNING account_id")
+            .bind(hash_token(token))
+            .bind(Utc::now().naive_utc())
+            .map(|row: PgRow| row.get("account_id"))
+            .fetch_one(&mut *tx)
+            .await {
+            Ok(account_id) => account_id,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::InvalidResetToken),
+            Err(e) => return Err(database_error("using password reset", e)),
+        };
+        sqlx::query("UPDATE accounts SET password = $1 WHERE id = $2")
+            .bind(password)
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("updating password", e))?;
+        sqlx::query("DELETE FROM sessions WHERE account_id = $1")
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database_error("deleting sessions", e))?;
+        commit(tx).await
+    }
+
+    /// Saves the hash of a session token, the token itself only being sent to the client
+    pub async fn add_session(
+        &self,
+        account: &Account,
+        token: String,
+        expires_on: NaiveDateTime,
+    ) -> Result<Session, Error> {
+        match sqlx::query("INSERT INTO sessions (token, account_id, expires_on) VALUES ($1, $2, $3)")
+            .bind(hash_token(&token))
+            .bind(account.id.0)
+            .bind(expires_on)
+            .execute(&self.connection)
+            .await {
+            Ok(_) => Ok(Session {
+                token,
+                account_id: account.id,
+                role: account.role,
+                expires_on,
+            }),
+            Err(e) => {
+                log::error!("Error adding session: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Resolves a session token, failing with `Unauthorized` when unknown or expired
+    pub async fn get_session(&self, token: &str) -> Result<Session, Error> {
+        match sqlx::query("SELECT s.account_id, s.expires_on, a.role FROM sessions s JOIN accounts a ON a.id = s.account_id WHERE s.token = $1 AND s.expires_on > $2")
+            .bind(hash_token(token))
+            .bind(Utc::now().naive_utc())
+            .map(|row: PgRow| Session {
+                token: token.to_string(),
+                account_id: AccountId(row.get("account_id")),
+                role: row.get::<String, _>("role").parse().unwrap_or(Role::User),
+                expires_on: row.get("expires_on"),
+            })
+            .fetch_one(&self.connection)
+            .await {
+            Ok(session) => Ok(session),
+            Err(sqlx::Error::RowNotFound) => Err(Error::Unauthorized),
+            Err(e) => {
+                log::error!("Error getting session: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Current reputation total of the account
+    pub async fn get_reputation_points(&self, account_id: AccountId) -> Result<i32, Error> {
+        match sqlx::query("SELECT reputation FROM accounts WHERE id = $1")
+            .bind(account_id.0)
+            .map(|row: PgRow| row.get("reputation"))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(reputation) => Ok(reputation),
+            Err(sqlx::Error::RowNotFound) => Err(Error::AccountNotFound),
+            Err(e) => {
+                log::error!("Error getting reputation: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Reputation total together with its breakdown by reason and the latest ledger entries
+    pub async fn get_reputation(&self, account_id: AccountId) -> Result<Reputation, Error> {
+        let reputation = self.get_reputation_points(account_id).await?;
+        let breakdown = sqlx::query("SELECT reason, SUM(points)::INTEGER AS points, COUNT(*) AS events FROM reputation_events WHERE account_id = $1 GROUP BY reason ORDER BY reason")
+            .bind(account_id.0)
+            .map(|row: PgRow| {
+                Some(ReputationBreakdown {
+                    reason: reputation_reason(&row)?,
+                    points: row.get("points"),
+                    events: row.get("events"),
+                })
+            })
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting reputation breakdown", e))?
+            .into_iter()
+            .flatten()
+            .collect();
+        let recent_events = sqlx::query("SELECT * FROM reputation_events WHERE account_id = $1 ORDER BY id DESC LIMIT 20")
+            .bind(account_id.0)
+            .map(|row: PgRow| {
+                Some(ReputationEvent {
+                    account_id: AccountId(row.get("account_id")),
+                    reason: reputation_reason(&row)?,
+                    points: row.get("points"),
+                    question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+                    answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+                    created_on: row.get("created_on"),
+                })
+            })
+            .fetch_all(&self.connection)
+            .await
+            .map_err(|e| database_error("getting reputation events", e))?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(Reputation {
+            account_id,
+            reputation,
+            breakdown,
+            recent_events,
+        })
+    }
+
+    /// Rebuilds the account's reputation total from its ledger
+    pub async fn recompute_reputation(&self, account_id: AccountId) -> Result<i32, Error> {
+        match sqlx::query("UPDATE accounts SET reputation = (SELECT COALESCE(SUM(points), 0) FROM reputation_events WHERE account_id = $1) WHERE id = $1 RETURNING reputation")
+            .bind(account_id.0)
+            .map(|row: PgRow| row.get("reputation"))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(reputation) => Ok(reputation),
+            Err(sqlx::Error::RowNotFound) => Err(Error::AccountNotFound),
+            Err(e) => {
+                log::error!("Error recomputing reputation: {}", e);
+                Err(Error::DatabaseQueryError)
+            }
+        }
+    }
+
+    /// Tags of a question for events about its answers. Events are published after the
+    /// change is committed, so a failure here only leaves the event without tags.
+    async fn question_tags(&self, id: &QuestionId) -> Option<Vec<String>> {
+        match sqlx::query("SELECT tags FROM questions WHERE id = $1")
+            .bind(id.0)
+            .map(|row: PgRow| row.get("tags"))
+            .fetch_one(&self.connection)
+            .await {
+            Ok(tags) => tags,
+            Err(e) => {
+                log::error!("Error getting question tags: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Drops the cached reads outdated by a committed write, publishes its event and sends
+    /// both to the other instances with `NOTIFY`. The write stands even if sending fails.
+    async fn changed(&self, invalidation: Option<Invalidation>, event: Option<EventDraft>) {
+        if let Some(invalidation) = &invalidation {
+            self.cache.invalidate(invalidation);
+        }
+        let change = Change {
+            origin: self.instance_id.clone(),
+            invalidation,
+            event: event.as_ref().map(ChangeEvent::from),
+        };
+        if let Some(event) = event {
+            self.events.publish(event);
+        }
+        let payload = match serde_json::to_string(&change) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::error!("Error serializing change: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(CHANNEL)
+            .bind(payload)
+            .execute(&self.connection)
+            .await {
+            log::error!("Error notifying change: {}", e);
+        }
+    }
+
+    /// Renders the HTML of the questions and answers saved before it was stored with them,
+    /// a batch at a time. Content edited meanwhile is left to the edit.
+    pub async fn render_missing_html(&self) -> Result<u64, Error> {
+        let mut rendered = 0;
+        for table in ["questions", "answers"] {
+            let select = format!(
+                "SELECT id, content FROM {} WHERE content_html IS NULL LIMIT 100",
+                table
+            );
+            let update = format!(
+                "UPDATE {} SET content_html = $1 WHERE id = $2 AND content = $3 AND content_html IS NULL",
+                table
+            );
+            loop {
+                let rows: Vec<(i32, String)> = sqlx::query(&select)
+                    .map(|row: PgRow| (row.get("id"), row.get("content")))
+                    .fetch_all(&self.connection)
+                    .await
+                    .map_err(|e| database_error("getting content to render", e))?;
+                if rows.is_empty() {
+                    break;
+                }
+                for (id, content) in rows {
+                    let content_html = markdown::render_blocking(content.clone()).await;
+                    rendered += sqlx::query(&update)
+                        .bind(content_html)
+                        .bind(id)
+                        .bind(content)
+                        .execute(&self.connection)
+                        .await
+                        .map_err(|e| database_error("saving rendered content", e))?
+                        .rows_affected();
+                }
+            }
+        }
+        Ok(rendered)
+    }
+
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>, Error> {
+        self.connection
+            .begin()
+            .await
+            .map_err(|e| database_error("starting transaction", e))
+    }
+}
+
+async fn commit(tx: Transaction<'_, Postgres>) -> Result<(), Error> {
+    tx.commit()
+        .await
+        .map_err(|e| database_error("committing transaction", e))
+}
+
+/// Content changed by a moderation decision, announced once the decision is committed
+enum Moderated {
+    Question(Question),
+    QuestionDeleted(QuestionId, Option<Vec<String>>),
+    Answer(Answer),
+    AnswerDeleted(QuestionId, AnswerId),
+    Unchanged,
+}
+
+/// Question with the same title and content as the draft, ignoring case and whitespace.
+/// Drafts with the same title are checked one at a time under an advisory lock on the title,
+/// held until the transaction ends, so that two of them cannot both be added.
+async fn find_duplicate_question(
+    tx: &mut Transaction<'_, Postgres>,
+    question: &QuestionDraft,
+) -> Result<Option<QuestionId>, Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext(lower(regexp_replace(btrim($1), '\\s+', ' ', 'g'))))")
+        .bind(&question.title)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("locking question title", e))?;
+    sqlx::query("SELECT id FROM questions WHERE deleted_at IS NULL AND lower(regexp_replace(btrim(title), '\\s+', ' ', 'g')) = lower(regexp_replace(btrim($1), '\\s+', ' ', 'g')) AND lower(regexp_replace(btrim(content), '\\s+', ' ', 'g')) = lower(regexp_replace(btrim($2), '\\s+', ' ', 'g')) LIMIT 1")
+        .bind(&question.title)
+        .bind(&question.content)
+        .map(|row: PgRow| QuestionId(row.get("id")))
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| database_error("finding duplicate question", e))
+}
+
+/// Overwrites the question and records the new content as its latest revision
+async fn write_question(
+    tx: &mut Transaction<'_, Postgres>,
+    question: &Question,
+    content_html: &str,
+    account_id: AccountId,
+) -> Result<Question, Error> {
+    let question = match sqlx::query("UPDATE questions SET title = $1, content = $2, content_html = $3, tags = $4 WHERE id = $5 AND deleted_at IS NULL RETURNING *")
+        .bind(&question.title)
+        .bind(&question.content)
+        .bind(content_html)
+        .bind(&question.tags)
+        .bind(question.id.0)
+        .map(|row: PgRow| question_from_row(&row))
+        .fetch_one(&mut **tx)
+        .await {
+        Ok(question) => question,
+        Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+        Err(e) => return Err(database_error("updating question", e)),
+    };
+    add_question_revision(tx, &question, account_id).await?;
+    Ok(question)
+}
+
+/// Overwrites the answer content and records it as the answer's latest revision
+async fn write_answer(
+    tx: &mut Transaction<'_, Postgres>,
+    id: &AnswerId,
+    content: String,
+    content_html: &str,
+    account_id: AccountId,
+) -> Result<Answer, Error> {
+    let answer = match sqlx::query("UPDATE answers SET content = $1, content_html = $2 WHERE id = $3 AND deleted_at IS NULL RETURNING *")
+        .bind(content)
+        .bind(content_html)
+        .bind(id.0)
+        .map(|row: PgRow| answer_from_row(&row))
+        .fetch_one(&mut **tx)
+        .await {
+        Ok(answer) => answer,
+        Err(sqlx::Error::RowNotFound) => return Err(Error::AnswerNotFound),
+        Err(e) => return Err(database_error("updating answer", e)),
+    };
+    add_answer_revision(tx, &answer, account_id).await?;
+    Ok(answer)
+}
+
+async fn write_comment(
+    tx: &mut Transaction<'_, Postgres>,
+    id: &CommentId,
+    content: String,
+) -> Result<Comment, Error> {
+    match sqlx::query("UPDATE comments SET content = $2, updated_on = NOW() WHERE id = $1 AND deleted_at IS NULL RETURNING *")
+        .bind(id.0)
+        .bind(content)
+        .map(|row: PgRow| comment_from_row(&row))
+        .fetch_one(&mut **tx)
+        .await {
+        Ok(comment) => Ok(comment),
+        Err(sqlx::Error::RowNotFound) => Err(Error::CommentNotFound),
+        Err(e) => Err(database_error("updating comment", e)),
+    }
+}
+
+/// Soft deletes the question together with its answers, returning its tags
+async fn soft_delete_question(
+    tx: &mut Transaction<'_, Postgres>,
+    id: &QuestionId,
+    account_id: AccountId,
+) -> Result<Option<Vec<String>>, Error> {
+    let tags = match sqlx::query("UPDATE questions SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL RETURNING tags")
+        .bind(id.0)
+        .bind(account_id.0)
+        .map(|row: PgRow| row.get::<Option<Vec<String>>, _>("tags"))
+        .fetch_one(&mut **tx)
+        .await {
+        Ok(tags) => tags,
+        Err(sqlx::Error::RowNotFound) => return Err(Error::QuestionNotFound),
+        Err(e) => return Err(database_error("deleting question", e)),
+    };
+    // NOW() is fixed for the whole transaction, so cascaded answers share the
+    // question's deletion time and can be told apart when restoring
+    sqlx::query("UPDATE answers SET deleted_at = NOW(), deleted_by = $2 WHERE question_id = $1 AND deleted_at IS NULL")
+        .bind(id.0)
+        .bind(account_id.0)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("deleting question answers", e))?;
+    dismiss_flags(tx, "question_id = $1 OR answer_id IN (SELECT id FROM answers WHERE question_id = $1)", id.0)
+        .await?;
+    Ok(tags)
+}
+
+/// Soft deletes the answer, returning the question it belongs to
+async fn soft_delete_answer(
+    tx: &mut Transaction<'_, Postgres>,
+    id: &AnswerId,
+    account_id: AccountId,
+) -> Result<QuestionId, Error> {
+    let question_id = match sqlx::query("UPDATE answers SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL RETURNING question_id")
+        .bind(id.0)
+        .bind(account_id.0)
+        .map(|row: PgRow| QuestionId(row.get("question_id")))
+        .fetch_one(&mut **tx)
+        .await {
+        Ok(question_id) => question_id,
+        Err(sqlx::Error::RowNotFound) => return Err(Error::AnswerNotFound),
+        Err(e) => return Err(database_error("deleting answer", e)),
+    };
+    dismiss_flags(tx, "answer_id = $1", id.0).await?;
+    Ok(question_id)
+}
+
+async fn soft_delete_comment(
+    tx: &mut Transaction<'_, Postgres>,
+    id: &CommentId,
+    account_id: AccountId,
+) -> Result<(), Error> {
+    let deleted = sqlx::query("UPDATE comments SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id.0)
+        .bind(account_id.0)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("deleting comment", e))?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::CommentNotFound);
+    }
+    dismiss_flags(tx, "comment_id = $1", id.0).await
+}
+
+/// Dismisses the open flags matching `condition` on `$1`, deleted content leaving the
+/// moderation queue
+async fn dismiss_flags(
+    tx: &mut Transaction<'_, Postgres>,
+    condition: &str,
+    id: i32,
+) -> Result<(), Error> {
+    let query = format!(
+        "UPDATE flags SET status = $2, resolved_on = NOW() WHERE status = 'open' AND ({})",
+        condition
+    );
+    sqlx::query(&query)
+        .bind(id)
+        .bind(FlagStatus::Dismissed.as_str())
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("dismissing flags", e))?;
+    Ok(())
+}
+
+/// Records the question as its next revision, numbered under a lock on the question so that
+/// concurrent edits cannot take the same number
+async fn add_question_revision(
+    tx: &mut Transaction<'_, Postgres>,
+    question: &Question,
+    account_id: AccountId,
+) -> Result<(), Error> {
+    sqlx::query("SELECT id FROM questions WHERE id = $1 FOR UPDATE")
+        .bind(question.id.0)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("locking question", e))?;
+    sqlx::query("INSERT INTO question_revisions (question_id, revision, title, content, tags, account_id) SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3, $4, $5 FROM question_revisions WHERE question_id = $1")
+        .bind(question.id.0)
+        .bind(&question.title)
+        .bind(&question.content)
+        .bind(&question.tags)
+        .bind(account_id.0)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("adding question revision", e))?;
+    Ok(())
+}
+
+/// Records the answer as its next revision, numbered under a lock on the answer so that
+/// concurrent edits cannot take the same number
+async fn add_answer_revision(
+    tx: &mut Transaction<'_, Postgres>,
+    answer: &Answer,
+    account_id: AccountId,
+) -> Result<(), Error> {
+    sqlx::query("SELECT id FROM answers WHERE id = $1 FOR UPDATE")
+        .bind(answer.id.0)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("locking answer", e))?;
+    sqlx::query("INSERT INTO answer_revisions (answer_id, revision, content, account_id) SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3 FROM answer_revisions WHERE answer_id = $1")
+        .bind(answer.id.0)
+        .bind(&answer.content)
+        .bind(account_id.0)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("adding answer revision", e))?;
+    Ok(())
+}
+
+/// Appends an entry to the reputation ledger and applies it to the account's total
+async fn add_reputation_event(
+    tx: &mut Transaction<'_, Postgres>,
+    account_id: AccountId,
+    reason: ReputationReason,
+    points: i32,
+    question_id: Option<&QuestionId>,
+    answer_id: Option<&AnswerId>,
+) -> Result<(), Error> {
+    sqlx::query("INSERT INTO reputation_events (account_id, reason, points, question_id, answer_id) VALUES ($1, $2, $3, $4, $5)")
+        .bind(account_id.0)
+        .bind(reason.as_str())
+        .bind(points)
+        .bind(question_id.map(|id| id.0))
+        .bind(answer_id.map(|id| id.0))
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("adding reputation event", e))?;
+    sqlx::query("UPDATE accounts SET reputation = reputation + $2 WHERE id = $1")
+        .bind(account_id.0)
+        .bind(points)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("updating reputation", e))?;
+    Ok(())
+}
+
+/// Notifies everyone following the question, directly or through one of its tags, of
+/// activity on it. The account causing the activity is not notified.
+async fn notify_followers(
+    tx: &mut Transaction<'_, Postgres>,
+    kind: NotificationKind,
+    actor_id: Option<AccountId>,
+    question_id: &QuestionId,
+    answer_id: Option<&AnswerId>,
+    comment_id: Option<&CommentId>,
+) -> Result<(), Error> {
+    sqlx::query("INSERT INTO notifications (account_id, kind, actor_id, question_id, answer_id, comment_id) SELECT DISTINCT s.account_id, $1, $2, q.id, $4, $5 FROM questions q JOIN subscriptions s ON s.question_id = q.id OR s.tag = ANY(q.tags) WHERE q.id = $3 AND s.account_id IS DISTINCT FROM $2")
+        .bind(kind.as_str())
+        .bind(actor_id.map(|id| id.0))
+        .bind(question_id.0)
+        .bind(answer_id.map(|id| id.0))
+        .bind(comment_id.map(|id| id.0))
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("notifying followers", e))?;
+    Ok(())
+}
+
+/// Author of the flagged content and the question it belongs to, locking the content
+async fn flagged_content(
+    tx: &mut Transaction<'_, Postgres>,
+    target: &FlagTarget,
+) -> Result<(Option<AccountId>, QuestionId), Error> {
+    let (query, not_found) = match target {
+        FlagTarget::Question(_) => (
+            "SELECT account_id, id AS question_id FROM questions WHERE id = $1 AND deleted_at IS NULL FOR SHARE",
+            Error::QuestionNotFound,
+        ),
+        FlagTarget::Answer(_) => (
+            "SELECT account_id, question_id FROM answers WHERE id = $1 AND deleted_at IS NULL FOR SHARE",
+            Error::AnswerNotFound,
+        ),
+        FlagTarget::Comment(_) => (
+            "SELECT c.account_id, COALESCE(c.question_id, a.question_id) AS question_id FROM comments c LEFT JOIN answers a ON a.id = c.answer_id WHERE c.id = $1 AND c.deleted_at IS NULL FOR SHARE OF c",
+            Error::CommentNotFound,
+        ),
+    };
+    match sqlx::query(query)
+        .bind(target.id())
+        .map(|row: PgRow| {
+            (
+                row.get::<Option<i32>, _>("account_id").map(AccountId),
+                QuestionId(row.get("question_id")),
+            )
+        })
+        .fetch_one(&mut **tx)
+        .await {
+        Ok(content) => Ok(content),
+        Err(sqlx::Error::RowNotFound) => Err(not_found),
+        Err(e) => Err(database_error("getting flagged content", e)),
+    }
+}
+
+/// Queues a delivery of the event to every active webhook registered for it. Queuing in
+/// the same transaction as the change guarantees the event is delivered only if it happened.
+async fn enqueue_webhooks<T: Serialize>(
+    tx: &mut Transaction<'_, Postgres>,
+    event: WebhookEvent,
+    data: &T,
+) -> Result<(), Error> {
+    let payload = serde_json::json!({
+        "event": event,
+        "created_on": Utc::now().naive_utc(),
+        "data": data,
+    });
+    sqlx::query("INSERT INTO webhook_deliveries (webhook_id, event, payload) SELECT id, $1, $2 FROM webhooks WHERE active AND $1 = ANY(events)")
+        .bind(event.as_str())
+        .bind(payload.to_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| database_error("queuing webhook deliveries", e))?;
+    Ok(())
+}
+
+/// Hex SHA-256 of a session or password reset token, as stored in place of the token
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn database_error(action: &str, e: sqlx::Error) -> Error {
+    log::error!("Error {}: {}", action, e);
+    Error::DatabaseQueryError
+}
+
+fn question_from_row(row: &PgRow) -> Question {
+    Question {
+        id: QuestionId(row.get("id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        content_html: row.get::<Option<String>, _>("content_html").unwrap_or_default(),
+        tags: row.get("tags"),
+        account_id: row.get::<Option<i32>, _>("account_id").map(AccountId),
+        accepted_answer_id: row.get::<Option<i32>, _>("accepted_answer_id").map(AnswerId),
+        score: row.get("score"),
+        bookmark_count: row.get("bookmark_count"),
+        status: row.get::<String, _>("status").parse().unwrap_or(QuestionStatus::Open),
+        close_reason: row
+            .get::<Option<String>, _>("close_reason")
+            .and_then(|reason| reason.parse().ok()),
+        duplicate_of: row.get::<Option<i32>, _>("duplicate_of").map(QuestionId),
+    }
+}
+
+fn answer_from_row(row: &PgRow) -> Answer {
+    Answer {
+        id: AnswerId(row.get("id")),
+        content: row.get("content"),
+        content_html: row.get::<Option<String>, _>("content_html").unwrap_or_default(),
+        question_id: QuestionId(row.get("question_id")),
+        account_id: row.get::<Option<i32>, _>("account_id").map(AccountId),
+        score: row.get("score"),
+    }
+}
+
+fn comment_from_row(row: &PgRow) -> Comment {
+    Comment {
+        id: CommentId(row.get("id")),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        content: row.get("content"),
+        account_id: row.get::<Option<i32>, _>("account_id").map(AccountId),
+        created_on: row.get("created_on"),
+        updated_on: row.get("updated_on"),
+    }
+}
+
+fn attachment_from_row(row: &PgRow) -> Attachment {
+    Attachment {
+        id: AttachmentId(row.get("id")),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        account_id: row.get::<Option<i32>, _>("account_id").map(AccountId),
+        file_name: row.get("file_name"),
+        content_type: row.get("content_type"),
+        size: row.get("size"),
+        blob_key: row.get("blob_key"),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn subscription_from_row(row: &PgRow) -> Subscription {
+    let target = match row.get::<Option<i32>, _>("question_id") {
+        Some(question_id) => SubscriptionTarget::Question(QuestionId(question_id)),
+        None => SubscriptionTarget::Tag(row.get("tag")),
+    };
+    Subscription {
+        target,
+        created_on: row.get("created_on"),
+    }
+}
+
+/// Kind of a notification row, `None` with a log entry for a kind this version does not
+/// know, so that the row is skipped rather than shown as another kind
+fn notification_kind(row: &PgRow) -> Option<NotificationKind> {
+    match row.get::<String, _>("kind").parse() {
+        Ok(kind) => Some(kind),
+        Err(e) => {
+            log::error!("Skipping notification: {}", e);
+            None
+        }
+    }
+}
+
+fn notification_from_row(row: &PgRow) -> Option<Notification> {
+    Some(Notification {
+        id: NotificationId(row.get("id")),
+        kind: notification_kind(row)?,
+        actor_id: row.get::<Option<i32>, _>("actor_id").map(AccountId),
+        question_id: QuestionId(row.get("question_id")),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        comment_id: row.get::<Option<i32>, _>("comment_id").map(CommentId),
+        read_on: row.get("read_on"),
+        created_on: row.get("created_on"),
+    })
+}
+
+fn email_preferences_from_row(row: &PgRow) -> EmailPreferences {
+    EmailPreferences {
+        new_answers: row.get("new_answers"),
+        digest: row.get::<String, _>("digest").parse().unwrap_or(DigestFrequency::Off),
+    }
+}
+
+fn flag_target_from_row(row: &PgRow) -> Option<FlagTarget> {
+    FlagTarget::from_columns(
+        row.get("question_id"),
+        row.get("answer_id"),
+        row.get("comment_id"),
+    )
+}
+
+fn flag_from_row(row: &PgRow) -> Flag {
+    Flag {
+        id: FlagId(row.get("id")),
+        target: flag_target_from_row(row).unwrap_or(FlagTarget::Question(QuestionId(0))),
+        reason: row.get::<String, _>("reason").parse().unwrap_or(FlagReason::Other),
+        note: row.get("note"),
+        account_id: AccountId(row.get("account_id")),
+        status: row.get::<String, _>("status").parse().unwrap_or(FlagStatus::Open),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn moderation_log_entry_from_row(row: &PgRow) -> ModerationLogEntry {
+    ModerationLogEntry {
+        id: row.get("id"),
+        // Purged content leaves the entry without a target
+        target: flag_target_from_row(row),
+        action: row.get::<String, _>("action").parse().unwrap_or(ModerationAction::Dismiss),
+        moderator_id: AccountId(row.get("moderator_id")),
+        author_id: row.get::<Option<i32>, _>("author_id").map(AccountId),
+        note: row.get("note"),
+        flags_resolved: row.get("flags_resolved"),
+        created_on: row.get("created_on"),
+    }
+}
+
+/// Reason of a reputation ledger row, `None` with a log entry for a reason this version
+/// does not know so that the row is left out rather than failing the whole read
+fn reputation_reason(row: &PgRow) -> Option<ReputationReason> {
+    let reason = row.get::<String, _>("reason");
+    match reason.parse() {
+        Ok(reason) => Some(reason),
+        Err(e) => {
+            log::error!("Skipping reputation entry: {}", e);
+            None
+        }
+    }
+}
+
+fn webhook_from_row(row: &PgRow) -> Webhook {
+    Webhook {
+        id: WebhookId(row.get("id")),
+        url: row.get("url"),
+        events: row
+            .get::<Vec<String>, _>("events")
+            .iter()
+            .filter_map(|event| event.parse().ok())
+            .collect(),
+        active: row.get("active"),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn webhook_delivery_from_row(row: &PgRow) -> WebhookDelivery {
+    WebhookDelivery {
+        id: DeliveryId(row.get("id")),
+        webhook_id: WebhookId(row.get("webhook_id")),
+        event: row.get::<String, _>("event").parse().unwrap_or(WebhookEvent::QuestionCreated),
+        status: row.get::<String, _>("status").parse().unwrap_or(DeliveryStatus::Pending),
+        attempts: row.get("attempts"),
+        next_attempt_at: row.get("next_attempt_at"),
+        last_status_code: row.get("last_status_code"),
+        last_error: row.get("last_error"),
+        created_on: row.get("created_on"),
+        delivered_on: row.get("delivered_on"),
+    }
+}
+
+fn account_from_row(row: &PgRow) -> Account {
+    Account {
+        id: AccountId(row.get("id")),
+        email: row.get("email"),
+        role: row.get::<String, _>("role").parse().unwrap_or(Role::User),
+    }
+}
+
+fn question_revision_from_row(row: &PgRow) -> QuestionRevision {
+    QuestionRevision {
+        question_id: QuestionId(row.get("question_id")),
+        revision: row.get("revision"),
+        title: row.get("title"),
+        content: row.get("content"),
+        tags: row.get("tags"),
+        account_id: row.get::<Option<i32>, _>("account_id").map(AccountId),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn answer_revision_from_row(row: &PgRow) -> AnswerRevision {
+    AnswerRevision {
+        answer_id: AnswerId(row.get("answer_id")),
+        revision: row.get("revision"),
+        content: row.get("content"),
+        account_id: row.get::<Option<i32>, _>("account_id").map(AccountId),
+        created_on: row.get("created_on"),
+    }
 }
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveTime};
+use warp::filters::reply::WithHeaders;
+use warp::http::header::{HeaderName, HeaderValue};
+use warp::http::HeaderMap;
+use warp::{Filter, Rejection};
+
+/// Format of the dates in `Sunset`, always in GMT
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Versions of the API, each mounted under its own prefix. When a route has to change its
+/// JSON, a `V2` is added here and its handlers are built in `main.rs` from the same filters
+/// as those of `V1`, so that both versions share the store, then served next to them with
+/// `.or(version(ApiVersion::V2).and(v2))`. The routes `V2` leaves unchanged are served again
+/// from the `V1` filters, e.g. `version(ApiVersion::V2).and(get_question_v2.or(get_answers))`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    /// First path segment of the version's routes, also its name in `DEPRECATIONS`
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+        }
+    }
+}
+
+/// Filter matching the prefix of a version, the version's routes being chained after it,
+/// e.g. `version(ApiVersion::V1).and(get_questions)` serving `GET /v1/questions`
+pub fn version(version: ApiVersion) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::path(version.prefix())
+}
+
+/// When a version or route was deprecated and when it will be removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deprecation {
+    pub since: NaiveDate,
+    pub sunset: NaiveDate,
+}
+
+/// Adds the `Deprecation` (RFC 9745) and `Sunset` (RFC 8594) headers to the replies of a
+/// version or route scheduled for removal, and nothing to those of the others. Applied to a
+/// version, it overrides the headers its routes set. Rejections turned into error replies by
+/// `recover(return_error)` only get there after leaving the filters, so they carry neither.
+pub fn deprecation(deprecations: &HashMap<String, Deprecation>, name: &str) -> WithHeaders {
+    let mut headers = HeaderMap::new();
+    if let Some(deprecation) = deprecations.get(name) {
+        let since = deprecation.since.and_time(NaiveTime::MIN).and_utc();
+        let sunset = deprecation.sunset.and_time(NaiveTime::MIN);
+        let values = [
+            ("deprecation", format!("@{}", since.timestamp())),
+            ("sunset", sunset.format(HTTP_DATE).to_string()),
+        ];
+        for (header, value) in values {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(HeaderName::from_static(header), value);
+            }
+        }
+    }
+    warp::reply::with::headers(headers)
+}
+
+/// Parses `name=<deprecated since>/<sunset>` entries separated by commas, with dates as
+/// `YYYY-MM-DD`, e.g. `unversioned=2026-10-19/2027-04-19`
+pub fn parse_deprecations(deprecations: &str) -> Result<HashMap<String, Deprecation>, String> {
+    deprecations
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let invalid = || format!("Invalid deprecation entry: {}", entry);
+            let (name, dates) = entry.split_once('=').ok_or_else(invalid)?;
+            let (since, sunset) = dates.split_once('/').ok_or_else(invalid)?;
+            let since = since.trim().parse().map_err(|_| invalid())?;
+            let sunset = sunset.trim().parse().map_err(|_| invalid())?;
+            if sunset <= since {
+                return Err(format!(
+                    "Sunset of {} must come after its deprecation",
+                    name.trim()
+                ));
+            }
+            Ok((name.trim().to_string(), Deprecation { since, sunset }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_route_deprecations() {
+        let deprecations = parse_deprecations("old_questions=2026-10-19/2027-01-01").unwrap();
+        let old_questions = warp::path("old")
+            .map(|| "Old questions")
+            .with(deprecation(&deprecations, "old_questions"));
+        let questions = warp::path("questions")
+            .map(|| "Questions")
+            .with(deprecation(&deprecations, "questions"));
+        let filter = version(ApiVersion::V1)
+            .and(old_questions.or(questions))
+            .with(deprecation(&deprecations, "v1"));
+
+        let response = warp::test::request()
+            .path("/v1/old")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.headers()["sunset"], "Fri, 01 Jan 2027 00:00:00 GMT");
+
+        let response = warp::test::request()
+            .path("/v1/questions")
+            .reply(&filter)
+            .await;
+        assert!(response.headers().get("sunset").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_versions_and_deprecations() {
+        let deprecations = parse_deprecations("unversioned=2026-10-19/2027-04-19").unwrap();
+        let routes = warp::path("questions")
+            .and(warp::path::end())
+            .map(|| "Questions");
+        let filter = version(ApiVersion::V1)
+            .and(routes.with(deprecation(&deprecations, "v1")))
+            .or(routes.with(deprecation(&deprecations, "unversioned")));
+
+        let response = warp::test::request()
+            .path("/v1/questions")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert!(response.headers().get("deprecation").is_none());
+        assert!(response.headers().get("sunset").is_none());
+
+        let response = warp::test::request()
+            .path("/questions")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["deprecation"], "@1792368000");
+        assert_eq!(
+            response.headers()["sunset"],
+            "Mon, 19 Apr 2027 00:00:00 GMT"
+        );
+
+        let response = warp::test::request()
+            .path("/v1/v1/questions")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), 404);
+
+        assert!(parse_deprecations("v1=2027-04-19/2026-10-19").is_err());
+        assert!(parse_deprecations("v1=2026-10-19").is_err());
+        assert!(parse_deprecations("v1=soon/later").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::domain::webhook::{DeliveryOutcome, DeliveryStatus, PendingDelivery};
+use crate::infrastructure::store::Store;
+
+/// Header carrying the hex encoded HMAC-SHA256 of the body, keyed with the webhook secret
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// Deliveries sent per run of the worker
+const BATCH_SIZE: i64 = 20;
+/// Time a receiving endpoint has to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Periodically sends the pending webhook deliveries that are due
+pub async fn run_webhook_worker(store: Store, client: reqwest::Client, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let deliveries = match store
+            .claim_webhook_deliveries(BATCH_SIZE, REQUEST_TIMEOUT * 2)
+            .await {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                log::error!("Error claiming webhook deliveries: {}", e);
+                continue;
+            }
+        };
+        for delivery in deliveries {
+            let outcome = deliver(&client, &delivery).await;
+            match store.record_webhook_delivery(&delivery, &outcome).await {
+                Ok(DeliveryStatus::Dead) => log::warn!(
+                    "Webhook delivery {} to {} dead-lettered: {:?}",
+                    delivery.id.0,
+                    delivery.url,
+                    outcome
+                ),
+                Ok(_) => {}
+                Err(e) => log::error!("Error recording webhook delivery {}: {}", delivery.id.0, e),
+            }
+        }
+    }
+}
+
+async fn deliver(client: &reqwest::Client, delivery: &PendingDelivery) -> DeliveryOutcome {
+    let result = client
+        .post(&delivery.url)
+        .timeout(REQUEST_TIMEOUT)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Event", delivery.event.as_str())
+        .header("X-Webhook-Delivery", delivery.id.0.to_string())
+        .header(SIGNATURE_HEADER, sign(&delivery.secret, &delivery.payload))
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+    match result {
+        Ok(res) if res.status().is_success() => DeliveryOutcome::Delivered {
+            status_code: res.status().as_u16(),
+        },
+        Ok(res) => DeliveryOutcome::Failed {
+            status_code: Some(res.status().as_u16()),
+            error: format!("Endpoint answered {}", res.status()),
+        },
+        Err(e) => DeliveryOutcome::Failed {
+            status_code: None,
+            error: e.to_string(),
+        },
+    }
+}
+
+/// Signature of a payload as sent in the `X-Webhook-Signature` header: `sha256=` followed
+/// by the hex encoded HMAC
+pub fn sign(secret: &str, payload: &str) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_rfc_4231() {
+        // Test case 2 of RFC 4231
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -1,16 +1,80 @@
 #![warn(clippy::all)]
+#![recursion_limit = "256"]
 
 use std::path::Path;
+use std::sync::Arc;
 
 use sqlx::migrate::Migrator;
+use utoipa::OpenApi;
 use warp::{Filter, http::Method};
 
 use errors::return_error;
 use infrastructure::store::Store;
 
-use crate::infrastructure::router::answer::add_answer;
+use crate::infrastructure::blob_store::{BlobStore, LocalBlobStore};
+use crate::infrastructure::body_limit::{body_limit, route_limit};
+use crate::infrastructure::change_feed::run_change_listener;
+use crate::infrastructure::compression::compress;
+use crate::infrastructure::config::Config;
+use crate::infrastructure::digest::run_digest_job;
+use crate::infrastructure::events::EventBus;
+use crate::infrastructure::http_cache::http_cache;
+use crate::infrastructure::live::LiveHub;
+use crate::infrastructure::mailer::Mailer;
+use crate::infrastructure::moderation::Moderation;
+use crate::infrastructure::openapi::ApiDoc;
+use crate::infrastructure::purge::run_purge_job;
+use crate::infrastructure::rate_limit::{
+    account_rate_limit, rate_limit, run_bucket_sweeper, with_rate_limit, RateLimiter,
+};
+use crate::infrastructure::read_cache::ReadCache;
+use crate::infrastructure::versioning::{deprecation, version, ApiVersion};
+use crate::infrastructure::webhook::run_webhook_worker;
+use crate::infrastructure::router::answer::{
+    add_answer, delete_answer, get_answers, restore_answer, update_answer,
+};
+use crate::infrastructure::router::attachment::{
+    add_answer_attachment, add_question_attachment, delete_attachment, get_answer_attachments,
+    get_attachment, get_question_attachments,
+};
+use crate::infrastructure::router::authentication::{
+    auth, login, register, request_password_reset, reset_password, ws_auth,
+};
+use crate::infrastructure::router::bookmark::{add_bookmark, delete_bookmark, get_bookmarks};
+use crate::infrastructure::router::cache::get_cache_stats;
+use crate::infrastructure::router::comment::{
+    add_answer_comment, add_question_comment, delete_comment, get_answer_comments,
+    get_question_comments, update_comment,
+};
+use crate::infrastructure::router::docs::{get_docs, get_openapi};
+use crate::infrastructure::router::email::{get_email_preferences, update_email_preferences};
+use crate::infrastructure::router::event::get_events;
+use crate::infrastructure::router::flag::{
+    add_flag, get_moderation_log, get_moderation_queue, moderate,
+};
+use crate::infrastructure::router::health::health;
+use crate::infrastructure::router::live::live_question;
+use crate::infrastructure::router::notification::{
+    get_notifications, mark_all_notifications_read, mark_notification_read,
+};
+use crate::infrastructure::router::preview::preview;
 use crate::infrastructure::router::question::{
-    add_question, delete_question, get_questions, update_question,
+    accept_answer, add_question, close_question, delete_question, get_question, get_questions,
+    get_similar_questions, lock_question, reopen_question, restore_question, update_question,
+};
+use crate::infrastructure::router::reputation::{get_reputation, recompute_reputation};
+use crate::infrastructure::router::revision::{
+    get_answer_revisions, get_answer_revisions_diff, get_question_revisions,
+    get_question_revisions_diff, rollback_answer, rollback_question,
+};
+use crate::infrastructure::router::subscription::{
+    follow_question, follow_tag, get_subscriptions, unfollow_question, unfollow_tag,
+};
+use crate::infrastructure::router::vote::{
+    unvote_answer, unvote_question, vote_answer, vote_question,
+};
+use crate::infrastructure::router::webhook::{
+    add_webhook, delete_webhook, get_webhook_deliveries, get_webhooks,
 };
 
 mod domain;
@@ -22,6 +86,8 @@ async fn main() {
 
     log::info!("Starting server...");
 
+    let config = Config::from_env();
+
     let log = warp::log::custom(|info| {
         log::info!("{} {} {} - {:?} from {} with {:?}",
             info.method(),
@@ -33,7 +99,11 @@ async fn main() {
         )
     });
 
-    let store = Store::new("postgres://localhost:5432/rustwebdev")
+    let events = EventBus::new(config.event_buffer_size);
+
+    let read_cache = ReadCache::new(config.read_cache_size, config.read_cache_ttl);
+
+    let store = Store::new("postgres://localhost:5432/rustwebdev", events, read_cache)
         .await;
 
     let migrator = Migrator::new(Path::new("../migrations"))
@@ -41,13 +111,89 @@ async fn main() {
 
     migrator.run(&store.clone().connection).await.unwrap();
 
-    let store_filter = warp::any().map(move || store.clone());
+    match store.render_missing_html().await {
+        Ok(0) => {}
+        Ok(rendered) => log::info!("Rendered the HTML of {} questions and answers", rendered),
+        Err(e) => log::error!("Error rendering stored content: {}", e),
+    }
+
+    tokio::spawn(run_change_listener(store.clone()));
+
+    let blobs: Arc<dyn BlobStore> = Arc::new(LocalBlobStore::new(config.attachment_dir.clone()));
+
+    tokio::spawn(run_purge_job(
+        store.clone(),
+        blobs.clone(),
+        config.retention_days,
+        config.purge_interval,
+    ));
+    let blobs_filter = warp::any().map(move || blobs.clone());
+
+    let live_hub = LiveHub::new(
+        config.event_buffer_size,
+        config.live_heartbeat,
+        config.live_timeout,
+    );
+    tokio::spawn(live_hub.clone().forward(store.events.clone()));
+    let live_hub_filter = warp::any().map(move || live_hub.clone());
+
+    let store_filter = {
+        let store = store.clone();
+        warp::any().map(move || store.clone())
+    };
+
+    let http_client = reqwest::Client::new();
+
+    tokio::spawn(run_webhook_worker(
+        store.clone(),
+        http_client.clone(),
+        config.webhook_interval,
+    ));
+
+    let mailer = match Mailer::new(
+        config.smtp_url.as_deref(),
+        &config.mail_dir,
+        &config.mail_from,
+        config.app_url.clone(),
+    ) {
+        Ok(mailer) => mailer,
+        Err(e) => panic!("Can't set up mailer: {}", e),
+    };
+    let mailer_filter = {
+        let mailer = mailer.clone();
+        warp::any().map(move || mailer.clone())
+    };
+
+    tokio::spawn(run_digest_job(
+        store.clone(),
+        mailer,
+        config.digest_interval,
+    ));
+
+    let moderation = Moderation::new(http_client, config.bad_words_api_key.clone());
+    let moderation_filter = warp::any().map(move || moderation.clone());
+
+    let strict_duplicates = config.strict_duplicates;
+    let strict_duplicates_filter = warp::any().map(move || strict_duplicates);
+
+    let flag_hide_threshold = config.flag_hide_threshold;
+    let flag_hide_threshold_filter = warp::any().map(move || flag_hide_threshold);
+
+    let privilege_thresholds = config.privilege_thresholds;
+    let privilege_thresholds_filter = warp::any().map(move || privilege_thresholds);
+
+    let rate_limiter = RateLimiter::new(config.rate_limits.clone(), config.trusted_proxy_hops);
+    tokio::spawn(run_bucket_sweeper(
+        rate_limiter.clone(),
+        config.rate_limit_sweep_interval,
+    ));
 
     let id_filter = warp::any().map(|| uuid::Uuid::new_v4().to_string());
 
     let cors = warp::cors()
         .allow_any_origin()
-        .allow_header("content-type")
+        .allow_headers(vec!["content-type", "authorization", "range"])
+        .expose_headers(vec!["deprecation", "sunset"])
         .allow_methods(&[
             Method::GET,
             Method::POST,
@@ -56,60 +202,921 @@ async fn main() {
             Method::DELETE,
         ]);
 
-    let health = warp::path("health").map(|| "Alive".to_string());
+    let health = warp::path("health").and_then(health);
+
+    let api_doc = Arc::new(ApiDoc::openapi());
+    let api_doc_filter = warp::any().map(move || api_doc.clone());
+    let openapi = warp::get()
+        .and(warp::path("openapi.json"))
+        .and(warp::path::end())
+        .and(api_doc_filter)
+        .and_then(get_openapi);
+
+    let docs_config = Arc::new(utoipa_swagger_ui::Config::from("/openapi.json"));
+    let docs_config_filter = warp::any().map(move || docs_config.clone());
+    let docs = warp::get()
+        .and(warp::path("docs"))
+        .and(warp::path::full())
+        .and(warp::path::tail())
+        .and(docs_config_filter)
+        .and_then(get_docs);
+
+    // Routes are named as in `BODY_LIMITS` and `RATE_LIMITS`, for `DEPRECATIONS`
+    let deprecated = |name: &str| deprecation(&config.deprecations, name);
 
     let get_questions = warp::get()
         .and(warp::path("questions"))
         .and(warp::path::end())
         .and(warp::query())
         .and(store_filter.clone())
-        .and(id_filter.clone())
-        .and_then(get_questions);
+        .and(http_cache(&config.cache_control, "get_questions"))
+        .and(id_filter)
+        .and_then(get_questions)
+        .with(deprecated("get_questions"));
+
+    let get_similar_questions = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path("similar"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_similar_questions)
+        .with(deprecated("get_similar_questions"));
+
+    let get_question = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(http_cache(&config.cache_control, "get_question"))
+        .and(id_filter)
+        .and_then(get_question)
+        .with(deprecated("get_question"));
 
     let add_question = warp::post()
         .and(warp::path("questions"))
         .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "add_question", auth(store.clone())))
         .and(store_filter.clone())
+        .and(strict_duplicates_filter)
+        .and(body_limit(&config.body_limits, "add_question"))
         .and(warp::body::json())
-        .and(id_filter.clone())
-        .and_then(add_question);
+        .and(id_filter)
+        .and_then(|limit, session, store, strict_duplicates, question_draft, id| {
+            with_rate_limit(limit, add_question(session, store, strict_duplicates, question_draft, id))
+        })
+        .with(deprecated("add_question"));
 
     let update_question = warp::put()
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
+        .and(auth(store.clone()))
         .and(store_filter.clone())
+        .and(privilege_thresholds_filter)
+        .and(body_limit(&config.body_limits, "update_question"))
         .and(warp::body::json())
-        .and(id_filter.clone())
-        .and_then(update_question);
+        .and(id_filter)
+        .and_then(update_question)
+        .with(deprecated("update_question"));
 
     let delete_question = warp::delete()
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
+        .and(auth(store.clone()))
         .and(store_filter.clone())
-        .and(id_filter.clone())
-        .and_then(delete_question);
+        .and(privilege_thresholds_filter)
+        .and(id_filter)
+        .and_then(delete_question)
+        .with(deprecated("delete_question"));
+
+    let restore_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(restore_question)
+        .with(deprecated("restore_question"));
+
+    let close_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("close"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "close_question"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(close_question)
+        .with(deprecated("close_question"));
+
+    let reopen_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reopen"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(reopen_question)
+        .with(deprecated("reopen_question"));
+
+    let lock_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("lock"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(lock_question)
+        .with(deprecated("lock_question"));
+
+    let accept_answer = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("accept"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(accept_answer)
+        .with(deprecated("accept_answer"));
+
+    let get_answers = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("answers"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and(http_cache(&config.cache_control, "get_answers"))
+        .and(id_filter)
+        .and_then(get_answers)
+        .with(deprecated("get_answers"));
 
     let add_answer = warp::post()
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path("answers"))
         .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "add_answer", auth(store.clone())))
+        .and(store_filter.clone())
+        .and(mailer_filter.clone())
+        .and(body_limit(&config.body_limits, "add_answer"))
+        .and(warp::body::form())
+        .and(id_filter)
+        .and_then(|question_id, limit, session, store, mailer, params, id| {
+            with_rate_limit(limit, add_answer(question_id, session, store, mailer, params, id))
+        })
+        .with(deprecated("add_answer"));
+
+    let update_answer = warp::put()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth(store.clone()))
         .and(store_filter.clone())
+        .and(privilege_thresholds_filter)
+        .and(body_limit(&config.body_limits, "update_answer"))
         .and(warp::body::form())
-        .and(id_filter.clone())
-        .and_then(add_answer);
+        .and(id_filter)
+        .and_then(update_answer)
+        .with(deprecated("update_answer"));
+
+    let delete_answer = warp::delete()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(privilege_thresholds_filter)
+        .and(id_filter)
+        .and_then(delete_answer)
+        .with(deprecated("delete_answer"));
+
+    let restore_answer = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(restore_answer)
+        .with(deprecated("restore_answer"));
+
+    let get_question_revisions = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_question_revisions)
+        .with(deprecated("get_question_revisions"));
+
+    let get_question_revisions_diff = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path("diff"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_question_revisions_diff)
+        .with(deprecated("get_question_revisions_diff"));
+
+    let rollback_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("rollback"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(rollback_question)
+        .with(deprecated("rollback_question"));
+
+    let get_answer_revisions = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_answer_revisions)
+        .with(deprecated("get_answer_revisions"));
+
+    let get_answer_revisions_diff = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path("diff"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_answer_revisions_diff)
+        .with(deprecated("get_answer_revisions_diff"));
+
+    let rollback_answer = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("rollback"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(rollback_answer)
+        .with(deprecated("rollback_answer"));
+
+    let vote_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "vote_question"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(vote_question)
+        .with(deprecated("vote_question"));
+
+    let unvote_question = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(unvote_question)
+        .with(deprecated("unvote_question"));
+
+    let vote_answer = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "vote_answer"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(vote_answer)
+        .with(deprecated("vote_answer"));
+
+    let unvote_answer = warp::delete()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(unvote_answer)
+        .with(deprecated("unvote_answer"));
+
+    let add_question_comment = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "comment", auth(store.clone())))
+        .and(store_filter.clone())
+        .and(moderation_filter.clone())
+        .and(body_limit(&config.body_limits, "add_question_comment"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|question_id, limit, session, store, moderation, comment_draft, id| {
+            with_rate_limit(limit, add_question_comment(question_id, session, store, moderation, comment_draft, id))
+        })
+        .with(deprecated("add_question_comment"));
+
+    let get_question_comments = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_question_comments)
+        .with(deprecated("get_question_comments"));
+
+    let add_answer_comment = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "comment", auth(store.clone())))
+        .and(store_filter.clone())
+        .and(moderation_filter.clone())
+        .and(body_limit(&config.body_limits, "add_answer_comment"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|answer_id, limit, session, store, moderation, comment_draft, id| {
+            with_rate_limit(limit, add_answer_comment(answer_id, session, store, moderation, comment_draft, id))
+        })
+        .with(deprecated("add_answer_comment"));
+
+    let get_answer_comments = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_answer_comments)
+        .with(deprecated("get_answer_comments"));
+
+    let update_comment = warp::put()
+        .and(warp::path("comments"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "comment", auth(store.clone())))
+        .and(store_filter.clone())
+        .and(moderation_filter.clone())
+        .and(body_limit(&config.body_limits, "update_comment"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|comment_id, limit, session, store, moderation, comment_draft, id| {
+            with_rate_limit(limit, update_comment(comment_id, session, store, moderation, comment_draft, id))
+        })
+        .with(deprecated("update_comment"));
+
+    let delete_comment = warp::delete()
+        .and(warp::path("comments"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(delete_comment)
+        .with(deprecated("delete_comment"));
+
+    let add_question_attachment = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("attachments"))
+        .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "attachment", auth(store.clone())))
+        .and(store_filter.clone())
+        .and(blobs_filter.clone())
+        .and(body_limit(&config.body_limits, "add_question_attachment"))
+        .and(warp::multipart::form().max_length(route_limit(&config.body_limits, "add_question_attachment")))
+        .and(id_filter)
+        .and_then(|question_id, limit, session, store, blobs, form, id| {
+            with_rate_limit(limit, add_question_attachment(question_id, session, store, blobs, form, id))
+        })
+        .with(deprecated("add_question_attachment"));
+
+    let get_question_attachments = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("attachments"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_question_attachments)
+        .with(deprecated("get_question_attachments"));
+
+    let add_answer_attachment = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("attachments"))
+        .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "attachment", auth(store.clone())))
+        .and(store_filter.clone())
+        .and(blobs_filter.clone())
+        .and(body_limit(&config.body_limits, "add_answer_attachment"))
+        .and(warp::multipart::form().max_length(route_limit(&config.body_limits, "add_answer_attachment")))
+        .and(id_filter)
+        .and_then(|answer_id, limit, session, store, blobs, form, id| {
+            with_rate_limit(limit, add_answer_attachment(answer_id, session, store, blobs, form, id))
+        })
+        .with(deprecated("add_answer_attachment"));
+
+    let get_answer_attachments = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("attachments"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_answer_attachments)
+        .with(deprecated("get_answer_attachments"));
+
+    let get_attachment = warp::get()
+        .and(warp::path("attachments"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(blobs_filter.clone())
+        .and(warp::header::optional::<String>("range"))
+        .and(id_filter)
+        .and_then(get_attachment)
+        .with(deprecated("get_attachment"));
+
+    let delete_attachment = warp::delete()
+        .and(warp::path("attachments"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(blobs_filter.clone())
+        .and(id_filter)
+        .and_then(delete_attachment)
+        .with(deprecated("delete_attachment"));
+
+    let add_bookmark = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("bookmark"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(add_bookmark)
+        .with(deprecated("add_bookmark"));
+
+    let delete_bookmark = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("bookmark"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(delete_bookmark)
+        .with(deprecated("delete_bookmark"));
+
+    let get_bookmarks = warp::get()
+        .and(warp::path("me"))
+        .and(warp::path("bookmarks"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_bookmarks)
+        .with(deprecated("get_bookmarks"));
+
+    let follow_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("follow"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(follow_question)
+        .with(deprecated("follow_question"));
+
+    let unfollow_question = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("follow"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(unfollow_question)
+        .with(deprecated("unfollow_question"));
+
+    let follow_tag = warp::post()
+        .and(warp::path("tags"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("follow"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(follow_tag)
+        .with(deprecated("follow_tag"));
+
+    let unfollow_tag = warp::delete()
+        .and(warp::path("tags"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("follow"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(unfollow_tag)
+        .with(deprecated("unfollow_tag"));
+
+    let get_subscriptions = warp::get()
+        .and(warp::path("me"))
+        .and(warp::path("subscriptions"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_subscriptions)
+        .with(deprecated("get_subscriptions"));
+
+    let get_notifications = warp::get()
+        .and(warp::path("me"))
+        .and(warp::path("notifications"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_notifications)
+        .with(deprecated("get_notifications"));
+
+    let mark_notification_read = warp::post()
+        .and(warp::path("me"))
+        .and(warp::path("notifications"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("read"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(mark_notification_read)
+        .with(deprecated("mark_notification_read"));
 
-    let routes = get_questions
+    let mark_all_notifications_read = warp::post()
+        .and(warp::path("me"))
+        .and(warp::path("notifications"))
+        .and(warp::path("read"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(mark_all_notifications_read)
+        .with(deprecated("mark_all_notifications_read"));
+
+    let get_email_preferences = warp::get()
+        .and(warp::path("me"))
+        .and(warp::path("email-preferences"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_email_preferences)
+        .with(deprecated("get_email_preferences"));
+
+    let update_email_preferences = warp::put()
+        .and(warp::path("me"))
+        .and(warp::path("email-preferences"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "update_email_preferences"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(update_email_preferences)
+        .with(deprecated("update_email_preferences"));
+
+    let add_flag = warp::post()
+        .and(warp::path("flags"))
+        .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "flag", auth(store.clone())))
+        .and(store_filter.clone())
+        .and(flag_hide_threshold_filter)
+        .and(body_limit(&config.body_limits, "add_flag"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|limit, session, store, hide_threshold, flag_draft, id| {
+            with_rate_limit(limit, add_flag(session, store, hide_threshold, flag_draft, id))
+        })
+        .with(deprecated("add_flag"));
+
+    let get_moderation_queue = warp::get()
+        .and(warp::path("moderation"))
+        .and(warp::path("queue"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_moderation_queue)
+        .with(deprecated("get_moderation_queue"));
+
+    let moderate = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "moderate"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(moderate)
+        .with(deprecated("moderate"));
+
+    let get_moderation_log = warp::get()
+        .and(warp::path("moderation"))
+        .and(warp::path("log"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_moderation_log)
+        .with(deprecated("get_moderation_log"));
+
+    let get_events = warp::get()
+        .and(warp::path("events"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(warp::sse::last_event_id::<u64>())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_events)
+        .with(deprecated("get_events"));
+
+    let live_question = warp::path("questions")
+        .and(warp::path::param::<i32>())
+        .and(warp::path("live"))
+        .and(warp::path::end())
+        .and(warp::ws())
+        .and(ws_auth(store.clone()))
+        .and(store_filter.clone())
+        .and(live_hub_filter)
+        .and(id_filter)
+        .and_then(live_question)
+        .with(deprecated("live_question"));
+
+    let add_webhook = warp::post()
+        .and(warp::path("webhooks"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "add_webhook"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(add_webhook)
+        .with(deprecated("add_webhook"));
+
+    let get_webhooks = warp::get()
+        .and(warp::path("webhooks"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_webhooks)
+        .with(deprecated("get_webhooks"));
+
+    let delete_webhook = warp::delete()
+        .and(warp::path("webhooks"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(delete_webhook)
+        .with(deprecated("delete_webhook"));
+
+    let get_webhook_deliveries = warp::get()
+        .and(warp::path("webhooks"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("deliveries"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_webhook_deliveries)
+        .with(deprecated("get_webhook_deliveries"));
+
+    let get_reputation = warp::get()
+        .and(warp::path("users"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reputation"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_reputation)
+        .with(deprecated("get_reputation"));
+
+    let recompute_reputation = warp::post()
+        .and(warp::path("users"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reputation"))
+        .and(warp::path("recompute"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(recompute_reputation)
+        .with(deprecated("recompute_reputation"));
+
+    let get_cache_stats = warp::get()
+        .and(warp::path("cache"))
+        .and(warp::path("stats"))
+        .and(warp::path::end())
+        .and(auth(store.clone()))
+        .and(store_filter.clone())
+        .and(id_filter)
+        .and_then(get_cache_stats)
+        .with(deprecated("get_cache_stats"));
+
+    let preview = warp::post()
+        .and(warp::path("preview"))
+        .and(warp::path::end())
+        .and(account_rate_limit(rate_limiter.clone(), "preview", auth(store.clone())))
+        .and(body_limit(&config.body_limits, "preview"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|limit, session, draft, id| with_rate_limit(limit, preview(session, draft, id)))
+        .with(deprecated("preview"));
+
+    let registration = warp::post()
+        .and(warp::path("registration"))
+        .and(warp::path::end())
+        .and(rate_limit(rate_limiter.clone(), "registration"))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "registration"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|limit, store, account, id| {
+            with_rate_limit(limit, register(store, account, id))
+        })
+        .with(deprecated("registration"));
+
+    let login = warp::post()
+        .and(warp::path("login"))
+        .and(warp::path::end())
+        .and(rate_limit(rate_limiter.clone(), "login"))
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "login"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|limit, store, account, id| {
+            with_rate_limit(limit, login(store, account, id))
+        })
+        .with(deprecated("login"));
+
+    let request_password_reset = warp::post()
+        .and(warp::path("password-reset"))
+        .and(warp::path::end())
+        .and(rate_limit(rate_limiter.clone(), "password_reset"))
+        .and(store_filter.clone())
+        .and(mailer_filter)
+        .and(body_limit(&config.body_limits, "request_password_reset"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(|limit, store, mailer, request, id| {
+            with_rate_limit(limit, request_password_reset(store, mailer, request, id))
+        })
+        .with(deprecated("request_password_reset"));
+
+    let reset_password = warp::post()
+        .and(warp::path("password-reset"))
+        .and(warp::path("confirm"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(body_limit(&config.body_limits, "reset_password"))
+        .and(warp::body::json())
+        .and(id_filter)
+        .and_then(reset_password)
+        .with(deprecated("reset_password"));
+
+    let v1 = get_questions
+        .or(get_similar_questions)
+        .or(get_question)
         .or(add_question)
         .or(update_question)
         .or(delete_question)
+        .or(restore_question)
+        .or(close_question)
+        .or(reopen_question)
+        .or(lock_question)
+        .or(accept_answer)
+        .or(get_answers)
         .or(add_answer)
+        .or(update_answer)
+        .or(delete_answer)
+        .or(restore_answer)
+        .or(get_question_revisions)
+        .or(get_question_revisions_diff)
+        .or(rollback_question)
+        .or(get_answer_revisions)
+        .or(get_answer_revisions_diff)
+        .or(rollback_answer)
+        .or(vote_question)
+        .or(unvote_question)
+        .or(vote_answer)
+        .or(unvote_answer)
+        .or(add_question_comment)
+        .or(get_question_comments)
+        .or(add_answer_comment)
+        .or(get_answer_comments)
+        .or(update_comment)
+        .or(delete_comment)
+        .or(add_question_attachment)
+        .or(get_question_attachments)
+        .or(add_answer_attachment)
+        .or(get_answer_attachments)
+        .or(get_attachment)
+        .or(delete_attachment)
+        .or(add_bookmark)
+        .or(delete_bookmark)
+        .or(get_bookmarks)
+        .or(follow_question)
+        .or(unfollow_question)
+        .or(follow_tag)
+        .or(unfollow_tag)
+        .or(get_subscriptions)
+        .or(get_notifications)
+        .or(mark_notification_read)
+        .or(mark_all_notifications_read)
+        .or(get_email_preferences)
+        .or(update_email_preferences)
+        .or(add_flag)
+        .or(get_moderation_queue)
+        .or(moderate)
+        .or(get_moderation_log)
+        .or(get_events)
+        .or(live_question)
+        .or(add_webhook)
+        .or(get_webhooks)
+        .or(delete_webhook)
+        .or(get_webhook_deliveries)
+        .or(get_reputation)
+        .or(recompute_reputation)
+        .or(get_cache_stats)
+        .or(preview)
+        .or(registration)
+        .or(login)
+        .or(request_password_reset)
+        .or(reset_password);
+
+    // The routes served without a prefix before versioning are kept until their sunset
+    let api = version(ApiVersion::V1)
+        .and(v1.clone().with(deprecation(&config.deprecations, ApiVersion::V1.prefix())))
+        .or(v1.with(deprecation(&config.deprecations, "unversioned")));
+
+    let routes = api
         .or(health)
+        .or(openapi)
+        .or(docs)
         .with(cors)
         .with(log)
         .recover(return_error);
+    let routes = warp::header::optional::<String>("accept-encoding")
+        .and(routes)
+        .map(compress);
 
     warp::serve(routes).run(([127, 0, 0, 1], 3030)).await;
 }